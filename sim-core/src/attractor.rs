@@ -1,22 +1,54 @@
-use crate::types::NodeId;
+use crate::{tree::Tree, types::NodeId};
 use glam::Vec2;
 use rand::Rng;
 use std::f32::consts::TAU;
 
 /// A single attractor point used to guide growth or influence in the system.
 ///
-/// Each attractor has a position in 2D space, a liveness flag, and an optional
-/// owner (e.g. the node that has claimed or consumed it).
+/// Each attractor has a position in 2D space, a liveness flag, an optional
+/// owner (e.g. the node that has claimed or consumed it), and a group.
 ///
 /// ### Fields
 /// - `pos` - The position of the attractor in world coordinates.
 /// - `alive` - Whether this attractor is still active and can be used.
 /// - `owner` - Optional ID of the node that owns or has claimed this attractor.
-#[derive(Debug)]
+/// - `group` - Group index in `0..32`. An attractor only influences nodes
+///   whose [`crate::tree::TreeNode::group_mask`] contains this group.
+/// - `velocity` - Drift per unit time applied by [`AttractorSet::advance`].
+///   Zero for static attractors.
+/// - `locked` - Whether this attractor is a permanent guide that
+///   [`crate::phases::kill_phase`] never kills.
+/// - `weight` - Strength multiplier of this attractor's pull; `1.0` for
+///   an ordinary attractor. It scales the attractor's contribution in
+///   [`crate::phases::attraction_phase`]. Saves written before the field
+///   existed load with `1.0`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Attractor {
     pub pos: Vec2,
     pub alive: bool,
     pub owner: Option<NodeId>,
+    pub group: u8,
+    pub velocity: Vec2,
+    pub locked: bool,
+    #[cfg_attr(feature = "serde", serde(default = "default_weight"))]
+    pub weight: f32,
+}
+
+/// Weight of attractors deserialized without one.
+#[cfg(feature = "serde")]
+fn default_weight() -> f32 {
+    1.0
+}
+
+impl Attractor {
+    /// Returns the bit for this attractor's group in a group mask.
+    ///
+    /// Groups outside `0..32` map to `0` and therefore match no mask.
+    #[inline]
+    pub fn group_bit(&self) -> u32 {
+        1u32.checked_shl(self.group as u32).unwrap_or(0)
+    }
 }
 
 /// A collection of attractor points.
@@ -26,7 +58,8 @@ pub struct Attractor {
 ///
 /// Typical usage is to generate an `AttractorSet` at the beginning of a
 /// simulation step, then let tree nodes or agents query and claim them.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AttractorSet {
     pub points: Vec<Attractor>,
 }
@@ -34,8 +67,8 @@ pub struct AttractorSet {
 impl AttractorSet {
     /// Creates an [`AttractorSet`] from explicit positions.
     ///
-    /// Every position becomes an `Attractor` with `alive = true` and
-    /// `owner = None`.
+    /// Every position becomes an `Attractor` with `alive = true`,
+    /// `owner = None`, `group = 0`, zero velocity, and `locked = false`.
     ///
     /// ### Parameters
     /// - `positions` - A list of positions where attractors should be placed.
@@ -49,6 +82,10 @@ impl AttractorSet {
                 pos,
                 alive: true,
                 owner: None,
+                group: 0,
+                velocity: Vec2::ZERO,
+                locked: false,
+                weight: 1.0,
             })
             .collect();
 
@@ -125,6 +162,356 @@ impl AttractorSet {
 
         Self::from_positions(positions)
     }
+
+    /// Generates attractors at random spots along an open polyline.
+    ///
+    /// Each attractor picks a point uniformly by arc length, so longer
+    /// segments receive proportionally more attractors, and is then pushed
+    /// sideways (perpendicular to its segment) by a uniform offset in
+    /// `[-jitter, jitter]`. Useful for guiding a trunk along a path.
+    ///
+    /// ### Parameters
+    /// - `points` - Polyline vertices in order (not closed).
+    /// - `jitter` - Maximum perpendicular distance from the polyline.
+    /// - `count` - Number of attractors to generate.
+    /// - `rng` - Random number generator used to sample positions.
+    ///
+    /// ### Returns
+    /// An [`AttractorSet`] with `count` attractors; empty if there are
+    /// fewer than two vertices or the polyline has zero length.
+    pub fn random_along_polyline(
+        points: &[Vec2],
+        jitter: f32,
+        count: usize,
+        rng: &mut impl Rng,
+    ) -> Self {
+        // Arc length at the end of each segment.
+        let ends: Vec<f32> = points
+            .windows(2)
+            .scan(0.0, |len, w| {
+                *len += w[0].distance(w[1]);
+                Some(*len)
+            })
+            .collect();
+        let total = ends.last().copied().unwrap_or(0.0);
+        if total <= 0.0 {
+            return Self::from_positions(Vec::new());
+        }
+
+        let jitter = jitter.abs();
+        let positions = (0..count)
+            .map(|_| {
+                let s = rng.random_range(0.0..total);
+                let i = ends.partition_point(|&end| end <= s).min(ends.len() - 1);
+                let (a, b) = (points[i], points[i + 1]);
+                let start = if i == 0 { 0.0 } else { ends[i - 1] };
+                let len = ends[i] - start;
+                let along = if len > 0.0 { (s - start) / len } else { 0.0 };
+                let normal = (b - a).normalize_or_zero().perp();
+                a.lerp(b, along) + normal * rng.random_range(-jitter..=jitter)
+            })
+            .collect();
+
+        Self::from_positions(positions)
+    }
+
+    /// Generates attractors with a density following a grayscale image.
+    ///
+    /// The image is stretched over the world rectangle `world_min..world_max`,
+    /// with the top pixel row at `world_max.y` (world `+y` points up).
+    /// Each attractor picks a pixel with probability proportional to its
+    /// brightness (by binary search in a cumulative-brightness table) and
+    /// lands uniformly inside it, so brighter pixels receive proportionally
+    /// more attractors and black pixels none. The cost per attractor does
+    /// not depend on how much of the image is dark. Taking raw bytes keeps
+    /// this independent of any image crate.
+    ///
+    /// ### Parameters
+    /// - `pixels` - `width * height` brightness values, row by row, top
+    ///   row first.
+    /// - `width`, `height` - Image size in pixels.
+    /// - `world_min`, `world_max` - World rectangle the image covers.
+    /// - `count` - Number of attractors to generate.
+    /// - `rng` - Random number generator used to sample positions.
+    ///
+    /// ### Returns
+    /// An [`AttractorSet`] with `count` attractors, or an empty set if the
+    /// image is entirely black.
+    ///
+    /// ### Panics
+    /// Panics if `pixels.len() != width * height`.
+    pub fn from_image_mask(
+        pixels: &[u8],
+        width: usize,
+        height: usize,
+        world_min: Vec2,
+        world_max: Vec2,
+        count: usize,
+        rng: &mut impl Rng,
+    ) -> Self {
+        assert_eq!(pixels.len(), width * height, "mask size mismatch");
+        // cumulative[i] is the total brightness of pixels 0..=i.
+        let cumulative: Vec<u64> = pixels
+            .iter()
+            .scan(0u64, |sum, &p| {
+                *sum += p as u64;
+                Some(*sum)
+            })
+            .collect();
+        let total = cumulative.last().copied().unwrap_or(0);
+        if total == 0 {
+            return Self::from_positions(Vec::new());
+        }
+
+        let extent = world_max - world_min;
+        let positions = (0..count)
+            .map(|_| {
+                let r = rng.random_range(0..total);
+                let i = cumulative.partition_point(|&c| c <= r);
+                // Continuous image coordinates, origin at the top-left corner.
+                let u = ((i % width) as f32 + rng.random_range(0.0..1.0f32)) / width as f32;
+                let v = ((i / width) as f32 + rng.random_range(0.0..1.0f32)) / height as f32;
+                Vec2::new(world_min.x + u * extent.x, world_max.y - v * extent.y)
+            })
+            .collect();
+
+        Self::from_positions(positions)
+    }
+
+    /// Places attractors evenly along the boundary of a closed polygon.
+    ///
+    /// Starting at `vertices[0]`, an attractor is placed every `spacing`
+    /// units of arc length while walking the edges in order, including the
+    /// closing edge back to the first vertex. Growth toward such a set
+    /// traces the outline of the shape rather than filling it.
+    ///
+    /// ### Parameters
+    /// - `vertices` - Polygon corners in order (the polygon is closed
+    ///   implicitly).
+    /// - `spacing` - Distance between consecutive attractors along the
+    ///   perimeter.
+    ///
+    /// ### Returns
+    /// An [`AttractorSet`] with the boundary attractors; empty if there are
+    /// fewer than two vertices or `spacing` is not positive.
+    pub fn on_polygon_boundary(vertices: &[Vec2], spacing: f32) -> Self {
+        let mut positions = Vec::new();
+        if vertices.len() < 2 || spacing <= 0.0 {
+            return Self::from_positions(positions);
+        }
+
+        // Arc length at which the next attractor goes, relative to the current edge start.
+        let mut next = 0.0;
+        let edges = vertices.iter().zip(vertices.iter().cycle().skip(1));
+        for (&a, &b) in edges {
+            let len = a.distance(b);
+            while next < len {
+                positions.push(a.lerp(b, next / len));
+                next += spacing;
+            }
+            next -= len;
+        }
+
+        Self::from_positions(positions)
+    }
+
+    /// Places attractors on a golden-angle (phyllotaxis) spiral.
+    ///
+    /// Point `i` is placed at radius `r = scale * sqrt(i)` and angle
+    /// `θ = i * 137.5°` around `center`, like the seeds of a sunflower head.
+    /// The `sqrt` keeps the point density constant over the disk, so the
+    /// coverage is very even. No randomness is involved.
+    ///
+    /// ### Parameters
+    /// - `center` - Center of the spiral.
+    /// - `count` - Number of attractors to generate.
+    /// - `scale` - Radial spacing; the outermost point lies at
+    ///   `scale * sqrt(count - 1)`.
+    ///
+    /// ### Returns
+    /// An [`AttractorSet`] with `count` attractors along the spiral.
+    pub fn phyllotaxis(center: Vec2, count: usize, scale: f32) -> Self {
+        let golden_angle = 137.5f32.to_radians();
+        let positions = (0..count)
+            .map(|i| {
+                let r = scale * (i as f32).sqrt();
+                let theta = i as f32 * golden_angle;
+                center + Vec2::from_angle(theta) * r
+            })
+            .collect();
+
+        Self::from_positions(positions)
+    }
+
+    /// Moves every alive attractor by `velocity * dt`.
+    ///
+    /// Dead attractors stay where they were killed, so they keep marking the
+    /// spot that was consumed. Attractors with zero velocity are unaffected.
+    ///
+    /// ### Parameters
+    /// - `dt` - Time step to integrate over.
+    /// - `bounds` - Optional `(min, max)` corners; moved attractors are
+    ///   clamped to this box.
+    pub fn advance(&mut self, dt: f32, bounds: Option<(Vec2, Vec2)>) {
+        for a in self.points.iter_mut().filter(|a| a.alive) {
+            a.pos += a.velocity * dt;
+            if let Some((min, max)) = bounds {
+                a.pos = a.pos.clamp(min, max);
+            }
+        }
+    }
+
+    /// Lists alive attractors that no tree node can currently reach.
+    ///
+    /// An attractor is unreachable when the nearest node is farther away
+    /// than `influence_radius` (or the tree is empty). Such attractors never
+    /// take part in the attraction phase, so regions full of them will not
+    /// grow until the tree gets closer by other means. Group masks are not
+    /// taken into account.
+    ///
+    /// ### Parameters
+    /// - `tree` - The tree to measure distances against.
+    /// - `influence_radius` - Maximum distance at which a node is attracted.
+    ///
+    /// ### Returns
+    /// Indices into `points` of the unreachable alive attractors, in order.
+    pub fn unreachable(&self, tree: &Tree, influence_radius: f32) -> Vec<usize> {
+        let r2 = influence_radius * influence_radius;
+        self.points
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| a.alive)
+            .filter(|(_, a)| tree.find_nearest_node(a.pos).is_none_or(|(_, d2)| d2 > r2))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Axis-aligned bounding box of all attractor positions.
+    ///
+    /// Dead attractors are included, since they are still stored.
+    ///
+    /// ### Returns
+    /// The `(min, max)` corners, or `None` if the set is empty.
+    pub fn bounds(&self) -> Option<(Vec2, Vec2)> {
+        let first = self.points.first()?.pos;
+        Some(self.points.iter().fold((first, first), |(min, max), a| {
+            (min.min(a.pos), max.max(a.pos))
+        }))
+    }
+
+    /// Number of attractors that have been killed but are still stored.
+    pub fn dead_count(&self) -> usize {
+        self.points.iter().filter(|a| !a.alive).count()
+    }
+
+    /// Drops every dead attractor to reclaim memory.
+    ///
+    /// The phases skip dead attractors anyway, but still have to iterate
+    /// over them. Survivors keep their relative order. Nothing else needs
+    /// remapping: `owner` refers to tree nodes, not attractor indices.
+    pub fn retain_alive(&mut self) {
+        self.points.retain(|a| a.alive);
+    }
+
+    /// Removes every attractor inside an axis-aligned rectangle.
+    ///
+    /// Points on the boundary count as inside. Alive and dead attractors
+    /// are removed alike; the rest keep their relative order.
+    ///
+    /// ### Parameters
+    /// - `center` - Center of the rectangle region.
+    /// - `half_extents` - Half-width and half-height of the rectangle.
+    ///
+    /// ### Returns
+    /// The number of attractors removed.
+    pub fn remove_in_rect(&mut self, center: Vec2, half_extents: Vec2) -> usize {
+        let half_extents = half_extents.abs();
+        self.remove_where(|p| {
+            let d = (p - center).abs();
+            d.x <= half_extents.x && d.y <= half_extents.y
+        })
+    }
+
+    /// Removes every attractor inside an axis-aligned oval (ellipse).
+    ///
+    /// Uses the same shape as [`AttractorSet::random_in_oval`]; points on
+    /// the boundary count as inside. A zero radius removes nothing.
+    ///
+    /// ### Parameters
+    /// - `center` - Center of the oval region.
+    /// - `radii` - Radii of the oval in x and y directions.
+    ///
+    /// ### Returns
+    /// The number of attractors removed.
+    pub fn remove_in_oval(&mut self, center: Vec2, radii: Vec2) -> usize {
+        let radii = radii.abs();
+        if radii.x == 0.0 || radii.y == 0.0 {
+            return 0;
+        }
+        self.remove_where(|p| ((p - center) / radii).length_squared() <= 1.0)
+    }
+
+    /// Removes the attractors whose position matches `inside`.
+    fn remove_where(&mut self, inside: impl Fn(Vec2) -> bool) -> usize {
+        let before = self.points.len();
+        self.points.retain(|a| !inside(a.pos));
+        before - self.points.len()
+    }
+
+    /// Randomly subsamples the alive attractors down to `target`.
+    ///
+    /// Locked alive attractors are user-placed guides: they are always
+    /// kept and count toward `target`. The other surviving alive
+    /// attractors are chosen uniformly without replacement; the rest are
+    /// removed from the set, so the cost of later phases shrinks with
+    /// them. Dead attractors are kept, and the relative order of all
+    /// remaining points is preserved. Does nothing if there are already at
+    /// most `target` alive attractors.
+    ///
+    /// ### Parameters
+    /// - `target` - Maximum number of alive attractors to keep; exceeded
+    ///   only if more than `target` of them are locked.
+    /// - `rng` - Random number generator used to pick the survivors.
+    pub fn resample_to(&mut self, target: usize, rng: &mut impl Rng) {
+        let alive = self.points.iter().filter(|a| a.alive).count();
+        if alive <= target {
+            return;
+        }
+        let free: Vec<usize> = (0..self.points.len())
+            .filter(|&i| self.points[i].alive && !self.points[i].locked)
+            .collect();
+        let quota = target.saturating_sub(alive - free.len());
+
+        let mut keep = vec![false; self.points.len()];
+        for i in rand::seq::index::sample(rng, free.len(), quota) {
+            keep[free[i]] = true;
+        }
+        let mut i = 0;
+        self.points.retain(|a| {
+            let kept = !a.alive || a.locked || keep[i];
+            i += 1;
+            kept
+        });
+    }
+
+    /// Sorts the attractors spatially by `x`, then by `y`.
+    ///
+    /// This gives a reproducible order regardless of how (and in which
+    /// bursts) the attractors were spawned. Each attractor's `alive` and
+    /// `owner` fields move together with its position. Since `owner`
+    /// refers to tree nodes rather than attractor indices, no remapping
+    /// is needed.
+    ///
+    /// Comparison uses [`f32::total_cmp`], so the order is total even in
+    /// the presence of NaN coordinates.
+    pub fn sort_by_position(&mut self) {
+        self.points.sort_by(|a, b| {
+            a.pos
+                .x
+                .total_cmp(&b.pos.x)
+                .then(a.pos.y.total_cmp(&b.pos.y))
+        });
+    }
 }
 
 #[cfg(test)]
@@ -134,6 +521,16 @@ mod tests {
     use rand::SeedableRng;
     use rand::rngs::StdRng;
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn attractors_saved_without_weight_load_with_unit_weight() {
+        let json = r#"{"pos":[1.0,2.0],"alive":true,"owner":null,"group":0,
+            "velocity":[0.0,0.0],"locked":false}"#;
+        let a: Attractor = serde_json::from_str(json).unwrap();
+        assert_eq!(a.pos, Vec2::new(1.0, 2.0));
+        assert_eq!(a.weight, 1.0);
+    }
+
     #[test]
     fn from_positions_initializes_attractors_correctly() {
         let positions = vec![Vec2::new(0.0, 0.0), Vec2::new(1.0, 2.0)];
@@ -145,9 +542,22 @@ mod tests {
             assert_eq!(attractor.pos, positions[i]);
             assert!(attractor.alive);
             assert!(attractor.owner.is_none());
+            assert_eq!(attractor.group, 0);
         }
     }
 
+    #[test]
+    fn group_bit_maps_groups_to_mask_bits() {
+        let mut set = AttractorSet::from_positions(vec![Vec2::ZERO]);
+        assert_eq!(set.points[0].group_bit(), 0b1);
+
+        set.points[0].group = 3;
+        assert_eq!(set.points[0].group_bit(), 0b1000);
+
+        set.points[0].group = 40;
+        assert_eq!(set.points[0].group_bit(), 0);
+    }
+
     #[test]
     fn random_in_rect_generates_points_within_bounds() {
         let center = Vec2::new(0.0, 0.0);
@@ -208,4 +618,350 @@ mod tests {
             assert!(attractor.owner.is_none());
         }
     }
+
+    #[test]
+    fn on_polygon_boundary_places_points_on_edges_at_spacing() {
+        let square = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 10.0),
+            Vec2::new(0.0, 10.0),
+        ];
+        let set = AttractorSet::on_polygon_boundary(&square, 2.5);
+
+        // Perimeter 40 at spacing 2.5.
+        assert_eq!(set.points.len(), 16);
+
+        let eps = 1e-4;
+        for a in &set.points {
+            let p = a.pos;
+            let on_vertical =
+                (p.x.abs() < eps || (p.x - 10.0).abs() < eps) && (-eps..=10.0 + eps).contains(&p.y);
+            let on_horizontal =
+                (p.y.abs() < eps || (p.y - 10.0).abs() < eps) && (-eps..=10.0 + eps).contains(&p.x);
+            assert!(on_vertical || on_horizontal, "{p:?} is not on the boundary");
+        }
+        // Consecutive points along a straight edge are `spacing` apart.
+        for pair in set.points[..4].windows(2) {
+            assert!((pair[0].pos.distance(pair[1].pos) - 2.5).abs() < eps);
+        }
+
+        assert!(
+            AttractorSet::on_polygon_boundary(&square[..1], 1.0)
+                .points
+                .is_empty()
+        );
+        assert!(
+            AttractorSet::on_polygon_boundary(&square, 0.0)
+                .points
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn phyllotaxis_steps_by_golden_angle() {
+        let center = Vec2::new(5.0, -3.0);
+        let set = AttractorSet::phyllotaxis(center, 200, 2.0);
+
+        assert_eq!(set.points.len(), 200);
+        assert_eq!(set.points[0].pos, center);
+
+        let golden_angle = 137.5f32.to_radians();
+        for pair in set.points[1..].windows(2) {
+            let a = pair[0].pos - center;
+            let b = pair[1].pos - center;
+            let delta = a.angle_to(b).rem_euclid(TAU);
+            assert!(
+                (delta - golden_angle).abs() < 1e-3,
+                "angle step {delta} differs from golden angle {golden_angle}"
+            );
+        }
+    }
+
+    #[test]
+    fn advance_moves_alive_attractors_by_velocity() {
+        let mut set = AttractorSet::from_positions(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(20.0, 0.0),
+        ]);
+        set.points[0].velocity = Vec2::new(2.0, -1.0);
+        set.points[1].velocity = Vec2::new(1.0, 1.0);
+        set.points[1].alive = false;
+
+        set.advance(0.5, None);
+
+        assert_eq!(set.points[0].pos, Vec2::new(1.0, -0.5));
+        // Dead attractors stay put.
+        assert_eq!(set.points[1].pos, Vec2::new(10.0, 0.0));
+        // Zero velocity means no movement.
+        assert_eq!(set.points[2].pos, Vec2::new(20.0, 0.0));
+
+        set.advance(10.0, Some((Vec2::splat(-5.0), Vec2::splat(5.0))));
+        assert_eq!(set.points[0].pos, Vec2::new(5.0, -5.0));
+    }
+
+    #[test]
+    fn unreachable_reports_only_far_alive_attractors() {
+        let tree = Tree::new(Vec2::ZERO, 1.0);
+        let mut set = AttractorSet::from_positions(vec![
+            Vec2::new(0.0, 10.0),
+            Vec2::new(0.0, 500.0),
+            Vec2::new(500.0, 0.0),
+        ]);
+        set.points[2].alive = false;
+
+        assert_eq!(set.unreachable(&tree, 60.0), vec![1]);
+
+        // Without any nodes, every alive attractor is unreachable.
+        let empty = Tree { nodes: Vec::new() };
+        assert_eq!(set.unreachable(&empty, 60.0), vec![0, 1]);
+    }
+
+    #[test]
+    fn sort_by_position_orders_by_x_then_y_and_keeps_fields() {
+        let mut set = AttractorSet::from_positions(vec![
+            Vec2::new(2.0, 0.0),
+            Vec2::new(-1.0, 5.0),
+            Vec2::new(2.0, -3.0),
+            Vec2::new(0.5, 1.0),
+        ]);
+        set.points[0].alive = false;
+        set.points[2].owner = Some(7);
+
+        set.sort_by_position();
+
+        let positions: Vec<Vec2> = set.points.iter().map(|a| a.pos).collect();
+        assert_eq!(
+            positions,
+            vec![
+                Vec2::new(-1.0, 5.0),
+                Vec2::new(0.5, 1.0),
+                Vec2::new(2.0, -3.0),
+                Vec2::new(2.0, 0.0),
+            ]
+        );
+
+        // Liveness and ownership travel with their points.
+        assert_eq!(set.points[2].owner, Some(7));
+        assert!(set.points[2].alive);
+        assert!(!set.points[3].alive);
+        assert!(set.points[3].owner.is_none());
+    }
+
+    #[test]
+    fn resample_to_keeps_exactly_target_alive_original_points() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut set = AttractorSet::random_in_rect(Vec2::ZERO, Vec2::splat(100.0), 200, &mut rng);
+        set.points[0].alive = false;
+        let original: Vec<Vec2> = set.points.iter().map(|a| a.pos).collect();
+
+        set.resample_to(50, &mut rng);
+
+        assert_eq!(set.points.iter().filter(|a| a.alive).count(), 50);
+        assert_eq!(set.points.len(), 51, "dead attractors are kept");
+        assert!(set.points.iter().all(|a| original.contains(&a.pos)));
+
+        // Already below the target: unchanged.
+        let before = set.points.len();
+        set.resample_to(80, &mut rng);
+        assert_eq!(set.points.len(), before);
+    }
+
+    #[test]
+    fn resample_to_keeps_locked_attractors_and_counts_them() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut set = AttractorSet::random_in_rect(Vec2::ZERO, Vec2::splat(100.0), 100, &mut rng);
+        let guides = [7, 42, 99];
+        for &i in &guides {
+            set.points[i].locked = true;
+        }
+        let guide_pos: Vec<Vec2> = guides.iter().map(|&i| set.points[i].pos).collect();
+
+        set.resample_to(10, &mut rng);
+        assert_eq!(set.points.len(), 10);
+        assert_eq!(set.points.iter().filter(|a| a.locked).count(), 3);
+        assert!(
+            guide_pos
+                .iter()
+                .all(|p| set.points.iter().any(|a| a.pos == *p))
+        );
+
+        // A target below the number of guides still keeps every guide.
+        set.resample_to(1, &mut rng);
+        assert_eq!(set.points.len(), 3);
+        assert!(set.points.iter().all(|a| a.locked));
+    }
+
+    #[test]
+    fn bounds_cover_all_points() {
+        assert_eq!(AttractorSet::from_positions(Vec::new()).bounds(), None);
+
+        let single = AttractorSet::from_positions(vec![Vec2::new(2.0, -1.0)]);
+        assert_eq!(
+            single.bounds(),
+            Some((Vec2::new(2.0, -1.0), Vec2::new(2.0, -1.0)))
+        );
+
+        let mut set = AttractorSet::from_positions(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(5.0, -2.0),
+            Vec2::new(-1.0, 3.0),
+        ]);
+        set.points[1].alive = false;
+        assert_eq!(
+            set.bounds(),
+            Some((Vec2::new(-1.0, -2.0), Vec2::new(5.0, 3.0)))
+        );
+    }
+
+    #[test]
+    fn random_along_polyline_stays_within_jitter_of_the_path() {
+        // An L-shaped path: 30 units right, then 10 units up.
+        let path = [Vec2::ZERO, Vec2::new(30.0, 0.0), Vec2::new(30.0, 10.0)];
+        let jitter = 2.0;
+        let mut rng = StdRng::seed_from_u64(4);
+
+        let set = AttractorSet::random_along_polyline(&path, jitter, 400, &mut rng);
+
+        assert_eq!(set.points.len(), 400);
+        let dist_to_segment = |p: Vec2, a: Vec2, b: Vec2| {
+            let t = ((p - a).dot(b - a) / (b - a).length_squared()).clamp(0.0, 1.0);
+            p.distance(a.lerp(b, t))
+        };
+        let mut on_first = 0;
+        for a in &set.points {
+            let d = dist_to_segment(a.pos, path[0], path[1])
+                .min(dist_to_segment(a.pos, path[1], path[2]));
+            assert!(d <= jitter + 1e-4, "{} is {d} away", a.pos);
+            if a.pos.x < 28.0 {
+                on_first += 1;
+            }
+        }
+        // Length weighting: about 3/4 of the points lie on the longer leg.
+        assert!((240..=330).contains(&on_first), "{on_first}");
+
+        let degenerate = AttractorSet::random_along_polyline(&[Vec2::ONE], jitter, 10, &mut rng);
+        assert!(degenerate.points.is_empty());
+    }
+
+    #[test]
+    fn from_image_mask_samples_only_bright_pixels() {
+        // Only the top-right pixel is bright.
+        let mask = [0, 255, 0, 0];
+        let (min, max) = (Vec2::new(-10.0, -10.0), Vec2::new(10.0, 10.0));
+        let mut rng = StdRng::seed_from_u64(8);
+
+        let set = AttractorSet::from_image_mask(&mask, 2, 2, min, max, 200, &mut rng);
+
+        assert_eq!(set.points.len(), 200);
+        for a in &set.points {
+            assert!((0.0..=10.0).contains(&a.pos.x), "{}", a.pos);
+            assert!((0.0..=10.0).contains(&a.pos.y), "{}", a.pos);
+        }
+
+        let black = AttractorSet::from_image_mask(&[0; 4], 2, 2, min, max, 10, &mut rng);
+        assert!(black.points.is_empty());
+    }
+
+    #[test]
+    fn from_image_mask_density_follows_brightness() {
+        // Left pixel at full brightness, right pixel at a quarter.
+        let mask = [200, 50];
+        let mut rng = StdRng::seed_from_u64(3);
+        let set = AttractorSet::from_image_mask(
+            &mask,
+            2,
+            1,
+            Vec2::ZERO,
+            Vec2::new(2.0, 1.0),
+            2000,
+            &mut rng,
+        );
+        let left = set.points.iter().filter(|a| a.pos.x < 1.0).count();
+        assert!((1500..=1700).contains(&left), "{left}");
+    }
+
+    #[test]
+    fn from_image_mask_finds_a_single_bright_pixel_in_a_large_image() {
+        // One lit pixel in a 4K frame: rejection sampling would need about
+        // eight million tries per attractor.
+        let (width, height) = (3840, 2160);
+        let (x, y) = (1234, 567);
+        let mut mask = vec![0u8; width * height];
+        mask[y * width + x] = 255;
+        let max = Vec2::new(width as f32, height as f32);
+        let mut rng = StdRng::seed_from_u64(4);
+
+        let set =
+            AttractorSet::from_image_mask(&mask, width, height, Vec2::ZERO, max, 500, &mut rng);
+
+        assert_eq!(set.points.len(), 500);
+        // With one world unit per pixel, the lit pixel covers x in
+        // 1234..1235 and (counting rows from the top) y in 1592..1593.
+        for a in &set.points {
+            assert!((1234.0..=1235.0).contains(&a.pos.x), "{}", a.pos);
+            assert!((1592.0..=1593.0).contains(&a.pos.y), "{}", a.pos);
+        }
+    }
+
+    #[test]
+    fn retain_alive_drops_exactly_the_dead_in_order() {
+        let positions: Vec<Vec2> = (0..6).map(|i| Vec2::new(i as f32, 0.0)).collect();
+        let mut set = AttractorSet::from_positions(positions);
+        set.points[1].alive = false;
+        set.points[4].alive = false;
+        set.points[5].owner = Some(3);
+        assert_eq!(set.dead_count(), 2);
+
+        set.retain_alive();
+
+        let xs: Vec<f32> = set.points.iter().map(|a| a.pos.x).collect();
+        assert_eq!(xs, vec![0.0, 2.0, 3.0, 5.0]);
+        assert_eq!(set.points[3].owner, Some(3));
+        assert_eq!(set.dead_count(), 0);
+    }
+
+    #[test]
+    fn remove_in_rect_removes_only_points_inside() {
+        let mut set = AttractorSet::from_positions(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 1.0), // on the corner
+            Vec2::new(2.5, 0.0),
+            Vec2::new(-1.0, -1.5),
+            Vec2::new(0.0, 3.0),
+        ]);
+        set.points[0].alive = false;
+
+        let removed = set.remove_in_rect(Vec2::ZERO, Vec2::new(2.0, 1.0));
+
+        assert_eq!(removed, 2);
+        let left: Vec<Vec2> = set.points.iter().map(|a| a.pos).collect();
+        assert_eq!(
+            left,
+            vec![
+                Vec2::new(2.5, 0.0),
+                Vec2::new(-1.0, -1.5),
+                Vec2::new(0.0, 3.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn remove_in_oval_removes_only_points_inside() {
+        let center = Vec2::new(5.0, 5.0);
+        let radii = Vec2::new(4.0, 2.0);
+        let mut rng = StdRng::seed_from_u64(9);
+        let mut set = AttractorSet::random_in_rect(center, Vec2::new(8.0, 8.0), 400, &mut rng);
+        let inside = |p: Vec2| ((p - center) / radii).length_squared() <= 1.0;
+        let expected = set.points.iter().filter(|a| inside(a.pos)).count();
+        assert!(expected > 0);
+
+        let removed = set.remove_in_oval(center, radii);
+
+        assert_eq!(removed, expected);
+        assert_eq!(set.points.len(), 400 - expected);
+        assert!(set.points.iter().all(|a| !inside(a.pos)));
+        assert_eq!(set.remove_in_oval(center, Vec2::new(0.0, 3.0)), 0);
+    }
 }
@@ -1,10 +1,12 @@
 use glam::Vec2;
+use std::fmt;
 
 /// Which tool is currently selected for spawning objects in the scene.
 ///
 /// This enum usually backs a UI toggle (e.g. radio buttons or a dropdown)
 /// that decides what will be placed when the user clicks in the viewer.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SpawnTool {
     /// Spawn a single root node (e.g. the initial tree/root of the structure).
     RootNode,
@@ -12,6 +14,82 @@ pub enum SpawnTool {
     RectAttractors,
     /// Spawn attractors inside an oval (ellipse) defined by a center and radii.
     OvalAttractors,
+    /// Spawn attractors evenly along the outline of a rectangle defined by a
+    /// center and half-extents, so growth traces the outline.
+    RectOutlineAttractors,
+    /// Spawn attractors scattered along a line between two clicked points,
+    /// e.g. to guide a trunk up a path.
+    LineAttractors,
+    /// Spawn nothing; while the pointer is held, it acts as a temporary
+    /// attractor that steers growth toward the cursor
+    /// (see [`crate::simulation::Simulation::follow_target`]).
+    Follow,
+}
+
+/// Which node's distance decides whether an attractor is killed.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KillAnchor {
+    /// The closest node, regardless of [`Config::kill_from_kn`].
+    NearestNode,
+    /// The node the attractor influenced in the last attraction phase
+    /// (its `owner`), as in classic space colonization.
+    Owner,
+    /// The `k`-th nearest node, with `k = Config::kill_from_kn`.
+    #[default]
+    KthNode,
+}
+
+/// How an attractor's pull on a node scales with their distance.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InfluenceFalloff {
+    /// Every attractor in range pulls with weight `1`.
+    #[default]
+    Constant,
+    /// Weight `1 - d / influence_radius`: `1` at the node, fading smoothly
+    /// to `0` at the edge of the influence radius.
+    Linear,
+    /// Weight `(influence_radius / d)²`: `1` at the edge of the influence
+    /// radius, growing quickly for closer attractors.
+    InverseSquare,
+}
+
+/// Parameters for alternating (phyllotactic) branch placement.
+///
+/// When enabled via [`Config::phyllotaxy`], every new child's growth
+/// direction is rotated by `divergence` to alternate sides of the
+/// attraction direction each generation: children at even depth turn
+/// counterclockwise (left), children at odd depth clockwise (right).
+///
+/// ### Fields
+/// - `divergence` - Rotation applied to the growth direction, in radians.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhyllotaxyParams {
+    pub divergence: f32,
+}
+
+/// Parameters for stochastic splitting into two children.
+///
+/// When enabled via [`Config::branching`], an influenced node grows two
+/// children instead of one if a per-node random draw in `[0, 1)` is below
+/// `probability(node_pos)`. The two children are rotated by `±angle / 2`
+/// around the regular growth direction. The draw is a hash of `seed`, the
+/// node id, and its child count, so runs are reproducible.
+///
+/// `probability` is a plain function pointer (not a closure) so that
+/// [`Config`] stays `Copy`.
+///
+/// ### Fields
+/// - `angle` - Total angle between the two children, in radians.
+/// - `probability` - Split probability as a function of node position.
+/// - `seed` - Seed for the per-node draws.
+#[derive(Clone, Copy, Debug)]
+pub struct BranchParams {
+    pub angle: f32,
+    pub probability: fn(Vec2) -> f32,
+    pub seed: u64,
 }
 
 /// Global configuration for the tree / attractor system.
@@ -33,33 +111,158 @@ pub enum SpawnTool {
 /// - `kill_from_kn` - Same `k` index as above, but used during the
 ///   kill phase when checking whether an attractor is close enough
 ///   (within [`Config::kill_radius`]) to be marked as consumed.
+///   Only used when `kill_anchor` is [`KillAnchor::KthNode`].
+/// - `kill_anchor` - Which node's distance gates the kill test
+///   (see [`KillAnchor`]).
 /// - `influence_radius` - Maximum distance at which an attractor can
 ///   influence a node.
+/// - `falloff` - How an attractor's weight depends on its distance to the
+///   node (see [`InfluenceFalloff`]).
+/// - `max_attractor_weight` - Upper bound on a single attractor's weight,
+///   so one very close attractor can't dominate a node's direction.
+///   `f32::INFINITY` disables the clamp.
+/// - `max_influences_per_node` - Optional cap on how many attractors may
+///   pull on one node per step. Once a node reaches the cap, further
+///   attractors fall through to their next candidate node, spreading
+///   attraction across the frontier. `None` disables the cap.
+/// - `attract_leaves_only` - Whether only leaves (nodes without children)
+///   can be pulled by attractors, preventing side-sprouts from the trunk.
+/// - `attract_k_nearest` - Whether each attractor pulls on all of its
+///   `attract_from_kn + 1` nearest nodes within the influence radius,
+///   instead of only the node at rank `attract_from_kn`.
 /// - `kill_radius` - Distance threshold under which an attractor
 ///   is considered “consumed” and can be removed.
 /// - `step_len` - Step length for each growth update of a node/branch.
+///   With `adaptive_step`, the upper bound of the step.
+/// - `min_step` - Step length of a node pulled by a single full-strength
+///   influence when `adaptive_step` is set, and the lower bound of the step.
+/// - `adaptive_step` - Whether a node's step scales with the magnitude of
+///   its summed (not averaged) influence, as
+///   `clamp(min_step * |sum|, min_step, step_len)`, so densely attracted
+///   crowns grow faster than sparsely attracted tips.
 /// - `tropism` - Directional bias (e.g. gravity or wind) added to the
 ///   growth direction.
+/// - `tropism_depth_factor` - Per-generation scale of `tropism`: a node at
+///   depth `d` below its root feels `tropism * tropism_depth_factor^d`.
+///   `1` applies the same bias everywhere; values below `1` keep deep
+///   branches straighter than those near the root, values above `1` make
+///   deep branches bend more.
+/// - `require_attraction` - Whether a node whose influences cancel out
+///   (average pull shorter than [`crate::phases::MIN_ATTRACTION`]) is left
+///   alone, instead of growing purely along `tropism`.
+/// - `dedup_children` - Whether growth skips candidates that land very
+///   close to an existing child of the same node (see
+///   [`crate::tree::Tree::has_child_near`]). Disable for dense-growth experiments that
+///   need tightly spaced branching.
+/// - `min_child_spacing` - Distance under which a candidate counts as a
+///   duplicate of an existing child when `dedup_children` is set. Raise it
+///   toward `step_len` to avoid clumped tips.
+/// - `direction_damping` - How strongly a node's new growth direction is
+///   pulled toward its previous one, in `[0, 1)`. `0` disables damping.
+///   Only used by [`crate::phases::growth_phase_with_state`].
+/// - `momentum` - Blend ratio between a node's incoming edge direction and
+///   its attraction direction, in `[0, 1)`: the growth direction becomes
+///   `normalize(momentum * heading + (1 - momentum) * attraction)`, so
+///   branches sweep in arcs instead of kinking. `0` disables it.
+/// - `max_turn_angle` - Optional limit, in radians, on how far a growth
+///   direction may turn away from the node's incoming (parent → node)
+///   direction. Roots are exempt. `None` disables the limit.
+/// - `phyllotaxy` - Optional alternating left/right bias of the growth
+///   direction per generation (see [`PhyllotaxyParams`]). `None` disables it.
+/// - `avoid_crossings` - Whether growth rejects a new child whose edge would
+///   cross an existing branch, keeping the tree planar.
+/// - `max_nodes` - Optional growth budget: once the tree holds this many
+///   nodes, growth adds no more children. `None` grows without limit.
+/// - `branching` - Optional spatially varying splitting into two children
+///   (see [`BranchParams`]). `None` grows one child per node and step.
+///   Holds a function pointer, so it is skipped by the `serde` feature and
+///   deserialized configs have it set to `None`. With `serde`, every other
+///   missing key also falls back to [`Config::default`].
 ///
 /// - `spawn_tool` - Which spawning mode is currently active in the UI.
 /// - `spawn_attractors` - How many attractors to spawn in the chosen shape.
+/// - `spawn_density` - Optional attractors per unit area. When set, filled
+///   rect and oval spawns use `density * area` instead of
+///   `spawn_attractors`, so resizing the shape keeps the visual density.
 /// - `spawn_rect_half_extents` - Half-extents of the rectangle used when
-///   `spawn_tool` is [`SpawnTool::RectAttractors`].
+///   `spawn_tool` is [`SpawnTool::RectAttractors`] or
+///   [`SpawnTool::RectOutlineAttractors`].
 /// - `spawn_oval_radii` - Radii of the oval used when
 ///   `spawn_tool` is [`SpawnTool::OvalAttractors`].
+/// - `spawn_line_jitter` - Maximum sideways offset of attractors spawned
+///   with [`SpawnTool::LineAttractors`].
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Config {
     pub attract_from_kn: usize,
     pub kill_from_kn: usize,
+    pub kill_anchor: KillAnchor,
     pub influence_radius: f32,
+    pub falloff: InfluenceFalloff,
+    #[cfg_attr(feature = "serde", serde(with = "non_finite_f32"))]
+    pub max_attractor_weight: f32,
+    pub max_influences_per_node: Option<u32>,
+    pub attract_leaves_only: bool,
+    pub attract_k_nearest: bool,
     pub kill_radius: f32,
     pub step_len: f32,
+    pub min_step: f32,
+    pub adaptive_step: bool,
     pub tropism: Vec2,
+    pub tropism_depth_factor: f32,
+    pub require_attraction: bool,
+    pub dedup_children: bool,
+    pub min_child_spacing: f32,
+    pub direction_damping: f32,
+    pub momentum: f32,
+    pub max_turn_angle: Option<f32>,
+    pub phyllotaxy: Option<PhyllotaxyParams>,
+    pub avoid_crossings: bool,
+    pub max_nodes: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub branching: Option<BranchParams>,
 
     pub spawn_tool: SpawnTool,
     pub spawn_attractors: usize,
+    pub spawn_density: Option<f32>,
     pub spawn_rect_half_extents: Vec2,
     pub spawn_oval_radii: Vec2,
+    pub spawn_line_jitter: f32,
+}
+
+/// Serde adapter for floats that default to infinity, such as
+/// [`Config::max_attractor_weight`].
+///
+/// JSON has no infinities or NaN, so those are written as the strings
+/// `"inf"`, `"-inf"`, and `"NaN"` instead of being lost as `null`.
+#[cfg(feature = "serde")]
+mod non_finite_f32 {
+    use serde::{Deserialize, Deserializer, Serializer, de};
+
+    pub fn serialize<S: Serializer>(v: &f32, s: S) -> Result<S::Ok, S::Error> {
+        if v.is_finite() {
+            s.serialize_f32(*v)
+        } else {
+            s.collect_str(v)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<f32, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Number(f32),
+            Text(String),
+        }
+        match Repr::deserialize(d)? {
+            Repr::Number(v) => Ok(v),
+            Repr::Text(s) => s.parse().map_err(de::Error::custom),
+        }
+    }
 }
 
 impl Default for Config {
@@ -75,19 +278,378 @@ impl Default for Config {
         Self {
             attract_from_kn: 0,
             kill_from_kn: 0,
+            kill_anchor: KillAnchor::KthNode,
             influence_radius: 60.0,
+            falloff: InfluenceFalloff::Constant,
+            max_attractor_weight: f32::INFINITY,
+            max_influences_per_node: None,
+            attract_leaves_only: false,
+            attract_k_nearest: false,
             kill_radius: 30.0,
             step_len: 5.0,
+            min_step: 1.0,
+            adaptive_step: false,
             tropism: Vec2::new(0.0, 0.0),
+            tropism_depth_factor: 1.0,
+            require_attraction: false,
+            dedup_children: true,
+            min_child_spacing: 0.1,
+            direction_damping: 0.0,
+            momentum: 0.0,
+            max_turn_angle: None,
+            phyllotaxy: None,
+            avoid_crossings: false,
+            max_nodes: None,
+            branching: None,
 
             spawn_tool: SpawnTool::OvalAttractors,
             spawn_attractors: 100,
+            spawn_density: None,
             spawn_rect_half_extents: Vec2::new(30.0, 30.0),
             spawn_oval_radii: Vec2::new(30.0, 30.0),
+            spawn_line_jitter: 5.0,
+        }
+    }
+}
+
+impl Config {
+    /// Starts a [`ConfigBuilder`] from the defaults.
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    /// Starts a [`ConfigBuilder`] from this configuration.
+    pub fn to_builder(&self) -> ConfigBuilder {
+        ConfigBuilder { cfg: *self }
+    }
+
+    /// Checks the invariants the growth phases rely on.
+    ///
+    /// Radii and `step_len` must be positive, `kill_radius` must not
+    /// exceed `influence_radius`, and with `adaptive_step` the `min_step`
+    /// must lie in `(0, step_len]`. The `k` indices are unsigned, so they
+    /// are always valid.
+    ///
+    /// ### Returns
+    /// `Ok(())`, or the first [`ConfigError`] found.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        // `x > 0.0` is false for NaN, so NaN is rejected too.
+        let positive = |x: f32| x > 0.0;
+        if !positive(self.influence_radius) {
+            return Err(ConfigError::NonPositiveInfluenceRadius(
+                self.influence_radius,
+            ));
+        }
+        if !positive(self.kill_radius) {
+            return Err(ConfigError::NonPositiveKillRadius(self.kill_radius));
+        }
+        if self.kill_radius > self.influence_radius {
+            return Err(ConfigError::KillRadiusExceedsInfluence {
+                kill_radius: self.kill_radius,
+                influence_radius: self.influence_radius,
+            });
+        }
+        if !positive(self.step_len) {
+            return Err(ConfigError::NonPositiveStepLen(self.step_len));
+        }
+        if self.adaptive_step && !(positive(self.min_step) && self.min_step <= self.step_len) {
+            return Err(ConfigError::InvalidMinStep {
+                min_step: self.min_step,
+                step_len: self.step_len,
+            });
+        }
+        Ok(())
+    }
+
+    /// Blends this configuration toward `other`.
+    ///
+    /// Float and `Vec2` fields are interpolated linearly (as is the
+    /// phyllotaxy divergence when both sides enable it). Integer, boolean,
+    /// enum, and optional fields snap to `other` once `t >= 0.5`. A driver
+    /// can sweep `t` from `0` to `1` over a run to animate parameters.
+    ///
+    /// ### Parameters
+    /// - `other` - The configuration reached at `t = 1`.
+    /// - `t` - Blend factor, clamped to `[0, 1]`.
+    ///
+    /// ### Returns
+    /// A copy of `self` at `t = 0`, of `other` at `t = 1`, and a blend in between.
+    pub fn lerp(&self, other: &Config, t: f32) -> Config {
+        let t = t.clamp(0.0, 1.0);
+        let f = |a: f32, b: f32| lerp_f32(a, b, t);
+        let v = |a: Vec2, b: Vec2| Vec2::new(f(a.x, b.x), f(a.y, b.y));
+        let snap = if t >= 0.5 { other } else { self };
+
+        let phyllotaxy = match (self.phyllotaxy, other.phyllotaxy) {
+            (Some(a), Some(b)) => Some(PhyllotaxyParams {
+                divergence: f(a.divergence, b.divergence),
+            }),
+            _ => snap.phyllotaxy,
+        };
+
+        Config {
+            influence_radius: f(self.influence_radius, other.influence_radius),
+            max_attractor_weight: f(self.max_attractor_weight, other.max_attractor_weight),
+            kill_radius: f(self.kill_radius, other.kill_radius),
+            step_len: f(self.step_len, other.step_len),
+            min_child_spacing: f(self.min_child_spacing, other.min_child_spacing),
+            min_step: f(self.min_step, other.min_step),
+            tropism: v(self.tropism, other.tropism),
+            tropism_depth_factor: f(self.tropism_depth_factor, other.tropism_depth_factor),
+            direction_damping: f(self.direction_damping, other.direction_damping),
+            momentum: f(self.momentum, other.momentum),
+            phyllotaxy,
+            spawn_rect_half_extents: v(self.spawn_rect_half_extents, other.spawn_rect_half_extents),
+            spawn_oval_radii: v(self.spawn_oval_radii, other.spawn_oval_radii),
+            spawn_line_jitter: f(self.spawn_line_jitter, other.spawn_line_jitter),
+            ..*snap
+        }
+    }
+}
+
+/// Why a [`ConfigBuilder`] rejected its configuration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfigError {
+    /// `influence_radius` is zero, negative, or NaN.
+    NonPositiveInfluenceRadius(f32),
+    /// `kill_radius` is zero, negative, or NaN.
+    NonPositiveKillRadius(f32),
+    /// `kill_radius` is larger than `influence_radius`, so attractors
+    /// would be consumed before they could pull on anything.
+    KillRadiusExceedsInfluence {
+        kill_radius: f32,
+        influence_radius: f32,
+    },
+    /// `step_len` is zero, negative, or NaN.
+    NonPositiveStepLen(f32),
+    /// `adaptive_step` is set but `min_step` is not in `(0, step_len]`.
+    InvalidMinStep { min_step: f32, step_len: f32 },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ConfigError::NonPositiveInfluenceRadius(r) => {
+                write!(f, "influence_radius must be positive, got {r}")
+            }
+            ConfigError::NonPositiveKillRadius(r) => {
+                write!(f, "kill_radius must be positive, got {r}")
+            }
+            ConfigError::KillRadiusExceedsInfluence {
+                kill_radius,
+                influence_radius,
+            } => write!(
+                f,
+                "kill_radius ({kill_radius}) exceeds influence_radius ({influence_radius})"
+            ),
+            ConfigError::NonPositiveStepLen(s) => {
+                write!(f, "step_len must be positive, got {s}")
+            }
+            ConfigError::InvalidMinStep { min_step, step_len } => {
+                write!(
+                    f,
+                    "min_step ({min_step}) must be in (0, step_len = {step_len}]"
+                )
+            }
         }
     }
 }
 
+impl std::error::Error for ConfigError {}
+
+/// Chainable, validating construction of a [`Config`].
+///
+/// Starts from [`Config::default`] (or an existing config via
+/// [`Config::to_builder`]); each setter replaces one growth field, and
+/// [`ConfigBuilder::build`] checks the result with [`Config::validate`].
+/// The UI-only `spawn_*` fields keep their defaults. The fields of
+/// [`Config`] stay public, so direct mutation still works.
+///
+/// ### Fields
+/// - `cfg` - The configuration being built.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ConfigBuilder {
+    cfg: Config,
+}
+
+impl ConfigBuilder {
+    /// Sets [`Config::attract_from_kn`].
+    pub fn attract_from_kn(mut self, attract_from_kn: usize) -> Self {
+        self.cfg.attract_from_kn = attract_from_kn;
+        self
+    }
+
+    /// Sets [`Config::kill_from_kn`].
+    pub fn kill_from_kn(mut self, kill_from_kn: usize) -> Self {
+        self.cfg.kill_from_kn = kill_from_kn;
+        self
+    }
+
+    /// Sets [`Config::kill_anchor`].
+    pub fn kill_anchor(mut self, kill_anchor: KillAnchor) -> Self {
+        self.cfg.kill_anchor = kill_anchor;
+        self
+    }
+
+    /// Sets [`Config::influence_radius`]; must be positive.
+    pub fn influence_radius(mut self, influence_radius: f32) -> Self {
+        self.cfg.influence_radius = influence_radius;
+        self
+    }
+
+    /// Sets [`Config::falloff`].
+    pub fn falloff(mut self, falloff: InfluenceFalloff) -> Self {
+        self.cfg.falloff = falloff;
+        self
+    }
+
+    /// Sets [`Config::max_attractor_weight`].
+    pub fn max_attractor_weight(mut self, max_attractor_weight: f32) -> Self {
+        self.cfg.max_attractor_weight = max_attractor_weight;
+        self
+    }
+
+    /// Sets [`Config::max_influences_per_node`].
+    pub fn max_influences_per_node(mut self, max_influences_per_node: Option<u32>) -> Self {
+        self.cfg.max_influences_per_node = max_influences_per_node;
+        self
+    }
+
+    /// Sets [`Config::attract_leaves_only`].
+    pub fn attract_leaves_only(mut self, attract_leaves_only: bool) -> Self {
+        self.cfg.attract_leaves_only = attract_leaves_only;
+        self
+    }
+
+    /// Sets [`Config::attract_k_nearest`].
+    pub fn attract_k_nearest(mut self, attract_k_nearest: bool) -> Self {
+        self.cfg.attract_k_nearest = attract_k_nearest;
+        self
+    }
+
+    /// Sets [`Config::kill_radius`]; must be positive and at most the
+    /// influence radius.
+    pub fn kill_radius(mut self, kill_radius: f32) -> Self {
+        self.cfg.kill_radius = kill_radius;
+        self
+    }
+
+    /// Sets [`Config::step_len`]; must be positive.
+    pub fn step_len(mut self, step_len: f32) -> Self {
+        self.cfg.step_len = step_len;
+        self
+    }
+
+    /// Sets [`Config::min_step`]; with adaptive steps, must lie in
+    /// `(0, step_len]`.
+    pub fn min_step(mut self, min_step: f32) -> Self {
+        self.cfg.min_step = min_step;
+        self
+    }
+
+    /// Sets [`Config::adaptive_step`].
+    pub fn adaptive_step(mut self, adaptive_step: bool) -> Self {
+        self.cfg.adaptive_step = adaptive_step;
+        self
+    }
+
+    /// Sets [`Config::tropism`].
+    pub fn tropism(mut self, tropism: Vec2) -> Self {
+        self.cfg.tropism = tropism;
+        self
+    }
+
+    /// Sets [`Config::tropism_depth_factor`].
+    pub fn tropism_depth_factor(mut self, tropism_depth_factor: f32) -> Self {
+        self.cfg.tropism_depth_factor = tropism_depth_factor;
+        self
+    }
+
+    /// Sets [`Config::require_attraction`].
+    pub fn require_attraction(mut self, require_attraction: bool) -> Self {
+        self.cfg.require_attraction = require_attraction;
+        self
+    }
+
+    /// Sets [`Config::dedup_children`].
+    pub fn dedup_children(mut self, dedup_children: bool) -> Self {
+        self.cfg.dedup_children = dedup_children;
+        self
+    }
+
+    /// Sets [`Config::min_child_spacing`].
+    pub fn min_child_spacing(mut self, min_child_spacing: f32) -> Self {
+        self.cfg.min_child_spacing = min_child_spacing;
+        self
+    }
+
+    /// Sets [`Config::direction_damping`].
+    pub fn direction_damping(mut self, direction_damping: f32) -> Self {
+        self.cfg.direction_damping = direction_damping;
+        self
+    }
+
+    /// Sets [`Config::momentum`].
+    pub fn momentum(mut self, momentum: f32) -> Self {
+        self.cfg.momentum = momentum;
+        self
+    }
+
+    /// Sets [`Config::max_turn_angle`].
+    pub fn max_turn_angle(mut self, max_turn_angle: Option<f32>) -> Self {
+        self.cfg.max_turn_angle = max_turn_angle;
+        self
+    }
+
+    /// Sets [`Config::phyllotaxy`].
+    pub fn phyllotaxy(mut self, phyllotaxy: Option<PhyllotaxyParams>) -> Self {
+        self.cfg.phyllotaxy = phyllotaxy;
+        self
+    }
+
+    /// Sets [`Config::avoid_crossings`].
+    pub fn avoid_crossings(mut self, avoid_crossings: bool) -> Self {
+        self.cfg.avoid_crossings = avoid_crossings;
+        self
+    }
+
+    /// Sets [`Config::max_nodes`].
+    pub fn max_nodes(mut self, max_nodes: Option<usize>) -> Self {
+        self.cfg.max_nodes = max_nodes;
+        self
+    }
+
+    /// Sets [`Config::branching`].
+    pub fn branching(mut self, branching: Option<BranchParams>) -> Self {
+        self.cfg.branching = branching;
+        self
+    }
+
+    /// Validates and returns the configuration.
+    ///
+    /// ### Returns
+    /// The built [`Config`], or the first [`ConfigError`] found.
+    pub fn build(self) -> Result<Config, ConfigError> {
+        self.cfg.validate()?;
+        Ok(self.cfg)
+    }
+}
+
+/// Interpolates between two floats, returning the endpoints exactly at
+/// `t = 0` / `t = 1`. Non-finite values (e.g. a disabled clamp of
+/// `f32::INFINITY`) can't be blended and snap at `t >= 0.5` instead.
+fn lerp_f32(a: f32, b: f32, t: f32) -> f32 {
+    if t <= 0.0 || a == b {
+        a
+    } else if t >= 1.0 {
+        b
+    } else if !a.is_finite() || !b.is_finite() {
+        if t >= 0.5 { b } else { a }
+    } else {
+        a + (b - a) * t
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,20 +662,45 @@ mod tests {
         // k-NN settings
         assert_eq!(cfg.attract_from_kn, 0);
         assert_eq!(cfg.kill_from_kn, 0);
+        assert_eq!(cfg.kill_anchor, KillAnchor::KthNode);
 
         // Radii and step length
         assert_eq!(cfg.influence_radius, 60.0);
         assert_eq!(cfg.kill_radius, 30.0);
         assert_eq!(cfg.step_len, 5.0);
+        assert_eq!(cfg.min_step, 1.0);
+        assert!(!cfg.adaptive_step);
+
+        // Attraction weighting
+        assert_eq!(cfg.falloff, InfluenceFalloff::Constant);
+        assert_eq!(cfg.max_attractor_weight, f32::INFINITY);
+        assert_eq!(cfg.max_influences_per_node, None);
+        assert!(!cfg.attract_leaves_only);
+        assert!(!cfg.attract_k_nearest);
 
         // Tropism
         assert_eq!(cfg.tropism, Vec2::new(0.0, 0.0));
+        assert_eq!(cfg.tropism_depth_factor, 1.0);
+
+        // Growth
+        assert!(!cfg.require_attraction);
+        assert!(cfg.dedup_children);
+        assert_eq!(cfg.min_child_spacing, 0.1);
+        assert_eq!(cfg.direction_damping, 0.0);
+        assert_eq!(cfg.momentum, 0.0);
+        assert_eq!(cfg.max_turn_angle, None);
+        assert_eq!(cfg.phyllotaxy, None);
+        assert!(!cfg.avoid_crossings);
+        assert_eq!(cfg.max_nodes, None);
+        assert!(cfg.branching.is_none());
 
         // Spawn settings
         assert_eq!(cfg.spawn_tool, SpawnTool::OvalAttractors);
         assert_eq!(cfg.spawn_attractors, 100);
+        assert_eq!(cfg.spawn_density, None);
         assert_eq!(cfg.spawn_rect_half_extents, Vec2::new(30.0, 30.0));
         assert_eq!(cfg.spawn_oval_radii, Vec2::new(30.0, 30.0));
+        assert_eq!(cfg.spawn_line_jitter, 5.0);
     }
 
     #[test]
@@ -127,6 +714,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::clone_on_copy)]
     fn config_is_copy_and_clone() {
         let cfg = Config::default();
         let mut cfg2 = cfg; // Copy
@@ -140,6 +728,100 @@ mod tests {
         assert_eq!(cfg2.influence_radius, cfg3.influence_radius);
     }
 
+    #[test]
+    fn builder_builds_valid_configs() {
+        let cfg = Config::builder()
+            .influence_radius(40.0)
+            .kill_radius(4.0)
+            .step_len(2.0)
+            .tropism(Vec2::new(0.0, -0.1))
+            .max_nodes(Some(500))
+            .build()
+            .unwrap();
+        assert_eq!(cfg.influence_radius, 40.0);
+        assert_eq!(cfg.kill_radius, 4.0);
+        assert_eq!(cfg.step_len, 2.0);
+        assert_eq!(cfg.tropism, Vec2::new(0.0, -0.1));
+        assert_eq!(cfg.max_nodes, Some(500));
+        // Untouched fields keep their defaults.
+        assert_eq!(cfg.spawn_attractors, Config::default().spawn_attractors);
+
+        let tweaked = cfg.to_builder().momentum(0.5).build().unwrap();
+        assert_eq!(tweaked.momentum, 0.5);
+        assert_eq!(tweaked.step_len, 2.0);
+        assert!(Config::default().validate().is_ok());
+    }
+
+    #[test]
+    fn builder_reports_each_invalid_setting() {
+        let err = |b: ConfigBuilder| b.build().unwrap_err();
+
+        assert_eq!(
+            err(Config::builder().influence_radius(0.0)),
+            ConfigError::NonPositiveInfluenceRadius(0.0)
+        );
+        assert!(matches!(
+            err(Config::builder().influence_radius(f32::NAN)),
+            ConfigError::NonPositiveInfluenceRadius(_)
+        ));
+        assert_eq!(
+            err(Config::builder().kill_radius(-1.0)),
+            ConfigError::NonPositiveKillRadius(-1.0)
+        );
+        assert_eq!(
+            err(Config::builder().influence_radius(10.0).kill_radius(20.0)),
+            ConfigError::KillRadiusExceedsInfluence {
+                kill_radius: 20.0,
+                influence_radius: 10.0
+            }
+        );
+        assert_eq!(
+            err(Config::builder().step_len(0.0)),
+            ConfigError::NonPositiveStepLen(0.0)
+        );
+        let bad_min = err(Config::builder().adaptive_step(true).min_step(9.0));
+        assert_eq!(
+            bad_min,
+            ConfigError::InvalidMinStep {
+                min_step: 9.0,
+                step_len: 5.0
+            }
+        );
+        assert_eq!(
+            bad_min.to_string(),
+            "min_step (9) must be in (0, step_len = 5]"
+        );
+    }
+
+    #[test]
+    fn lerp_hits_endpoints_and_blends_floats() {
+        let a = Config::default();
+        let b = Config {
+            influence_radius: 100.0,
+            tropism: Vec2::new(0.0, -1.0),
+            attract_from_kn: 3,
+            falloff: InfluenceFalloff::InverseSquare,
+            max_attractor_weight: 5.0,
+            ..Config::default()
+        };
+
+        // Config has no PartialEq (it may hold a fn pointer); compare Debug output.
+        assert_eq!(format!("{:?}", a.lerp(&b, 0.0)), format!("{a:?}"));
+        assert_eq!(format!("{:?}", a.lerp(&b, 1.0)), format!("{b:?}"));
+
+        let mid = a.lerp(&b, 0.5);
+        assert_eq!(mid.influence_radius, 80.0);
+        assert_eq!(mid.tropism, Vec2::new(0.0, -0.5));
+        assert_eq!(mid.attract_from_kn, 3);
+        assert_eq!(mid.falloff, InfluenceFalloff::InverseSquare);
+        assert_eq!(mid.max_attractor_weight, 5.0);
+
+        let early = a.lerp(&b, 0.25);
+        assert_eq!(early.attract_from_kn, 0);
+        assert_eq!(early.falloff, InfluenceFalloff::Constant);
+        assert_eq!(early.max_attractor_weight, f32::INFINITY);
+    }
+
     #[test]
     fn influence_radius_is_not_smaller_than_kill_radius_in_default() {
         let cfg = Config::default();
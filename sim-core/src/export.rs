@@ -0,0 +1,28 @@
+//! Exporters that turn a grown tree into other formats.
+//!
+//! Submodules:
+//! - [`csv`] — CSV tables such as per-step growth metrics and node and
+//!   edge lists.
+//! - [`geojson`] — root-to-leaf paths as GeoJSON line strings.
+//! - [`lsystem`] — turtle-graphics strings for L-system tools.
+//! - [`raster`] — a small software rasterizer producing grayscale images
+//!   (e.g. for PNG export).
+//! - `scene` — whole-simulation JSON snapshots that can be resumed, and
+//!   file helpers saving a tree, attractors, and config (requires the
+//!   `serde` feature).
+//! - [`svg`] — vector images with one line per edge.
+
+pub mod csv;
+pub mod geojson;
+pub mod lsystem;
+pub mod raster;
+#[cfg(feature = "serde")]
+pub mod scene;
+pub mod svg;
+
+pub use csv::{edges_to_csv, edges_to_csv_with, history_to_csv, to_csv};
+pub use geojson::tree_to_geojson;
+pub use lsystem::tree_to_lsystem;
+#[cfg(feature = "serde")]
+pub use scene::{load_state, save_state, scene_to_json};
+pub use svg::{SvgOptions, to_svg, to_svg_with};
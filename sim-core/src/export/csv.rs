@@ -0,0 +1,139 @@
+//! Comma-separated exports for spreadsheets and plotting tools.
+
+use crate::{edge_buffer::EdgeBuffer, simulation::FrameStats, tree::Tree};
+use std::fmt::Write;
+
+/// Formats per-step growth metrics as CSV.
+///
+/// The output has a header row followed by one row per frame, with the
+/// columns `step,node_count,leaf_count,alive_attractors,total_length`.
+/// Rows are written in the order given; lines end with `\n`.
+///
+/// ### Parameters
+/// - `frames` - Metrics recorded after each step (see
+///   [`crate::simulation::Simulation::frame_stats`]).
+///
+/// ### Returns
+/// The CSV document as a string.
+pub fn history_to_csv(frames: &[FrameStats]) -> String {
+    let mut out = String::from("step,node_count,leaf_count,alive_attractors,total_length\n");
+    for f in frames {
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{}",
+            f.step, f.node_count, f.leaf_count, f.alive_attractors, f.total_length
+        );
+    }
+    out
+}
+
+/// Formats every node of a tree as CSV.
+///
+/// The output has the header `id,x,y,radius,parent` followed by one row
+/// per node in id order. The `parent` column is empty for roots and free
+/// nodes. Lines end with `\n`.
+///
+/// ### Parameters
+/// - `tree` - The tree to dump.
+///
+/// ### Returns
+/// The CSV document as a string.
+pub fn to_csv(tree: &Tree) -> String {
+    let mut out = String::from("id,x,y,radius,parent\n");
+    for (id, node) in tree.nodes.iter().enumerate() {
+        let _ = write!(out, "{id},{},{},{},", node.pos.x, node.pos.y, node.radius);
+        if let Some(p) = node.parent {
+            let _ = write!(out, "{p}");
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Formats every edge of a tree as CSV.
+///
+/// The output has the header `parent_id,child_id` followed by one row per
+/// parent–child link, ordered by child id.
+///
+/// ### Parameters
+/// - `tree` - The tree to dump.
+///
+/// ### Returns
+/// The CSV document as a string.
+pub fn edges_to_csv(tree: &Tree) -> String {
+    edges_to_csv_with(&tree.edge_buffer())
+}
+
+/// Like [`edges_to_csv`], reading the links from an already built
+/// [`EdgeBuffer`].
+///
+/// ### Parameters
+/// - `edges` - Edges of the tree to dump.
+///
+/// ### Returns
+/// The CSV document as a string.
+pub fn edges_to_csv_with(edges: &EdgeBuffer) -> String {
+    let mut out = String::from("parent_id,child_id\n");
+    for &(p, id) in edges.links() {
+        let _ = writeln!(out, "{p},{id}");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Tree;
+    use crate::{attractor::AttractorSet, config::Config, simulation::Simulation};
+    use glam::Vec2;
+
+    #[test]
+    fn node_and_edge_csv_list_every_node_and_link() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 2.0);
+        let a = tree.add_child(0, Vec2::new(1.5, 2.0), 1.0);
+        tree.add_child(a, Vec2::new(-0.25, 3.0), 0.5);
+
+        let csv = to_csv(&tree);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "id,x,y,radius,parent");
+        assert_eq!(lines.len() - 1, tree.nodes.len());
+        assert_eq!(lines[1], "0,0,0,2,");
+        assert_eq!(lines[2], "1,1.5,2,1,0");
+        assert_eq!(lines[3], "2,-0.25,3,0.5,1");
+
+        let edges = edges_to_csv(&tree);
+        assert_eq!(edges, "parent_id,child_id\n0,1\n1,2\n");
+    }
+
+    #[test]
+    fn history_csv_has_header_and_one_row_per_frame() {
+        let mut sim = Simulation::new(Config::default());
+        sim.add_root(Vec2::ZERO, 1.0);
+        sim.attractors =
+            AttractorSet::from_positions(vec![Vec2::new(0.0, 40.0), Vec2::new(20.0, 30.0)]);
+
+        let mut frames = vec![sim.frame_stats()];
+        for _ in 0..4 {
+            sim.step();
+            frames.push(sim.frame_stats());
+        }
+
+        let csv = history_to_csv(&frames);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 1 + frames.len());
+        assert_eq!(
+            lines[0],
+            "step,node_count,leaf_count,alive_attractors,total_length"
+        );
+
+        let steps: Vec<usize> = lines[1..]
+            .iter()
+            .map(|l| l.split(',').next().unwrap().parse().unwrap())
+            .collect();
+        assert_eq!(steps, vec![0, 1, 2, 3, 4]);
+        for (line, f) in lines[1..].iter().zip(&frames) {
+            assert_eq!(line.split(',').count(), 5);
+            assert!(line.starts_with(&format!("{},{},", f.step, f.node_count)));
+        }
+    }
+}
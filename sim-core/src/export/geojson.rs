@@ -0,0 +1,118 @@
+//! GeoJSON export, for dropping a tree onto a map.
+
+use crate::tree::Tree;
+use glam::Vec2;
+use std::fmt::Write;
+
+/// Formats every root-to-leaf path of `tree` as a GeoJSON `LineString`.
+///
+/// The result is a `FeatureCollection` with one feature per leaf that
+/// has a parent, in leaf id order. Each feature's coordinates run from
+/// the root down to the leaf and its properties hold the `leaf` id and
+/// the path's `depth` (number of edges). Isolated nodes have no path
+/// and are skipped.
+///
+/// World positions map linearly to `[longitude, latitude]`: the world
+/// origin lands on `origin` and one world unit spans `scale` degrees on
+/// both axes, with world `+y` pointing north.
+///
+/// ### Parameters
+/// - `tree` - The tree to export.
+/// - `origin` - `(longitude, latitude)` of the world origin, in degrees.
+/// - `scale` - Degrees per world unit.
+///
+/// ### Returns
+/// The GeoJSON document as a string.
+pub fn tree_to_geojson(tree: &Tree, origin: Vec2, scale: f32) -> String {
+    let mut out = String::from("{\"type\":\"FeatureCollection\",\"features\":[");
+    let mut first = true;
+    for (leaf, node) in tree.nodes.iter().enumerate() {
+        if !node.children.is_empty() || node.parent.is_none() {
+            continue;
+        }
+
+        // Walk up to the root, then emit the path root-first.
+        let mut path = vec![leaf];
+        while let Some(p) = tree.nodes[*path.last().unwrap()].parent {
+            path.push(p);
+        }
+        path.reverse();
+
+        if !first {
+            out.push(',');
+        }
+        first = false;
+        out.push_str(
+            "{\"type\":\"Feature\",\"geometry\":{\"type\":\"LineString\",\"coordinates\":[",
+        );
+        for (i, &id) in path.iter().enumerate() {
+            let lon_lat = origin + tree.nodes[id].pos * scale;
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(out, "[{},{}]", lon_lat.x, lon_lat.y);
+        }
+        let _ = write!(
+            out,
+            "]}},\"properties\":{{\"leaf\":{leaf},\"depth\":{}}}}}",
+            path.len() - 1
+        );
+    }
+    out.push_str("]}");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_linestring_per_root_to_leaf_path() {
+        // Root with a fork: 0 -> 1 -> {2, 3}, plus 0 -> 4, and a lone free node.
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        tree.add_child(a, Vec2::new(-1.0, 2.0), 1.0);
+        tree.add_child(a, Vec2::new(1.0, 2.0), 1.0);
+        tree.add_child(0, Vec2::new(2.0, 0.0), 1.0);
+        tree.add_free_node(Vec2::new(5.0, 5.0), 1.0);
+
+        let json = tree_to_geojson(&tree, Vec2::new(10.0, 50.0), 0.5);
+        let doc: serde_json::Value = serde_json::from_str(&json).expect("valid JSON");
+
+        assert_eq!(
+            doc.get("type").unwrap().as_str().unwrap(),
+            "FeatureCollection"
+        );
+        let features = doc.get("features").unwrap().as_array().unwrap();
+        assert_eq!(features.len(), 3);
+
+        let first = &features[0];
+        let props = first.get("properties").unwrap();
+        assert_eq!(props.get("leaf").unwrap().as_u64().unwrap(), 2);
+        assert_eq!(props.get("depth").unwrap().as_u64().unwrap(), 2);
+        let geometry = first.get("geometry").unwrap();
+        assert_eq!(
+            geometry.get("type").unwrap().as_str().unwrap(),
+            "LineString"
+        );
+        let coords: Vec<Vec2> = geometry
+            .get("coordinates")
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| {
+                let c = c.as_array().unwrap();
+                Vec2::new(c[0].as_f64().unwrap() as f32, c[1].as_f64().unwrap() as f32)
+            })
+            .collect();
+        assert_eq!(
+            coords,
+            vec![
+                Vec2::new(10.0, 50.0),
+                Vec2::new(10.0, 50.5),
+                Vec2::new(9.5, 51.0)
+            ]
+        );
+    }
+}
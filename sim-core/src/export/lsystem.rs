@@ -0,0 +1,156 @@
+//! Lindenmayer-style turtle strings, for handing trees to L-system tools.
+
+use crate::{tree::Tree, types::NodeId};
+
+/// Turn angle `δ` of one `+` or `-` symbol, in degrees.
+///
+/// Edge directions are rounded to multiples of this angle, so a turtle
+/// interpreting the string with the same `δ` redraws the tree's shape.
+pub const LSYSTEM_ANGLE_STEP: f32 = 5.0;
+
+/// Number of turn steps in a full circle.
+const STEPS_PER_TURN: i32 = (360.0 / LSYSTEM_ANGLE_STEP) as i32;
+
+/// Formats `tree` as a turtle-graphics string.
+///
+/// The turtle starts at a root heading up (world `+y`). Every edge becomes
+/// one `F` (move forward one unit, drawing), preceded by the turns that
+/// bring the turtle's heading to the edge's direction rounded to
+/// [`LSYSTEM_ANGLE_STEP`]: `+` turns left (counterclockwise) and `-` turns
+/// right, always the shorter way round. At a fork every child but the last
+/// is wrapped in `[` / `]` (push / pop the turtle state); the last child
+/// continues inline. Children keep their id order.
+///
+/// Edge lengths are not encoded, so the reconstruction is approximate:
+/// trees grown with a fixed step length keep their proportions. If the
+/// tree has several roots with children, each root's subtree is emitted
+/// as its own bracketed block starting from the turtle's origin; isolated
+/// nodes produce no symbols.
+///
+/// ### Parameters
+/// - `tree` - The tree to export.
+///
+/// ### Returns
+/// The turtle string, with exactly one `F` per edge.
+pub fn tree_to_lsystem(tree: &Tree) -> String {
+    /// Pending output, processed last-in first-out.
+    enum Item {
+        /// Emit the subtree below `node`, with the turtle heading `heading`.
+        Visit(NodeId, i32),
+        /// Turn toward `child`'s edge, draw it, then visit `child`.
+        Edge(NodeId, i32),
+        Symbol(char),
+    }
+
+    let roots: Vec<NodeId> = (0..tree.nodes.len())
+        .filter(|&id| tree.nodes[id].parent.is_none() && !tree.nodes[id].children.is_empty())
+        .collect();
+    let bracket_roots = roots.len() > 1;
+
+    let mut out = String::new();
+    let mut stack = Vec::new();
+    for &root in roots.iter().rev() {
+        if bracket_roots {
+            stack.push(Item::Symbol(']'));
+        }
+        stack.push(Item::Visit(root, 0));
+        if bracket_roots {
+            stack.push(Item::Symbol('['));
+        }
+    }
+
+    while let Some(item) = stack.pop() {
+        match item {
+            Item::Symbol(c) => out.push(c),
+            Item::Visit(node, heading) => {
+                let Some((&last, rest)) = tree.nodes[node].children.split_last() else {
+                    continue;
+                };
+                // Popped in order: [first] [second] ... last.
+                stack.push(Item::Edge(last, heading));
+                for &child in rest.iter().rev() {
+                    stack.push(Item::Symbol(']'));
+                    stack.push(Item::Edge(child, heading));
+                    stack.push(Item::Symbol('['));
+                }
+            }
+            Item::Edge(child, heading) => {
+                let parent = tree.nodes[child].parent.expect("edge child has a parent");
+                let target = quantized_heading(tree, parent, child).unwrap_or(heading);
+                let turn = (target - heading).rem_euclid(STEPS_PER_TURN);
+                let turn = if turn > STEPS_PER_TURN / 2 {
+                    turn - STEPS_PER_TURN
+                } else {
+                    turn
+                };
+                let symbol = if turn > 0 { '+' } else { '-' };
+                out.extend(std::iter::repeat_n(symbol, turn.unsigned_abs() as usize));
+                out.push('F');
+                stack.push(Item::Visit(child, target));
+            }
+        }
+    }
+    out
+}
+
+/// Direction of the edge `parent -> child` as a whole number of turn
+/// steps counterclockwise from `+y`, or `None` for a zero-length edge.
+fn quantized_heading(tree: &Tree, parent: NodeId, child: NodeId) -> Option<i32> {
+    let d = tree.nodes[child].pos - tree.nodes[parent].pos;
+    if d == glam::Vec2::ZERO {
+        return None;
+    }
+    let degrees = (-d.x).atan2(d.y).to_degrees();
+    Some((degrees / LSYSTEM_ANGLE_STEP).round() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec2;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn fork_turns_both_ways_and_branches_all_but_the_last_child() {
+        // A trunk going up, then forking 45° left and right.
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        tree.add_child(a, Vec2::new(-1.0, 2.0), 1.0);
+        tree.add_child(a, Vec2::new(1.0, 2.0), 1.0);
+
+        assert_eq!(
+            tree_to_lsystem(&tree),
+            format!("F[{}F]{}F", "+".repeat(9), "-".repeat(9))
+        );
+    }
+
+    #[test]
+    fn brackets_balance_and_every_edge_is_one_forward_step() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        for id in 1..200 {
+            let parent = rng.random_range(0..id);
+            let pos = tree.nodes[parent].pos
+                + Vec2::new(rng.random_range(-2.0..2.0), rng.random_range(-0.5..2.0));
+            tree.add_child(parent, pos, 1.0);
+        }
+        let free = tree.add_free_node(Vec2::new(50.0, 0.0), 1.0);
+        tree.add_child(free, Vec2::new(50.0, 1.0), 1.0);
+
+        let s = tree_to_lsystem(&tree);
+
+        let mut depth = 0i32;
+        for c in s.chars() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                _ => {}
+            }
+            assert!(depth >= 0, "unbalanced: {s}");
+        }
+        assert_eq!(depth, 0);
+        assert_eq!(s.matches('F').count(), tree.edges().count());
+        assert!(s.chars().all(|c| "F+-[]".contains(c)));
+    }
+}
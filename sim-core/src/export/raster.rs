@@ -0,0 +1,309 @@
+//! Software rasterization of trees into grayscale images.
+//!
+//! The rasterizer has no image-format dependencies: it produces a
+//! [`Raster`] holding one coverage byte per pixel, which callers can encode
+//! (e.g. as PNG) however they like.
+
+use crate::{edge_buffer::EdgeBuffer, tree::Tree};
+use glam::Vec2;
+
+/// Settings controlling how a tree is rasterized.
+///
+/// ### Fields
+/// - `width` - Output width in pixels.
+/// - `height` - Output height in pixels.
+/// - `margin` - Empty border around the tree, in pixels.
+/// - `antialias` - Whether edges are drawn with Wu's antialiased line
+///   algorithm (`true`) or a plain Bresenham line (`false`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderStyle {
+    pub width: usize,
+    pub height: usize,
+    pub margin: f32,
+    pub antialias: bool,
+}
+
+impl Default for RenderStyle {
+    /// A 512×512 image with an 8 pixel margin and antialiasing enabled.
+    fn default() -> Self {
+        Self {
+            width: 512,
+            height: 512,
+            margin: 8.0,
+            antialias: true,
+        }
+    }
+}
+
+/// A grayscale coverage image.
+///
+/// Each pixel stores how much of it is covered by ink, from `0` (empty)
+/// to `255` (fully covered). Pixels are stored row by row, top row first.
+///
+/// ### Fields
+/// - `width` - Image width in pixels.
+/// - `height` - Image height in pixels.
+/// - `pixels` - `width * height` coverage values.
+#[derive(Clone, Debug)]
+pub struct Raster {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Raster {
+    /// Creates an empty (all-zero) raster.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    /// Returns the coverage at `(x, y)`, or `0` outside the image.
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x]
+        } else {
+            0
+        }
+    }
+
+    /// Adds coverage to a pixel, keeping the maximum of old and new values.
+    ///
+    /// Coordinates outside the image are ignored.
+    ///
+    /// ### Parameters
+    /// - `x`, `y` - Pixel coordinates (may be negative).
+    /// - `coverage` - Coverage in `[0, 1]`.
+    fn plot(&mut self, x: i64, y: i64, coverage: f32) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+        let idx = y as usize * self.width + x as usize;
+        let v = (coverage.clamp(0.0, 1.0) * 255.0).round() as u8;
+        self.pixels[idx] = self.pixels[idx].max(v);
+    }
+
+    /// Draws a line between two points given in pixel coordinates.
+    ///
+    /// ### Parameters
+    /// - `a`, `b` - Line end points in pixel space.
+    /// - `antialias` - Use Wu's algorithm instead of Bresenham.
+    pub fn draw_line(&mut self, a: Vec2, b: Vec2, antialias: bool) {
+        if antialias {
+            self.draw_line_wu(a, b);
+        } else {
+            self.draw_line_bresenham(a, b);
+        }
+    }
+
+    /// Plain Bresenham line: every touched pixel is fully covered.
+    fn draw_line_bresenham(&mut self, a: Vec2, b: Vec2) {
+        let (mut x0, mut y0) = (a.x.round() as i64, a.y.round() as i64);
+        let (x1, y1) = (b.x.round() as i64, b.y.round() as i64);
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+
+        loop {
+            self.plot(x0, y0, 1.0);
+            if x0 == x1 && y0 == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x0 += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y0 += sy;
+            }
+        }
+    }
+
+    /// Xiaolin Wu's antialiased line.
+    ///
+    /// Along the major axis, each step covers the two pixels straddling the
+    /// ideal line, weighted by their distance to it. End points are
+    /// weighted by their fractional overlap.
+    fn draw_line_wu(&mut self, a: Vec2, b: Vec2) {
+        let (mut x0, mut y0, mut x1, mut y1) = (a.x, a.y, b.x, b.y);
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            std::mem::swap(&mut x0, &mut y0);
+            std::mem::swap(&mut x1, &mut y1);
+        }
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+
+        let dx = x1 - x0;
+        let gradient = if dx == 0.0 { 1.0 } else { (y1 - y0) / dx };
+
+        // Plot with axes swapped back for steep lines.
+        let mut plot = |x: f32, y: f32, c: f32| {
+            if steep {
+                self.plot(y as i64, x as i64, c);
+            } else {
+                self.plot(x as i64, y as i64, c);
+            }
+        };
+
+        // First end point.
+        let xend = x0.round();
+        let yend = y0 + gradient * (xend - x0);
+        let xgap = 1.0 - (x0 + 0.5).fract();
+        let xpx0 = xend;
+        let ypx0 = yend.floor();
+        plot(xpx0, ypx0, (1.0 - yend.fract()) * xgap);
+        plot(xpx0, ypx0 + 1.0, yend.fract() * xgap);
+        let mut intery = yend + gradient;
+
+        // Second end point.
+        let xend = x1.round();
+        let yend = y1 + gradient * (xend - x1);
+        let xgap = (x1 + 0.5).fract();
+        let xpx1 = xend;
+        let ypx1 = yend.floor();
+        plot(xpx1, ypx1, (1.0 - yend.fract()) * xgap);
+        plot(xpx1, ypx1 + 1.0, yend.fract() * xgap);
+
+        // Main loop between the end points.
+        let mut x = xpx0 + 1.0;
+        while x < xpx1 {
+            let y = intery.floor();
+            plot(x, y, 1.0 - intery.fract());
+            plot(x, y + 1.0, intery.fract());
+            intery += gradient;
+            x += 1.0;
+        }
+    }
+}
+
+/// Rasterizes all parent–child edges of a tree.
+///
+/// The tree's node bounding box is scaled uniformly to fit inside the image
+/// minus `style.margin` on every side and centered. World `+y` points up,
+/// so the image is flipped vertically.
+///
+/// ### Parameters
+/// - `tree` - The tree to draw.
+/// - `style` - Output size, margin, and antialiasing settings.
+///
+/// ### Returns
+/// A [`Raster`] of size `style.width × style.height`. An empty tree
+/// produces an empty image.
+pub fn rasterize_tree(tree: &Tree, style: &RenderStyle) -> Raster {
+    rasterize_tree_with(tree, &tree.edge_buffer(), style)
+}
+
+/// Like [`rasterize_tree`], drawing the edges from an already built
+/// [`EdgeBuffer`] of `tree`.
+///
+/// ### Parameters
+/// - `tree` - The tree to draw; its nodes set the bounding box.
+/// - `edges` - Edges of exactly this tree.
+/// - `style` - Output size, margin, and antialiasing settings.
+///
+/// ### Returns
+/// A [`Raster`] of size `style.width × style.height`.
+pub fn rasterize_tree_with(tree: &Tree, edges: &EdgeBuffer, style: &RenderStyle) -> Raster {
+    let mut raster = Raster::new(style.width, style.height);
+    if tree.nodes.is_empty() {
+        return raster;
+    }
+
+    let mut min = Vec2::splat(f32::MAX);
+    let mut max = Vec2::splat(f32::MIN);
+    for node in &tree.nodes {
+        min = min.min(node.pos);
+        max = max.max(node.pos);
+    }
+
+    let size = Vec2::new(style.width as f32, style.height as f32);
+    let avail = (size - Vec2::splat(2.0 * style.margin)).max(Vec2::ONE);
+    let extent = (max - min).max(Vec2::splat(f32::EPSILON));
+    let scale = (avail.x / extent.x).min(avail.y / extent.y);
+    let center = (min + max) * 0.5;
+
+    let to_pixel = |p: Vec2| {
+        let local = (p - center) * scale;
+        Vec2::new(size.x * 0.5 + local.x, size.y * 0.5 - local.y)
+    };
+
+    for &(a, b) in edges.segments() {
+        raster.draw_line(to_pixel(a), to_pixel(b), style.antialias);
+    }
+
+    raster
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec2;
+
+    /// Returns true if the raster contains a value strictly between 0 and 255.
+    fn has_partial_coverage(raster: &Raster) -> bool {
+        raster.pixels.iter().any(|&v| v > 0 && v < 255)
+    }
+
+    #[test]
+    fn aliased_line_is_binary() {
+        let mut raster = Raster::new(32, 32);
+        raster.draw_line(Vec2::new(2.0, 3.0), Vec2::new(28.0, 14.0), false);
+
+        assert!(raster.pixels.iter().all(|&v| v == 0 || v == 255));
+        assert_eq!(raster.get(2, 3), 255);
+        assert_eq!(raster.get(28, 14), 255);
+    }
+
+    #[test]
+    fn antialiased_diagonal_has_intermediate_pixels_next_to_line() {
+        let mut raster = Raster::new(32, 32);
+        raster.draw_line(Vec2::new(2.0, 3.0), Vec2::new(28.0, 14.0), true);
+
+        assert!(has_partial_coverage(&raster));
+
+        // Sample a column in the middle: the line's ideal y there is
+        // 3 + 11 * (15 - 2) / 26 = 8.5, so both neighbors share coverage.
+        let above = raster.get(15, 8);
+        let below = raster.get(15, 9);
+        assert!(above > 0 && above < 255, "above = {above}");
+        assert!(below > 0 && below < 255, "below = {below}");
+    }
+
+    #[test]
+    fn rasterize_tree_respects_style() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        tree.add_child(0, Vec2::new(10.0, 4.0), 1.0);
+
+        let mut style = RenderStyle {
+            width: 64,
+            height: 48,
+            margin: 4.0,
+            antialias: false,
+        };
+        let raster = rasterize_tree(&tree, &style);
+        assert_eq!(raster.pixels.len(), 64 * 48);
+        assert!(raster.pixels.contains(&255));
+        assert!(!has_partial_coverage(&raster));
+
+        style.antialias = true;
+        let raster = rasterize_tree(&tree, &style);
+        assert!(has_partial_coverage(&raster));
+    }
+
+    #[test]
+    fn rasterize_empty_tree_is_blank() {
+        let tree = Tree { nodes: Vec::new() };
+        let raster = rasterize_tree(&tree, &RenderStyle::default());
+        assert!(raster.pixels.iter().all(|&v| v == 0));
+    }
+}
@@ -0,0 +1,326 @@
+//! Whole-scene snapshots as JSON, for pausing, sharing, and resuming runs.
+//!
+//! A scene bundles the tree, every attractor (including dead ones), the
+//! configuration, the per-node growth history, and the step counter. A
+//! simulation rebuilt from a scene continues exactly like the original:
+//! stepping is deterministic, so no RNG state has to be stored, and
+//! `serde_json` writes floats in their shortest round-trip form so every
+//! value is restored bit for bit.
+//!
+//! The documents are the `serde` encodings of [`Config`], [`TreeNode`],
+//! and [`Attractor`], so this module requires the `serde` feature.
+//! [`Config::branching`] holds a function pointer and cannot be stored;
+//! reloaded scenes have branching disabled.
+
+use crate::{
+    attractor::{Attractor, AttractorSet},
+    config::Config,
+    phases::GrowthState,
+    simulation::Simulation,
+    tree::{Tree, TreeNode},
+};
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Format version written to and expected in the `"version"` field.
+const SCENE_VERSION: u64 = 1;
+
+/// Error returned when a scene document can't be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SceneError(pub String);
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid scene: {}", self.0)
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<serde_json::Error> for SceneError {
+    fn from(e: serde_json::Error) -> Self {
+        SceneError(e.to_string())
+    }
+}
+
+/// A scene document as written by [`scene_to_json`].
+///
+/// ### Fields
+/// - `version` - Format version, always [`SCENE_VERSION`] when written.
+/// - `steps` - Growth steps taken so far.
+/// - `config` - The configuration. Missing keys fall back to
+///   [`Config::default`].
+/// - `nodes` - The tree's nodes, indexed by id.
+/// - `growth` - Last growth direction per node id (see [`GrowthState`]).
+/// - `attractors` - Every attractor, including dead ones.
+#[derive(Serialize, Deserialize)]
+struct Scene<N, G, A> {
+    version: u64,
+    steps: usize,
+    config: Config,
+    nodes: N,
+    growth: G,
+    attractors: A,
+}
+
+/// A borrowed scene, serialized without copying the simulation.
+type SceneRef<'a> = Scene<&'a [TreeNode], &'a [Option<Vec2>], &'a [Attractor]>;
+
+/// An owned scene, as deserialized from a document.
+type SceneOwned = Scene<Vec<TreeNode>, Vec<Option<Vec2>>, Vec<Attractor>>;
+
+/// Serializes the complete state of `sim` as a JSON document.
+///
+/// ### Parameters
+/// - `sim` - The simulation to snapshot.
+///
+/// ### Returns
+/// The scene as a JSON string; load it with [`Simulation::from_scene_json`].
+pub fn scene_to_json(sim: &Simulation) -> String {
+    write_scene(
+        sim.stats().steps,
+        &sim.tree,
+        &sim.attractors,
+        &sim.cfg,
+        sim.growth_state().directions(),
+    )
+}
+
+/// Saves a tree, its attractors, and a configuration to a JSON file.
+///
+/// The file is a regular scene document with a step counter of `0` and no
+/// growth history, so it can also be loaded with [`scene_from_json`].
+///
+/// ### Parameters
+/// - `path` - Output file path.
+/// - `tree` - The tree to save; node ids and links are stored as-is.
+/// - `attractors` - The attractors to save, including dead ones.
+/// - `cfg` - The configuration to save.
+///
+/// ### Returns
+/// - `Ok(())` if the file was written, or
+/// - `Err` if it could not be created or written.
+pub fn save_state(
+    path: impl AsRef<Path>,
+    tree: &Tree,
+    attractors: &AttractorSet,
+    cfg: &Config,
+) -> io::Result<()> {
+    fs::write(path, write_scene(0, tree, attractors, cfg, &[]))
+}
+
+/// Loads a tree, its attractors, and a configuration saved by
+/// [`save_state`] (or any scene document).
+///
+/// ### Parameters
+/// - `path` - The file to read.
+///
+/// ### Returns
+/// - `Ok((tree, attractors, cfg))` with the restored state, or
+/// - `Err` if the file can't be read, or with
+///   [`io::ErrorKind::InvalidData`] if it isn't a valid scene.
+pub fn load_state(path: impl AsRef<Path>) -> io::Result<(Tree, AttractorSet, Config)> {
+    let json = fs::read_to_string(path)?;
+    let sim = scene_from_json(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok((sim.tree, sim.attractors, sim.cfg))
+}
+
+/// Rebuilds a simulation from a document produced by [`scene_to_json`].
+///
+/// Missing configuration keys fall back to [`Config::default`], so scenes
+/// saved before a field was added still load.
+///
+/// ### Parameters
+/// - `json` - The scene document.
+///
+/// ### Returns
+/// - `Ok(sim)` with the restored simulation, or
+/// - `Err(SceneError)` if the document is malformed or inconsistent.
+pub fn scene_from_json(json: &str) -> Result<Simulation, SceneError> {
+    let scene: SceneOwned = serde_json::from_str(json)?;
+    if scene.version != SCENE_VERSION {
+        return Err(SceneError(format!("unsupported version {}", scene.version)));
+    }
+
+    let count = scene.nodes.len();
+    let in_range = |id: usize| {
+        if id < count {
+            Ok(())
+        } else {
+            Err(SceneError(format!("node id {id} out of range")))
+        }
+    };
+    for n in &scene.nodes {
+        n.parent.map(in_range).transpose()?;
+        n.children.iter().try_for_each(|&c| in_range(c))?;
+    }
+
+    Ok(Simulation::from_parts(
+        Tree { nodes: scene.nodes },
+        AttractorSet {
+            points: scene.attractors,
+        },
+        scene.config,
+        GrowthState::from_directions(scene.growth),
+        scene.steps,
+    ))
+}
+
+/// Serializes a scene document built from the given parts.
+fn write_scene(
+    steps: usize,
+    tree: &Tree,
+    attractors: &AttractorSet,
+    cfg: &Config,
+    growth: &[Option<Vec2>],
+) -> String {
+    let scene: SceneRef = Scene {
+        version: SCENE_VERSION,
+        steps,
+        config: *cfg,
+        nodes: &tree.nodes,
+        growth,
+        attractors: &attractors.points,
+    };
+    serde_json::to_string(&scene).expect("scenes contain only JSON-encodable values")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{PhyllotaxyParams, SpawnTool};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn trees_equal(a: &Tree, b: &Tree) -> bool {
+        a.nodes.len() == b.nodes.len()
+            && a.nodes.iter().zip(&b.nodes).all(|(x, y)| {
+                x.pos == y.pos
+                    && x.radius == y.radius
+                    && x.parent == y.parent
+                    && x.children == y.children
+            })
+    }
+
+    #[test]
+    fn resumed_scene_grows_exactly_like_uninterrupted_run() {
+        let cfg = Config {
+            direction_damping: 0.4,
+            tropism: Vec2::new(0.0, 0.03),
+            phyllotaxy: Some(PhyllotaxyParams { divergence: 0.1 }),
+            ..Config::default()
+        };
+        let mut sim = Simulation::new(cfg);
+        sim.add_root(Vec2::ZERO, 1.0);
+        sim.spawn_attractors(Vec2::new(0.0, 50.0), &mut StdRng::seed_from_u64(9));
+        sim.attractors.points[0].locked = true;
+        sim.attractors.points[1].velocity = Vec2::new(0.5, -0.25);
+        for _ in 0..8 {
+            sim.step();
+        }
+
+        let json = scene_to_json(&sim);
+        let mut resumed = Simulation::from_scene_json(&json).unwrap();
+        assert_eq!(scene_to_json(&resumed), json);
+        assert_eq!(resumed.stats(), sim.stats());
+
+        for _ in 0..12 {
+            assert_eq!(resumed.step(), sim.step());
+        }
+        assert!(trees_equal(&resumed.tree, &sim.tree));
+        assert_eq!(scene_to_json(&resumed), scene_to_json(&sim));
+    }
+
+    #[test]
+    fn non_finite_floats_and_missing_config_keys_load() {
+        let sim = Simulation::new(Config::default());
+        let json = scene_to_json(&sim);
+        assert!(json.contains("\"max_attractor_weight\":\"inf\""));
+        let loaded = Simulation::from_scene_json(&json).unwrap();
+        assert_eq!(loaded.cfg.max_attractor_weight, f32::INFINITY);
+
+        let minimal = r#"{"version":1,"steps":3,"config":{"step_len":2.5},
+            "nodes":[{"pos":[0,0],"radius":1,"parent":null,"children":[],"group_mask":1}],
+            "growth":[],"attractors":[]}"#;
+        let loaded = Simulation::from_scene_json(minimal).unwrap();
+        assert_eq!(loaded.cfg.step_len, 2.5);
+        assert_eq!(loaded.cfg.kill_radius, Config::default().kill_radius);
+        assert_eq!(loaded.stats().steps, 3);
+        assert_eq!(loaded.tree.nodes.len(), 1);
+    }
+
+    #[test]
+    fn serde_encodings_round_trip_and_skip_branching() {
+        let mut tree = Tree::new(Vec2::new(1.5, -2.0), 1.0);
+        tree.add_child(0, Vec2::new(1.5, 3.25), 0.5);
+        let node_json = serde_json::to_string(&tree.nodes[1]).unwrap();
+        assert!(node_json.contains("\"pos\":[1.5,3.25]"), "{node_json}");
+        let loaded: Tree = serde_json::from_str(&serde_json::to_string(&tree).unwrap()).unwrap();
+        assert!(trees_equal(&loaded, &tree));
+
+        let cfg = Config {
+            tropism: Vec2::new(0.0, -0.5),
+            branching: Some(crate::config::BranchParams {
+                angle: 0.5,
+                probability: |_| 1.0,
+                seed: 3,
+            }),
+            ..Config::default()
+        };
+        let json = serde_json::to_string(&cfg).unwrap();
+        assert!(json.contains("\"tropism\":[0.0,-0.5]"), "{json}");
+        assert!(!json.contains("branching"));
+        let loaded: Config = serde_json::from_str(&json).unwrap();
+        assert!(loaded.branching.is_none());
+        assert_eq!(loaded.tropism, cfg.tropism);
+        assert_eq!(loaded.max_attractor_weight, f32::INFINITY);
+    }
+
+    #[test]
+    fn malformed_scenes_are_rejected() {
+        assert!(Simulation::from_scene_json("").is_err());
+        assert!(Simulation::from_scene_json("{\"version\":2}").is_err());
+        let bad_parent = r#"{"version":1,"steps":0,"config":{},
+            "nodes":[{"pos":[0,0],"radius":1,"parent":5,"children":[],"group_mask":1}],
+            "growth":[],"attractors":[]}"#;
+        assert!(Simulation::from_scene_json(bad_parent).is_err());
+    }
+
+    #[test]
+    fn saved_state_loads_with_identical_structure() {
+        let mut tree = Tree::new(Vec2::ZERO, 2.0);
+        for i in 1..50 {
+            let parent = (i - 1) / 3;
+            let pos = tree.nodes[parent].pos + Vec2::new(i as f32 * 0.1 - 2.0, 1.5);
+            tree.add_child(parent, pos, 1.0 / i as f32);
+        }
+        let mut attractors =
+            AttractorSet::from_positions(vec![Vec2::new(3.0, 7.0), Vec2::new(-1.5, 9.25)]);
+        attractors.points[1].alive = false;
+        attractors.points[1].owner = Some(42);
+        let cfg = Config {
+            step_len: 0.75,
+            adaptive_step: true,
+            spawn_tool: SpawnTool::Follow,
+            ..Config::default()
+        };
+
+        let path = std::env::temp_dir().join("sca_save_state_test.json");
+        save_state(&path, &tree, &attractors, &cfg).unwrap();
+        let (loaded, loaded_attractors, loaded_cfg) = load_state(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.nodes.len(), 50);
+        assert!(trees_equal(&loaded, &tree));
+        assert_eq!(loaded_attractors.points.len(), 2);
+        assert!(!loaded_attractors.points[1].alive);
+        assert_eq!(loaded_attractors.points[1].owner, Some(42));
+        assert_eq!(loaded_cfg.step_len, 0.75);
+        assert!(loaded_cfg.adaptive_step);
+        assert_eq!(loaded_cfg.spawn_tool, SpawnTool::Follow);
+    }
+}
@@ -0,0 +1,163 @@
+//! SVG export, for sharing a grown tree as a vector image.
+
+use crate::{edge_buffer::EdgeBuffer, tree::Tree};
+use glam::Vec2;
+use std::fmt::Write;
+
+/// Settings controlling how a tree is written as SVG.
+///
+/// ### Fields
+/// - `stroke_scale` - Stroke width per unit of branch radius; each edge is
+///   drawn with width `stroke_scale * radius` of its child node.
+/// - `stroke` - Edge color as sRGB.
+/// - `background` - Background fill as sRGB, or `None` for transparent.
+/// - `margin` - Empty border around the tree, as a fraction of the larger
+///   side of the tree's bounding box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SvgOptions {
+    pub stroke_scale: f32,
+    pub stroke: [u8; 3],
+    pub background: Option<[u8; 3]>,
+    pub margin: f32,
+}
+
+impl Default for SvgOptions {
+    /// Black strokes twice the branch radius wide on white, with a 5% margin.
+    fn default() -> Self {
+        Self {
+            stroke_scale: 2.0,
+            stroke: [0, 0, 0],
+            background: Some([255, 255, 255]),
+            margin: 0.05,
+        }
+    }
+}
+
+/// Formats `tree` as a standalone SVG document.
+///
+/// Every edge becomes one `<line>` from parent to child, in child id
+/// order. Coordinates stay in world units; world `+y` points up, so `y` is
+/// negated. The `viewBox` covers the node bounding box, grown by the
+/// margin plus half the widest stroke so thick branches are not clipped.
+/// An empty tree produces an empty unit-sized document.
+///
+/// ### Parameters
+/// - `tree` - The tree to export.
+/// - `opts` - Stroke, color, and margin settings.
+///
+/// ### Returns
+/// The SVG document as a string.
+pub fn to_svg(tree: &Tree, opts: &SvgOptions) -> String {
+    to_svg_with(tree, &tree.edge_buffer(), opts)
+}
+
+/// Like [`to_svg`], writing the lines from an already built
+/// [`EdgeBuffer`] of `tree`.
+///
+/// ### Parameters
+/// - `tree` - The tree to export; its nodes set the `viewBox` and widths.
+/// - `edges` - Edges of exactly this tree.
+/// - `opts` - Stroke, color, and margin settings.
+///
+/// ### Returns
+/// The SVG document as a string.
+pub fn to_svg_with(tree: &Tree, edges: &EdgeBuffer, opts: &SvgOptions) -> String {
+    let flip = |p: Vec2| Vec2::new(p.x, -p.y);
+    let (min, size) = if let Some((lo, hi)) = tree.bounds() {
+        // SVG's y axis points down, so the flipped box spans -hi.y..-lo.y.
+        let (min, max) = (Vec2::new(lo.x, -hi.y), Vec2::new(hi.x, -lo.y));
+        let widest = tree.nodes.iter().map(|n| n.radius).fold(0.0, f32::max);
+        let pad = opts.margin * (max - min).max_element() + 0.5 * opts.stroke_scale * widest;
+        let pad = Vec2::splat(pad);
+        (
+            min - pad,
+            (max - min + 2.0 * pad).max(Vec2::splat(f32::EPSILON)),
+        )
+    } else {
+        (Vec2::ZERO, Vec2::ONE)
+    };
+
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">",
+        min.x, min.y, size.x, size.y
+    );
+    if let Some([r, g, b]) = opts.background {
+        let _ = write!(
+            out,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#{r:02x}{g:02x}{b:02x}\"/>",
+            min.x, min.y, size.x, size.y
+        );
+    }
+    let [r, g, b] = opts.stroke;
+    let _ = write!(
+        out,
+        "<g stroke=\"#{r:02x}{g:02x}{b:02x}\" stroke-linecap=\"round\">"
+    );
+    for (&(a, b), &(_, child)) in edges.segments().iter().zip(edges.links()) {
+        let (a, b) = (flip(a), flip(b));
+        let width = opts.stroke_scale * tree.nodes[child].radius;
+        let _ = write!(
+            out,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke-width=\"{width}\"/>",
+            a.x, a.y, b.x, b.y
+        );
+    }
+    out.push_str("</g></svg>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the four numbers of the `viewBox` attribute.
+    fn view_box(svg: &str) -> [f32; 4] {
+        let start = svg.find("viewBox=\"").unwrap() + "viewBox=\"".len();
+        let end = start + svg[start..].find('"').unwrap();
+        let v: Vec<f32> = svg[start..end]
+            .split(' ')
+            .map(|s| s.parse().unwrap())
+            .collect();
+        [v[0], v[1], v[2], v[3]]
+    }
+
+    #[test]
+    fn two_nodes_give_one_line_inside_the_view_box() {
+        let mut tree = Tree::new(Vec2::new(-3.0, 1.0), 1.0);
+        tree.add_child(0, Vec2::new(5.0, 9.0), 0.5);
+
+        let svg = to_svg(&tree, &SvgOptions::default());
+
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+        assert_eq!(svg.matches("<line").count(), 1);
+        assert!(svg.contains("stroke-width=\"1\""), "{svg}");
+        let [x, y, w, h] = view_box(&svg);
+        for p in [Vec2::new(-3.0, -1.0), Vec2::new(5.0, -9.0)] {
+            assert!(x < p.x && p.x < x + w, "{p} outside {svg}");
+            assert!(y < p.y && p.y < y + h, "{p} outside {svg}");
+        }
+    }
+
+    #[test]
+    fn stroke_width_follows_radius_and_background_is_optional() {
+        let mut tree = Tree::new(Vec2::ZERO, 4.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 1.0), 3.0);
+        tree.add_child(a, Vec2::new(0.0, 2.0), 1.5);
+        let opts = SvgOptions {
+            stroke_scale: 1.0,
+            stroke: [255, 0, 16],
+            background: None,
+            margin: 0.0,
+        };
+
+        let svg = to_svg(&tree, &opts);
+
+        assert!(svg.contains("stroke=\"#ff0010\""));
+        assert!(!svg.contains("<rect"));
+        assert!(svg.contains("stroke-width=\"3\""));
+        assert!(svg.contains("stroke-width=\"1.5\""));
+        assert_eq!(svg.matches("<line").count(), 2);
+    }
+}
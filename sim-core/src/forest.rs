@@ -0,0 +1,255 @@
+//! Several trees, each with its own configuration, competing for one
+//! shared attractor set.
+//!
+//! Every attractor is claimed by whichever species can pull on it with the
+//! closest node, so a fast-growing config that reaches an area first takes
+//! its attractors away from slower neighbours.
+
+use crate::{
+    attractor::AttractorSet,
+    config::Config,
+    influence_buffer::InfluenceBuffer,
+    phases::{self, GrowthState},
+    tree::Tree,
+    types::NodeId,
+};
+
+/// One competing tree and the configuration it grows with.
+///
+/// ### Fields
+/// - `tree` - The species' tree.
+/// - `cfg` - Growth parameters used for this tree only.
+/// - `growth` - Growth history used for direction damping.
+#[derive(Debug, Clone)]
+pub struct Species {
+    pub tree: Tree,
+    pub cfg: Config,
+    growth: GrowthState,
+}
+
+/// Outcome of a single [`Forest::step`].
+///
+/// ### Fields
+/// - `new_ids` - Per species, the ids of the nodes grown in this step.
+/// - `claimed` - Per species, the number of attractors it killed.
+/// - `owners` - Per attractor, the `(species, node)` it pulled on in this
+///   step, or `None`. Node ids are only unique within one species' tree,
+///   so this replaces [`crate::attractor::Attractor::owner`], which
+///   [`Forest::step`] clears.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForestStepResult {
+    pub new_ids: Vec<Vec<NodeId>>,
+    pub claimed: Vec<usize>,
+    pub owners: Vec<Option<(usize, NodeId)>>,
+}
+
+/// A set of species sharing one attractor set.
+///
+/// ### Fields
+/// - `species` - The competing trees, indexed by species id.
+#[derive(Debug, Clone, Default)]
+pub struct Forest {
+    pub species: Vec<Species>,
+}
+
+impl Forest {
+    /// Creates a forest without any species.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a species growing `tree` with `cfg`.
+    ///
+    /// ### Returns
+    /// The species id (its index in `species`).
+    pub fn add_species(&mut self, tree: Tree, cfg: Config) -> usize {
+        self.species.push(Species {
+            tree,
+            cfg,
+            growth: GrowthState::new(),
+        });
+        self.species.len() - 1
+    }
+
+    /// Advances every species by one Attract → Grow → Kill step.
+    ///
+    /// 1. Moving attractors drift by one unit of time.
+    /// 2. Each alive attractor picks, per species, the nodes it would pull
+    ///    on in that species' own attraction phase (group masks, leaves
+    ///    only, influence cap, `attract_from_kn`, and influence radius; see
+    ///    [`phases::attraction_phase_with`]). The species whose closest such
+    ///    node is nearest wins and is pulled, weighted by its config. The
+    ///    `(species, node)` pair is reported in
+    ///    [`ForestStepResult::owners`] and `owner` is cleared.
+    /// 3. Every species grows from its own influence buffer and config.
+    /// 4. Each alive, unlocked attractor is killed by the species whose kill
+    ///    anchor (per its `kill_anchor` and `kill_from_kn`, with the owner
+    ///    from step 2) is closest among those within their own kill radius.
+    ///
+    /// ### Parameters
+    /// - `attractors` - The shared attractor set.
+    /// - `acc` - One influence buffer per species; resized as needed.
+    ///
+    /// ### Returns
+    /// The new node ids and the number of claimed attractors per species,
+    /// and the owner of every attractor.
+    pub fn step(
+        &mut self,
+        attractors: &mut AttractorSet,
+        acc: &mut Vec<InfluenceBuffer>,
+    ) -> ForestStepResult {
+        attractors.advance(1.0, None);
+
+        acc.resize_with(self.species.len(), || InfluenceBuffer::with_len(0));
+        for (s, buf) in self.species.iter().zip(acc.iter_mut()) {
+            buf.ensure_len(s.tree.nodes.len());
+        }
+
+        let mut owners = vec![None; attractors.points.len()];
+        for (i, a) in attractors.points.iter_mut().enumerate() {
+            a.owner = None;
+            if !a.alive {
+                continue;
+            }
+            let winner = self
+                .species
+                .iter()
+                .zip(acc.iter())
+                .enumerate()
+                .filter_map(|(s, (sp, buf))| {
+                    let pulled: Vec<_> =
+                        phases::pulled_nodes(&sp.tree, &sp.tree, a, &sp.cfg, buf).collect();
+                    (!pulled.is_empty()).then_some((s, pulled))
+                })
+                .min_by(|x, y| x.1[0].1.total_cmp(&y.1[0].1));
+            let Some((s, pulled)) = winner else {
+                continue;
+            };
+            let sp = &self.species[s];
+            for &(id, d2) in &pulled {
+                let node = sp.tree.nodes[id].pos;
+                acc[s].add(id, phases::contribution(&sp.cfg, node, a, d2));
+            }
+            owners[i] = Some((s, pulled[0].0));
+        }
+
+        let new_ids = self
+            .species
+            .iter_mut()
+            .zip(acc.iter())
+            .map(|(s, buf)| {
+                phases::growth_phase_with_state(&mut s.tree, buf, &s.cfg, &mut s.growth)
+            })
+            .collect();
+
+        let mut claimed = vec![0; self.species.len()];
+        for (a, owner) in attractors.points.iter_mut().zip(&owners) {
+            if !a.alive || a.locked {
+                continue;
+            }
+            let killer = self
+                .species
+                .iter()
+                .enumerate()
+                .filter_map(|(s, sp)| {
+                    let own = owner.filter(|&(o, _)| o == s).map(|(_, id)| id);
+                    let d2 =
+                        phases::kill_anchor_distance2(&sp.tree, &sp.tree, a.pos, own, &sp.cfg)?;
+                    (d2 < sp.cfg.kill_radius * sp.cfg.kill_radius).then_some((s, d2))
+                })
+                .min_by(|x, y| x.1.total_cmp(&y.1));
+            if let Some((s, _)) = killer {
+                a.alive = false;
+                claimed[s] += 1;
+            }
+        }
+
+        ForestStepResult {
+            new_ids,
+            claimed,
+            owners,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
+mod tests {
+    use super::*;
+    use crate::config::KillAnchor;
+    use glam::Vec2;
+
+    #[test]
+    fn faster_species_claims_more_shared_attractors() {
+        // Two roots facing each other across a strip of attractors.
+        let mut weed = Config::default();
+        weed.influence_radius = 200.0;
+        weed.kill_radius = 4.0;
+        weed.step_len = 6.0;
+        let mut oak = weed;
+        oak.step_len = 2.0;
+
+        let mut forest = Forest::new();
+        let w = forest.add_species(Tree::new(Vec2::new(-60.0, 0.0), 1.0), weed);
+        let o = forest.add_species(Tree::new(Vec2::new(60.0, 0.0), 1.0), oak);
+        let mut attractors = AttractorSet::from_positions(
+            (0..25)
+                .map(|i| Vec2::new(-48.0 + i as f32 * 4.0, 0.5))
+                .collect(),
+        );
+        let mut acc = Vec::new();
+
+        let mut claimed = [0usize; 2];
+        for _ in 0..30 {
+            let result = forest.step(&mut attractors, &mut acc);
+            assert_eq!(result.new_ids.len(), 2);
+            claimed[w] += result.claimed[w];
+            claimed[o] += result.claimed[o];
+        }
+
+        assert!(
+            claimed[w] > claimed[o],
+            "weed {} vs oak {}",
+            claimed[w],
+            claimed[o]
+        );
+        assert!(claimed[o] > 0, "the oak should still get its nearby share");
+        assert_eq!(
+            claimed[w] + claimed[o],
+            attractors.points.iter().filter(|a| !a.alive).count()
+        );
+    }
+
+    #[test]
+    fn attractors_go_to_species_that_accept_them_and_owners_name_the_species() {
+        // The near tree only accepts group 1, so the group-0 attractor is
+        // pulled by the far tree even though a nearer node exists.
+        let mut near_tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        near_tree.nodes[0].group_mask = 0b10;
+        let mut forest = Forest::new();
+        forest.add_species(near_tree, Config::default());
+        let far = forest.add_species(Tree::new(Vec2::new(40.0, 0.0), 1.0), Config::default());
+
+        let mut attractors = AttractorSet::from_positions(vec![Vec2::new(10.0, 0.0)]);
+        let mut acc = Vec::new();
+        let result = forest.step(&mut attractors, &mut acc);
+
+        assert_eq!(result.owners, vec![Some((far, 0))]);
+        assert_eq!(attractors.points[0].owner, None);
+        assert!(result.new_ids[0].is_empty());
+        assert_eq!(result.new_ids[far].len(), 1);
+
+        // With the owner as kill anchor, only the owning species can kill.
+        let mut cfg = Config::default();
+        cfg.kill_anchor = KillAnchor::Owner;
+        cfg.kill_radius = 50.0;
+        let mut forest = Forest::new();
+        let first = forest.add_species(Tree::new(Vec2::new(0.0, 0.0), 1.0), cfg);
+        let second = forest.add_species(Tree::new(Vec2::new(4.0, 0.0), 1.0), cfg);
+        forest.species[second].cfg.influence_radius = 1.0;
+        let mut attractors = AttractorSet::from_positions(vec![Vec2::new(5.0, 0.0)]);
+        let result = forest.step(&mut attractors, &mut Vec::new());
+        assert_eq!(result.owners, vec![Some((first, 0))]);
+        assert_eq!(result.claimed, vec![1, 0]);
+    }
+}
@@ -0,0 +1,176 @@
+//! A [`Tree`] paired with a lazily rebuilt spatial index.
+//!
+//! [`IndexedTree`] routes every mutation through itself so it knows when
+//! its [`SpatialGrid`] is stale. Nodes added through it are inserted into
+//! the grid directly; after any other change the grid is rebuilt on the
+//! first query instead of relying on callers to remember to do it.
+
+use crate::{spatial::SpatialGrid, tree::Tree, types::NodeId};
+use glam::Vec2;
+
+/// A tree with a node-position grid that is rebuilt only when needed.
+///
+/// ### Fields
+/// - `tree` - The wrapped tree.
+/// - `grid` - Node positions bucketed by cell; valid only while `!dirty`.
+/// - `dirty` - Whether the tree changed since the grid was last built.
+/// - `rebuilds` - Number of times the grid has been rebuilt.
+#[derive(Debug, Clone)]
+pub struct IndexedTree {
+    tree: Tree,
+    grid: SpatialGrid,
+    dirty: bool,
+    rebuilds: usize,
+}
+
+impl IndexedTree {
+    /// Wraps a tree. The grid is built on the first query.
+    ///
+    /// ### Parameters
+    /// - `tree` - The tree to index.
+    /// - `cell_size` - Grid cell size; a good choice is the typical query radius.
+    pub fn new(tree: Tree, cell_size: f32) -> Self {
+        Self {
+            tree,
+            grid: SpatialGrid::new(cell_size),
+            dirty: true,
+            rebuilds: 0,
+        }
+    }
+
+    /// Read-only access to the wrapped tree.
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    /// Mutable access to the wrapped tree.
+    ///
+    /// Marks the index dirty, since any change may have been made.
+    pub fn tree_mut(&mut self) -> &mut Tree {
+        self.dirty = true;
+        &mut self.tree
+    }
+
+    /// Unwraps the tree, dropping the index.
+    pub fn into_inner(self) -> Tree {
+        self.tree
+    }
+
+    /// Adds a child node via [`Tree::add_child`] and inserts it into the grid.
+    ///
+    /// ### Returns
+    /// The id of the new node.
+    pub fn add_child(&mut self, parent: NodeId, pos: Vec2, radius: f32) -> NodeId {
+        let id = self.tree.add_child(parent, pos, radius);
+        self.index_new(id);
+        id
+    }
+
+    /// Adds a free node via [`Tree::add_free_node`] and inserts it into the grid.
+    ///
+    /// ### Returns
+    /// The id of the new node.
+    pub fn add_free_node(&mut self, pos: Vec2, radius: f32) -> NodeId {
+        let id = self.tree.add_free_node(pos, radius);
+        self.index_new(id);
+        id
+    }
+
+    /// Finds the node nearest to `pos` using the grid.
+    ///
+    /// ### Returns
+    /// The same result as [`Tree::find_nearest_node`].
+    pub fn find_nearest_node(&mut self, pos: Vec2) -> Option<(NodeId, f32)> {
+        self.refresh();
+        self.grid.query_nearest(pos)
+    }
+
+    /// Finds the *k*-th nearest accepted node using the grid.
+    ///
+    /// ### Returns
+    /// The same result as [`Tree::find_kth_nearest_ids_where`].
+    pub fn find_kth_nearest_ids_where(
+        &mut self,
+        pos: Vec2,
+        k: usize,
+        pred: impl Fn(NodeId) -> bool,
+    ) -> Option<(NodeId, f32)> {
+        self.refresh();
+        self.grid.query_kth_nearest_where(pos, k, pred)
+    }
+
+    /// Returns the ids of all nodes within `radius` of `pos`.
+    ///
+    /// Rebuilds the grid first if the tree changed since the last query.
+    ///
+    /// ### Parameters
+    /// - `pos` - Query center.
+    /// - `radius` - Query radius (inclusive).
+    ///
+    /// ### Returns
+    /// Matching node ids in unspecified order.
+    pub fn query_radius(&mut self, pos: Vec2, radius: f32) -> Vec<NodeId> {
+        self.refresh();
+        self.grid.query_radius(pos, radius)
+    }
+
+    /// Number of times the grid has been rebuilt so far.
+    pub fn rebuild_count(&self) -> usize {
+        self.rebuilds
+    }
+
+    /// Inserts a just-added node, unless the grid is stale anyway.
+    fn index_new(&mut self, id: NodeId) {
+        if !self.dirty {
+            self.grid.insert(id, self.tree.nodes[id].pos);
+        }
+    }
+
+    /// Rebuilds the grid from the current node positions if it is stale.
+    fn refresh(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        self.grid.clear();
+        for (id, node) in self.tree.nodes.iter().enumerate() {
+            self.grid.insert(id, node.pos);
+        }
+        self.dirty = false;
+        self.rebuilds += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn added_nodes_are_inserted_without_a_rebuild() {
+        let mut indexed = IndexedTree::new(Tree::new(Vec2::ZERO, 1.0), 5.0);
+        assert_eq!(
+            indexed.query_radius(Vec2::new(10.0, 0.0), 1.0),
+            Vec::<NodeId>::new()
+        );
+        assert_eq!(indexed.rebuild_count(), 1);
+
+        // The new node goes straight into the existing grid.
+        let id = indexed.add_child(0, Vec2::new(10.0, 0.0), 1.0);
+        assert_eq!(indexed.query_radius(Vec2::new(10.0, 0.0), 1.0), vec![id]);
+        assert_eq!(
+            indexed.find_nearest_node(Vec2::new(9.0, 0.0)),
+            Some((id, 1.0))
+        );
+        assert_eq!(indexed.rebuild_count(), 1);
+    }
+
+    #[test]
+    fn tree_mut_marks_index_dirty() {
+        let mut indexed = IndexedTree::new(Tree::new(Vec2::ZERO, 1.0), 5.0);
+        indexed.query_radius(Vec2::ZERO, 1.0);
+
+        indexed.tree_mut().nodes[0].pos = Vec2::new(20.0, 0.0);
+        assert!(indexed.query_radius(Vec2::ZERO, 1.0).is_empty());
+        assert_eq!(indexed.query_radius(Vec2::new(20.0, 0.0), 1.0), vec![0]);
+        assert_eq!(indexed.rebuild_count(), 2);
+    }
+}
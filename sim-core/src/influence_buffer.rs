@@ -1,4 +1,4 @@
-use crate::types::NodeId;
+use crate::{attractor::AttractorSet, config::Config, phases, tree::Tree, types::NodeId};
 use glam::Vec2;
 
 /// A temporary buffer that accumulates directional influence per node.
@@ -13,7 +13,7 @@ use glam::Vec2;
 ///
 /// Internally, `dir[i]` and `count[i]` correspond to node `i` (where
 /// `NodeId` is expected to be an index-like type, e.g. `usize`).
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct InfluenceBuffer {
     /// Accumulated direction vectors for each node.
     dir: Vec<Vec2>,
@@ -39,6 +39,40 @@ impl InfluenceBuffer {
         }
     }
 
+    /// Computes the influences of `attractors` on `tree` in one call.
+    ///
+    /// Builds a fresh buffer sized to the tree and fills it exactly like
+    /// [`phases::attraction_phase`], but leaves the attractors untouched:
+    /// the phase runs on a copy of the set, so `owner` fields are not
+    /// updated. Use [`InfluenceBuffer::accumulate_assigning`] to keep them.
+    ///
+    /// ### Parameters
+    /// - `tree` - The tree being attracted.
+    /// - `attractors` - The attractors pulling on it.
+    /// - `cfg` - Global configuration.
+    ///
+    /// ### Returns
+    /// A new [`InfluenceBuffer`] of length `tree.nodes.len()`.
+    pub fn accumulate(tree: &Tree, attractors: &AttractorSet, cfg: &Config) -> Self {
+        Self::accumulate_assigning(tree, &mut attractors.clone(), cfg)
+    }
+
+    /// Like [`InfluenceBuffer::accumulate`], but also updates each
+    /// attractor's `owner` as [`phases::attraction_phase`] does.
+    ///
+    /// ### Parameters
+    /// - `tree` - The tree being attracted.
+    /// - `attractors` - The attractors pulling on it; owners are updated.
+    /// - `cfg` - Global configuration.
+    ///
+    /// ### Returns
+    /// A new [`InfluenceBuffer`] of length `tree.nodes.len()`.
+    pub fn accumulate_assigning(tree: &Tree, attractors: &mut AttractorSet, cfg: &Config) -> Self {
+        let mut acc = Self::with_len(tree.nodes.len());
+        phases::attraction_phase(tree, attractors, cfg, &mut acc);
+        acc
+    }
+
     /// Ensures that the internal storage has exactly the given length.
     ///
     /// If the current length differs from `len`, both the direction and
@@ -107,6 +141,38 @@ impl InfluenceBuffer {
         }
     }
 
+    /// Returns the summed influence direction for a node.
+    ///
+    /// Unlike [`InfluenceBuffer::avg_dir`], this is not divided by the
+    /// count, so its length grows with the number (and weight) of aligned
+    /// influences. Returns `Vec2::ZERO` if no influences were accumulated.
+    ///
+    /// ### Parameters
+    /// - `id` - Node ID whose summed direction should be queried.
+    ///
+    /// ### Returns
+    /// The sum of all direction vectors added for the given node.
+    #[inline]
+    pub fn sum_dir(&self, id: NodeId) -> Vec2 {
+        self.dir[id]
+    }
+
+    /// Returns the length of the summed influence for a node.
+    ///
+    /// Many influences that cancel out give a small magnitude, while a
+    /// few aligned ones give a large one, even when both average to the
+    /// same direction.
+    ///
+    /// ### Parameters
+    /// - `id` - Node ID to query.
+    ///
+    /// ### Returns
+    /// `sum_dir(id).length()`.
+    #[inline]
+    pub fn magnitude(&self, id: NodeId) -> f32 {
+        self.dir[id].length()
+    }
+
     /// Returns `true` if the given node has received any influences.
     ///
     /// This is equivalent to checking whether the count for the node
@@ -177,6 +243,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn sum_dir_distinguishes_canceling_from_aligned_influences() {
+        let mut buf = InfluenceBuffer::with_len(2);
+
+        buf.add(0, Vec2::X);
+        buf.add(0, -Vec2::X);
+        assert!(buf.sum_dir(0).length() < 1e-6);
+        assert!(buf.magnitude(0) < 1e-6);
+        assert_eq!(buf.count[0], 2);
+        assert!(buf.is_influenced(0));
+
+        buf.add(1, Vec2::Y);
+        buf.add(1, Vec2::Y);
+        assert_eq!(buf.sum_dir(1), Vec2::new(0.0, 2.0));
+        assert!((buf.magnitude(1) - 2.0).abs() < 1e-6);
+        assert_eq!(buf.avg_dir(1), Vec2::Y);
+    }
+
     #[test]
     fn ensure_len_keeps_length_and_clears_when_same() {
         let mut buf = InfluenceBuffer::with_len(3);
@@ -298,4 +382,34 @@ mod tests {
         let b = InfluenceBuffer::with_len(3);
         a.merge_from(&b);
     }
+
+    #[test]
+    fn accumulate_matches_attraction_phase_without_touching_owners() {
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        tree.add_child(0, Vec2::new(0.0, 5.0), 1.0);
+        tree.add_child(0, Vec2::new(4.0, 1.0), 1.0);
+        let attractors = AttractorSet::from_positions(vec![
+            Vec2::new(0.0, 12.0),
+            Vec2::new(9.0, 2.0),
+            Vec2::new(-3.0, -4.0),
+            Vec2::new(300.0, 0.0),
+        ]);
+        let cfg = Config::default();
+
+        let mut manual = InfluenceBuffer::with_len(0);
+        let mut assigned = attractors.clone();
+        phases::attraction_phase(&tree, &mut assigned, &cfg, &mut manual);
+
+        let one_shot = InfluenceBuffer::accumulate(&tree, &attractors, &cfg);
+        assert_eq!(one_shot.dir, manual.dir);
+        assert_eq!(one_shot.count, manual.count);
+        assert!(attractors.points.iter().all(|a| a.owner.is_none()));
+
+        let mut owned = attractors.clone();
+        let opted_in = InfluenceBuffer::accumulate_assigning(&tree, &mut owned, &cfg);
+        assert_eq!(opted_in.count, manual.count);
+        let owners = |set: &AttractorSet| set.points.iter().map(|a| a.owner).collect::<Vec<_>>();
+        assert_eq!(owners(&owned), owners(&assigned));
+        assert_eq!(owners(&owned), vec![Some(1), Some(2), Some(0), None]);
+    }
 }
@@ -4,13 +4,34 @@
 //! - [`attractor`] — attractor points and sets.
 //! - [`tree`] — tree nodes and growth logic.
 //! - [`config`] — global configuration for the growth algorithm.
+//! - [`edge_buffer`] — flat edge lists shared by renderers and exporters.
+//! - [`export`] — exporters (e.g. raster images) for grown trees.
+//! - [`forest`] — trees with their own configs competing for shared attractors.
+//! - [`indexed_tree`] — tree wrapper with a lazily rebuilt spatial index.
 //! - [`influence_buffer`] — temporary buffers for accumulated influences.
+//! - [`math`] — vector helpers with an optional deterministic path.
 //! - [`phases`] — high-level simulation phases / pipeline.
+//! - `parallel` — multi-threaded phase variants (requires the `parallel` feature).
+//! - [`recorder`] — per-step growth recording for playback.
+//! - [`simulate`] — headless runs growing a tree to completion without a UI.
+//! - [`simulation`] — facade owning a complete simulation and stepping it.
+//! - [`spatial`] — uniform-grid spatial index and the nearest-node query trait.
 //! - [`types`] — shared type aliases and IDs.
 
 pub mod attractor;
 pub mod config;
+pub mod edge_buffer;
+pub mod export;
+pub mod forest;
+pub mod indexed_tree;
 pub mod influence_buffer;
+pub mod math;
+#[cfg(feature = "parallel")]
+pub mod parallel;
 pub mod phases;
+pub mod recorder;
+pub mod simulate;
+pub mod simulation;
+pub mod spatial;
 pub mod tree;
 pub mod types;
@@ -0,0 +1,76 @@
+//! Vector helpers used on the growth path.
+//!
+//! By default these forward to glam. With the `deterministic` feature they
+//! are spelled out with plain IEEE-754 operations (`+`, `*`, `/`, `sqrt`),
+//! which are correctly rounded on every platform and never fused into
+//! multiply-adds by rustc, so a fixed scenario produces bit-identical
+//! results (and the same [`crate::simulation::Simulation::content_hash`])
+//! across machines and glam versions or features. The two paths may differ
+//! from each other in the last bit (glam multiplies by a reciprocal length).
+//!
+//! Angle-based rotations (phyllotaxy, branching) still go through the
+//! platform's `sin`/`cos` and are not covered.
+
+use glam::Vec2;
+
+/// Returns `v` scaled to unit length, or zero if that is not possible.
+///
+/// ### Parameters
+/// - `v` - Vector to normalize.
+///
+/// ### Returns
+/// The unit vector, or `Vec2::ZERO` if `v` is zero, non-finite, or so
+/// small that its length underflows.
+#[cfg(not(feature = "deterministic"))]
+#[inline]
+pub fn normalize_or_zero(v: Vec2) -> Vec2 {
+    v.normalize_or_zero()
+}
+
+/// Returns `v` scaled to unit length, or zero if that is not possible.
+///
+/// ### Parameters
+/// - `v` - Vector to normalize.
+///
+/// ### Returns
+/// The unit vector, or `Vec2::ZERO` if `v` is zero, non-finite, or so
+/// small that its length underflows.
+#[cfg(feature = "deterministic")]
+#[inline]
+pub fn normalize_or_zero(v: Vec2) -> Vec2 {
+    let len = (v.x * v.x + v.y * v.y).sqrt();
+    if len > 0.0 && len.is_finite() {
+        Vec2::new(v.x / len, v.y / len)
+    } else {
+        Vec2::ZERO
+    }
+}
+
+/// Euclidean distance between `a` and `b`.
+#[cfg(not(feature = "deterministic"))]
+#[inline]
+pub fn distance(a: Vec2, b: Vec2) -> f32 {
+    a.distance(b)
+}
+
+/// Euclidean distance between `a` and `b`.
+#[cfg(feature = "deterministic")]
+#[inline]
+pub fn distance(a: Vec2, b: Vec2) -> f32 {
+    let dx = a.x - b.x;
+    let dy = a.y - b.y;
+    (dx * dx + dy * dy).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_and_distance_basics() {
+        assert_eq!(normalize_or_zero(Vec2::new(3.0, 4.0)), Vec2::new(0.6, 0.8));
+        assert_eq!(normalize_or_zero(Vec2::ZERO), Vec2::ZERO);
+        assert_eq!(normalize_or_zero(Vec2::new(f32::NAN, 1.0)), Vec2::ZERO);
+        assert_eq!(distance(Vec2::new(1.0, 1.0), Vec2::new(4.0, 5.0)), 5.0);
+    }
+}
@@ -0,0 +1,347 @@
+//! Multi-threaded variants of the simulation phases.
+//!
+//! Only available with the `parallel` feature, which pulls in `rayon`.
+//! Every function here produces exactly the same result as its serial
+//! counterpart in [`crate::phases`]: the expensive per-attractor searches
+//! run in parallel, but influences are accumulated in attractor order.
+//!
+//! With this feature, [`crate::phases::attraction_phase`] runs large
+//! attractor sets through [`attraction_phase_chunked`], so
+//! [`crate::simulation::Simulation::step`] goes parallel without any
+//! change on the caller's side.
+
+use crate::{
+    attractor::AttractorSet,
+    config::Config,
+    influence_buffer::InfluenceBuffer,
+    phases::{attraction_phase_with, contribution, pulled_nodes},
+    spatial::NearestIndex,
+    tree::Tree,
+};
+use rayon::prelude::*;
+use std::sync::Arc;
+
+/// Settings for the parallel phases.
+///
+/// The default runs on rayon's global pool. [`ParConfig::new`] with a
+/// thread count builds a dedicated pool once, which every call made with
+/// this config (and its clones) then reuses, e.g. to leave cores free for
+/// a UI thread.
+///
+/// ### Fields
+/// - `pool` - Dedicated worker pool, or `None` for the global pool.
+#[derive(Clone, Debug, Default)]
+pub struct ParConfig {
+    pool: Option<Arc<rayon::ThreadPool>>,
+}
+
+impl ParConfig {
+    /// Creates a config running on `threads` worker threads.
+    ///
+    /// ### Parameters
+    /// - `threads` - Number of worker threads. `None` uses rayon's global
+    ///   pool; `Some(n)` builds a dedicated pool of `n` threads.
+    ///
+    /// ### Returns
+    /// The config, or the error if the dedicated pool can't be built.
+    pub fn new(threads: Option<usize>) -> Result<Self, rayon::ThreadPoolBuildError> {
+        let pool = match threads {
+            Some(n) => Some(Arc::new(
+                rayon::ThreadPoolBuilder::new().num_threads(n).build()?,
+            )),
+            None => None,
+        };
+        Ok(Self { pool })
+    }
+
+    /// Number of threads of the dedicated pool, or `None` for the global pool.
+    pub fn threads(&self) -> Option<usize> {
+        self.pool.as_ref().map(|pool| pool.current_num_threads())
+    }
+
+    /// Runs `op` on the dedicated pool, or on the global pool if there is none.
+    fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+}
+
+/// Parallel version of [`crate::phases::attraction_phase`].
+///
+/// The nearest-node search for every alive attractor runs in parallel;
+/// the resulting directions are then added to `acc` sequentially, so the
+/// buffer contents are bit-for-bit identical to the serial phase.
+///
+/// With [`Config::max_influences_per_node`] set, each search depends on the
+/// counts accumulated so far, so the serial phase is used instead; the
+/// same goes for [`Config::attract_k_nearest`].
+///
+/// ### Parameters
+/// - `tree` - The current tree structure; only read access is required.
+/// - `attractors` - Set of attractors; their `owner` fields are updated.
+/// - `cfg` - Global configuration (influence radius, `attract_from_kn`).
+/// - `acc` - Scratch buffer used to accumulate influence directions per node.
+/// - `par` - Thread settings.
+pub fn attraction_phase_par(
+    tree: &Tree,
+    attractors: &mut AttractorSet,
+    cfg: &Config,
+    acc: &mut InfluenceBuffer,
+    par: &ParConfig,
+) {
+    if cfg.max_influences_per_node.is_some() || cfg.attract_k_nearest {
+        crate::phases::attraction_phase(tree, attractors, cfg, acc);
+        return;
+    }
+
+    let r2 = cfg.influence_radius * cfg.influence_radius;
+    acc.ensure_len(tree.nodes.len());
+
+    let contributions: Vec<_> = par.install(|| {
+        attractors
+            .points
+            .par_iter_mut()
+            .filter(|a| a.alive)
+            .map(|a| {
+                let bit = a.group_bit();
+                let hit = tree
+                    .find_kth_nearest_nodes_where(a.pos, cfg.attract_from_kn, |n| {
+                        n.group_mask & bit != 0
+                            && (!cfg.attract_leaves_only || n.children.is_empty())
+                    })
+                    .filter(|&(_, d2)| d2 < r2);
+                a.owner = hit.map(|(id, _)| id);
+                hit.map(|(id, d2)| (id, contribution(cfg, tree.nodes[id].pos, a, d2)))
+            })
+            .collect()
+    });
+
+    for (id, dir) in contributions.into_iter().flatten() {
+        acc.add(id, dir);
+    }
+}
+
+/// Attractors per worker chunk in [`attraction_phase_chunked`].
+pub const PAR_CHUNK: usize = 4096;
+
+/// Parallel version of [`crate::phases::attraction_phase_with`], working on
+/// fixed-size attractor chunks.
+///
+/// Every chunk of [`PAR_CHUNK`] attractors is searched by one worker with
+/// `index`, collecting its contributions in attractor order; the lists are
+/// then added to `acc` in chunk order, so the buffer contents are
+/// bit-for-bit identical to the serial phase. Unlike
+/// [`attraction_phase_par`], this also supports
+/// [`Config::attract_k_nearest`].
+///
+/// Workers don't sum into private [`InfluenceBuffer`]s merged with
+/// [`InfluenceBuffer::merge_from`]: float addition is not associative, so
+/// summing per worker first would change the rounding of the direction
+/// sums. Keeping each chunk's contributions as a list and adding them in
+/// attractor order preserves the serial sums, and the lists are sized by
+/// the chunk rather than by the tree.
+///
+/// [`crate::phases::attraction_phase`] calls this for sets of more than
+/// [`PAR_CHUNK`] attractors.
+///
+/// With [`Config::max_influences_per_node`] set, each search depends on the
+/// counts accumulated so far, so the serial phase is used instead.
+///
+/// ### Parameters
+/// - `tree` - The current tree structure.
+/// - `index` - Nearest-node index over exactly the nodes of `tree`.
+/// - `attractors` - Set of attractors; their `owner` fields are updated.
+/// - `cfg` - Global configuration (influence radius, `attract_from_kn`).
+/// - `acc` - Scratch buffer used to accumulate influence directions per node.
+pub fn attraction_phase_chunked(
+    tree: &Tree,
+    index: &(impl NearestIndex + Sync),
+    attractors: &mut AttractorSet,
+    cfg: &Config,
+    acc: &mut InfluenceBuffer,
+) {
+    if cfg.max_influences_per_node.is_some() {
+        attraction_phase_with(tree, index, attractors, cfg, acc);
+        return;
+    }
+
+    acc.ensure_len(tree.nodes.len());
+    let counts = &*acc;
+
+    let chunks: Vec<Vec<_>> = attractors
+        .points
+        .par_chunks_mut(PAR_CHUNK)
+        .map(|chunk| {
+            let mut pulls = Vec::new();
+            for a in chunk.iter_mut().filter(|a| a.alive) {
+                a.owner = None;
+                for (id, d2) in pulled_nodes(tree, index, a, cfg, counts) {
+                    pulls.push((id, contribution(cfg, tree.nodes[id].pos, a, d2)));
+                    a.owner.get_or_insert(id);
+                }
+            }
+            pulls
+        })
+        .collect();
+
+    for (id, dir) in chunks.into_iter().flatten() {
+        acc.add(id, dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phases;
+    use glam::Vec2;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn scene() -> (Tree, AttractorSet) {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 10.0), 1.0);
+        tree.add_child(a, Vec2::new(5.0, 15.0), 1.0);
+        tree.add_free_node(Vec2::new(40.0, 0.0), 1.0);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let attractors =
+            AttractorSet::random_in_oval(Vec2::new(10.0, 30.0), Vec2::splat(60.0), 500, &mut rng);
+        (tree, attractors)
+    }
+
+    /// Attractors spanning a little over two chunks.
+    fn large_set() -> AttractorSet {
+        let mut rng = StdRng::seed_from_u64(8);
+        AttractorSet::random_in_oval(
+            Vec2::new(10.0, 30.0),
+            Vec2::splat(60.0),
+            PAR_CHUNK * 2 + 100,
+            &mut rng,
+        )
+    }
+
+    fn owners(set: &AttractorSet) -> Vec<Option<usize>> {
+        set.points.iter().map(|a| a.owner).collect()
+    }
+
+    #[test]
+    fn dedicated_pool_is_built_once_and_shared_by_clones() {
+        let par = ParConfig::new(Some(2)).unwrap();
+        assert_eq!(par.threads(), Some(2));
+        let copy = par.clone();
+        assert!(Arc::ptr_eq(
+            par.pool.as_ref().unwrap(),
+            copy.pool.as_ref().unwrap()
+        ));
+        assert_eq!(ParConfig::new(None).unwrap().threads(), None);
+    }
+
+    #[test]
+    fn single_thread_matches_global_pool_and_serial() {
+        let (tree, base) = scene();
+        let cfg = Config::default();
+
+        let mut serial = base.clone();
+        let mut acc_serial = InfluenceBuffer::with_len(0);
+        phases::attraction_phase(&tree, &mut serial, &cfg, &mut acc_serial);
+
+        let mut one = base.clone();
+        let mut acc_one = InfluenceBuffer::with_len(0);
+        attraction_phase_par(
+            &tree,
+            &mut one,
+            &cfg,
+            &mut acc_one,
+            &ParConfig::new(Some(1)).unwrap(),
+        );
+
+        let mut many = base;
+        let mut acc_many = InfluenceBuffer::with_len(0);
+        attraction_phase_par(&tree, &mut many, &cfg, &mut acc_many, &ParConfig::default());
+
+        assert_eq!(owners(&one), owners(&many));
+        assert_eq!(owners(&one), owners(&serial));
+        for id in 0..tree.nodes.len() {
+            assert_eq!(acc_one.count[id], acc_many.count[id]);
+            assert_eq!(acc_one.avg_dir(id), acc_many.avg_dir(id));
+            assert_eq!(acc_one.avg_dir(id), acc_serial.avg_dir(id));
+        }
+    }
+
+    fn assert_same_influences(a: &InfluenceBuffer, b: &InfluenceBuffer, len: usize) {
+        assert_eq!(a.count, b.count);
+        for id in 0..len {
+            assert_eq!(a.sum_dir(id), b.sum_dir(id), "node {id}");
+        }
+    }
+
+    #[test]
+    fn parallel_phase_matches_serial_bit_for_bit() {
+        let (tree, base) = scene();
+        for cfg in [
+            Config::default(),
+            Config {
+                attract_leaves_only: true,
+                attract_from_kn: 1,
+                ..Config::default()
+            },
+        ] {
+            let mut serial = base.clone();
+            let mut acc_serial = InfluenceBuffer::with_len(0);
+            phases::attraction_phase(&tree, &mut serial, &cfg, &mut acc_serial);
+
+            let mut par = base.clone();
+            let mut acc_par = InfluenceBuffer::with_len(0);
+            attraction_phase_par(&tree, &mut par, &cfg, &mut acc_par, &ParConfig::default());
+
+            assert_eq!(owners(&par), owners(&serial));
+            assert_same_influences(&acc_par, &acc_serial, tree.nodes.len());
+        }
+    }
+
+    #[test]
+    fn chunked_phase_matches_serial_bit_for_bit() {
+        let (tree, _) = scene();
+        let base = large_set();
+        for cfg in [
+            Config::default(),
+            Config {
+                attract_k_nearest: true,
+                attract_from_kn: 1,
+                ..Config::default()
+            },
+        ] {
+            let mut serial = base.clone();
+            let mut acc_serial = InfluenceBuffer::with_len(0);
+            phases::attraction_phase_with(&tree, &tree, &mut serial, &cfg, &mut acc_serial);
+
+            let mut chunked = base.clone();
+            let mut acc_chunked = InfluenceBuffer::with_len(0);
+            attraction_phase_chunked(&tree, &tree, &mut chunked, &cfg, &mut acc_chunked);
+
+            assert_eq!(owners(&chunked), owners(&serial));
+            assert_same_influences(&acc_chunked, &acc_serial, tree.nodes.len());
+        }
+    }
+
+    #[test]
+    fn attraction_phase_runs_large_sets_in_chunks_with_serial_results() {
+        let (tree, _) = scene();
+        let base = large_set();
+        assert!(base.points.len() > PAR_CHUNK);
+        let cfg = Config::default();
+
+        let mut serial = base.clone();
+        let mut acc_serial = InfluenceBuffer::with_len(0);
+        phases::attraction_phase_with(&tree, &tree, &mut serial, &cfg, &mut acc_serial);
+
+        let mut routed = base;
+        let mut acc_routed = InfluenceBuffer::with_len(0);
+        phases::attraction_phase(&tree, &mut routed, &cfg, &mut acc_routed);
+
+        assert_eq!(owners(&routed), owners(&serial));
+        assert_same_influences(&acc_routed, &acc_serial, tree.nodes.len());
+    }
+}
@@ -0,0 +1,140 @@
+//! Growth recording for playback.
+//!
+//! Growth only ever appends nodes to [`Tree::nodes`], so the tree at any
+//! earlier step is a prefix of the current node array. [`GrowthRecorder`]
+//! exploits this by storing just the node count per recorded frame, which
+//! is enough to rebuild the tree as it was at that frame without
+//! re-simulating.
+
+use crate::tree::Tree;
+
+/// Records the node count of a tree after each simulation step.
+///
+/// Frame `0` is typically recorded right after the initial scene is set up,
+/// and frame `k` after the `k`-th step.
+///
+/// ### Fields
+/// - `frames` - Node count of the tree at each recorded frame.
+#[derive(Debug, Clone, Default)]
+pub struct GrowthRecorder {
+    frames: Vec<usize>,
+}
+
+impl GrowthRecorder {
+    /// Creates an empty recorder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the current state of `tree` as a new frame.
+    ///
+    /// ### Parameters
+    /// - `tree` - The tree to record.
+    pub fn record(&mut self, tree: &Tree) {
+        self.frames.push(tree.nodes.len());
+    }
+
+    /// Removes all recorded frames.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Number of recorded frames.
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns the node count recorded at `frame`, or `None` if out of range.
+    pub fn node_count_at(&self, frame: usize) -> Option<usize> {
+        self.frames.get(frame).copied()
+    }
+
+    /// Rebuilds the tree as it was at the given frame.
+    ///
+    /// The result contains the first `node_count_at(frame)` nodes of `tree`,
+    /// with child links to later nodes removed. This relies on `tree` having
+    /// only grown (never had nodes removed) since the frame was recorded.
+    ///
+    /// ### Parameters
+    /// - `tree` - The current (latest) tree.
+    /// - `frame` - Frame index to reconstruct.
+    ///
+    /// ### Returns
+    /// - `Some(tree)` with the reconstructed tree, or
+    /// - `None` if `frame` was not recorded or `tree` has fewer nodes than
+    ///   the frame requires.
+    pub fn reconstruct(&self, tree: &Tree, frame: usize) -> Option<Tree> {
+        let n = self.node_count_at(frame)?;
+        if n > tree.nodes.len() {
+            return None;
+        }
+
+        let nodes = tree.nodes[..n]
+            .iter()
+            .map(|node| {
+                let mut node = node.clone();
+                node.children.retain(|&c| c < n);
+                node
+            })
+            .collect();
+
+        Some(Tree { nodes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attractor::AttractorSet, config::Config, influence_buffer::InfluenceBuffer, phases,
+    };
+    use glam::Vec2;
+
+    #[test]
+    fn reconstructing_frame_matches_recorded_node_count() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let mut attractors = AttractorSet::from_positions(vec![
+            Vec2::new(0.0, 40.0),
+            Vec2::new(30.0, 30.0),
+            Vec2::new(-30.0, 30.0),
+        ]);
+        let cfg = Config::default();
+        let mut acc = InfluenceBuffer::with_len(0);
+
+        let mut recorder = GrowthRecorder::new();
+        recorder.record(&tree);
+        let mut counts = vec![tree.nodes.len()];
+
+        for _ in 0..6 {
+            phases::attraction_phase(&tree, &mut attractors, &cfg, &mut acc);
+            phases::growth_phase(&mut tree, &acc, &cfg);
+            phases::kill_phase(&tree, &mut attractors, &cfg);
+            recorder.record(&tree);
+            counts.push(tree.nodes.len());
+        }
+
+        assert_eq!(recorder.frame_count(), counts.len());
+        for (k, &count) in counts.iter().enumerate() {
+            let frame_tree = recorder.reconstruct(&tree, k).unwrap();
+            assert_eq!(frame_tree.nodes.len(), count);
+
+            // No child link may point past the end of the reconstructed tree.
+            for node in &frame_tree.nodes {
+                assert!(node.children.iter().all(|&c| c < count));
+            }
+        }
+    }
+
+    #[test]
+    fn reconstruct_out_of_range_returns_none() {
+        let tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let mut recorder = GrowthRecorder::new();
+        recorder.record(&tree);
+
+        assert!(recorder.reconstruct(&tree, 1).is_none());
+
+        // A tree that shrank below the recorded count cannot be reconstructed.
+        let empty = Tree { nodes: Vec::new() };
+        assert!(recorder.reconstruct(&empty, 0).is_none());
+    }
+}
@@ -0,0 +1,77 @@
+//! Headless runs for batch and command-line use.
+//!
+//! [`run_headless`] grows a tree to completion without any UI, e.g. for
+//! parameter sweeps or offline rendering.
+
+use crate::{attractor::AttractorSet, config::Config, simulation::Simulation, tree::Tree};
+use glam::Vec2;
+
+/// Grows a tree from a single root until it stops changing.
+///
+/// Steps a [`Simulation`] (which owns the influence buffer and runs the
+/// attraction, growth, and kill phases in order) until `max_steps` steps
+/// have run, or until a step grows no node while no alive attractor is
+/// moving. At that point every further step would be identical, which in
+/// particular covers the case where all attractors are dead.
+///
+/// ### Parameters
+/// - `cfg` - Configuration to grow with.
+/// - `attractors` - The attractors to grow toward.
+/// - `root` - Position of the root, which gets radius `1.0`.
+/// - `max_steps` - Upper bound on the number of steps.
+///
+/// ### Returns
+/// The final tree.
+pub fn run_headless(cfg: &Config, attractors: AttractorSet, root: Vec2, max_steps: usize) -> Tree {
+    let mut sim = Simulation::new(*cfg);
+    sim.add_root(root, 1.0);
+    sim.attractors = attractors;
+
+    for _ in 0..max_steps {
+        let grown = !sim.step().new_ids.is_empty();
+        let moving = sim
+            .attractors
+            .points
+            .iter()
+            .any(|a| a.alive && a.velocity != Vec2::ZERO);
+        if !grown && !moving {
+            break;
+        }
+    }
+    sim.tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn node_count_grows_then_plateaus() {
+        let cfg = Config {
+            influence_radius: 30.0,
+            kill_radius: 5.0,
+            step_len: 2.0,
+            ..Config::default()
+        };
+        let cloud = || {
+            AttractorSet::random_in_oval(
+                Vec2::new(0.0, 40.0),
+                Vec2::splat(35.0),
+                600,
+                &mut StdRng::seed_from_u64(5),
+            )
+        };
+        let nodes = |steps| run_headless(&cfg, cloud(), Vec2::ZERO, steps).nodes.len();
+
+        let (early, mid, done) = (nodes(5), nodes(20), nodes(2000));
+        assert!(
+            1 < early && early < mid && mid < done,
+            "{early} {mid} {done}"
+        );
+
+        // Growth has stopped; a higher step limit adds nothing.
+        assert_eq!(nodes(4000), done);
+    }
+}
@@ -0,0 +1,747 @@
+//! High-level simulation facade.
+//!
+//! [`Simulation`] owns the four pieces that are always used together
+//! ([`Tree`], [`AttractorSet`], [`Config`], [`InfluenceBuffer`]) and
+//! runs the phases from [`crate::phases`] in the right order, so callers
+//! don't need to know about phase ordering or influence buffer sizing.
+
+use crate::{
+    attractor::AttractorSet,
+    config::{Config, SpawnTool},
+    influence_buffer::InfluenceBuffer,
+    phases::{self, GrowthState},
+    tree::Tree,
+    types::NodeId,
+};
+use glam::Vec2;
+use rand::Rng;
+
+/// Weight of the pull of [`Simulation::follow_target`]; several times an
+/// ordinary attractor's so the target dominates where its node grows.
+const FOLLOW_WEIGHT: f32 = 4.0;
+
+/// Outcome of a single [`Simulation::step`].
+///
+/// ### Fields
+/// - `new_ids` - Ids of the nodes created during the growth phase, in
+///   the order they were added.
+/// - `killed` - Number of attractors killed during the kill phase.
+/// - `node_count` - Total number of nodes after the step.
+/// - `alive_attractors` - Number of attractors still alive after the step.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StepResult {
+    pub new_ids: Vec<NodeId>,
+    pub killed: usize,
+    pub node_count: usize,
+    pub alive_attractors: usize,
+}
+
+impl StepResult {
+    /// Whether the step changed nothing: no node grew and no attractor died
+    /// (see [`phases::StepOutcome::is_converged`]).
+    pub fn is_converged(&self) -> bool {
+        self.new_ids.is_empty() && self.killed == 0
+    }
+
+    /// Formats the result as a one-line log message.
+    ///
+    /// Example: `step 12: +8 nodes, -15 attractors, 320 total nodes, 742 alive`.
+    ///
+    /// ### Parameters
+    /// - `step` - Step number to print (typically [`SimStats::steps`]).
+    pub fn summary(&self, step: usize) -> String {
+        format!(
+            "step {step}: +{} nodes, -{} attractors, {} total nodes, {} alive",
+            self.new_ids.len(),
+            self.killed,
+            self.node_count,
+            self.alive_attractors
+        )
+    }
+}
+
+/// Aggregate counters describing the current simulation state.
+///
+/// ### Fields
+/// - `steps` - Number of steps run since creation or the last [`Simulation::clear`].
+/// - `node_count` - Number of nodes in the tree.
+/// - `alive_attractors` - Number of attractors that are still alive.
+/// - `dead_attractors` - Number of attractors that have been killed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SimStats {
+    pub steps: usize,
+    pub node_count: usize,
+    pub alive_attractors: usize,
+    pub dead_attractors: usize,
+}
+
+/// Growth metrics of a single frame, for plotting growth over time.
+///
+/// ### Fields
+/// - `step` - Number of steps run when the frame was taken.
+/// - `node_count` - Number of nodes in the tree.
+/// - `leaf_count` - Number of nodes without children.
+/// - `alive_attractors` - Number of attractors that are still alive.
+/// - `total_length` - Summed length of all branch edges.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameStats {
+    pub step: usize,
+    pub node_count: usize,
+    pub leaf_count: usize,
+    pub alive_attractors: usize,
+    pub total_length: f32,
+}
+
+/// A complete simulation: tree, attractors, configuration, and scratch buffer.
+///
+/// The tree, attractors, and configuration are public so that callers can
+/// inspect or edit them between steps. The influence buffer is private and
+/// is resized automatically on every [`Simulation::step`].
+///
+/// ### Fields
+/// - `tree` - The tree being grown.
+/// - `attractors` - Attractor points driving the growth.
+/// - `cfg` - Simulation configuration.
+/// - `acc` - Per-node influence buffer used between phases.
+/// - `growth` - Growth history used for direction damping.
+/// - `follow_target` - Position of a temporary attractor that pulls the
+///   nearest node on every step while set (e.g. the held cursor).
+/// - `steps` - Number of steps run so far.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub tree: Tree,
+    pub attractors: AttractorSet,
+    pub cfg: Config,
+    pub follow_target: Option<Vec2>,
+    acc: InfluenceBuffer,
+    growth: GrowthState,
+    steps: usize,
+}
+
+impl Simulation {
+    /// Creates an empty simulation (no nodes, no attractors).
+    ///
+    /// Use [`Simulation::add_root`] and [`Simulation::spawn_attractors`]
+    /// (or edit `tree` / `attractors` directly) to populate the scene.
+    ///
+    /// ### Parameters
+    /// - `cfg` - Configuration to run the simulation with.
+    ///
+    /// ### Returns
+    /// A new, empty [`Simulation`].
+    pub fn new(cfg: Config) -> Self {
+        Self {
+            tree: Tree { nodes: Vec::new() },
+            attractors: AttractorSet::from_positions(Vec::new()),
+            cfg,
+            follow_target: None,
+            acc: InfluenceBuffer::with_len(0),
+            growth: GrowthState::new(),
+            steps: 0,
+        }
+    }
+
+    /// Creates a simulation that continues growing an existing tree.
+    ///
+    /// The tree is adopted as-is (e.g. a hand-built or imported skeleton)
+    /// and the influence buffer is sized to it, so the simulation can be
+    /// stepped right away once attractors are spawned. It starts with no
+    /// attractors and a step counter of `0`.
+    ///
+    /// ### Parameters
+    /// - `tree` - The tree to adopt.
+    /// - `cfg` - Configuration to run the simulation with.
+    ///
+    /// ### Returns
+    /// A new [`Simulation`] owning `tree`.
+    pub fn from_tree(tree: Tree, cfg: Config) -> Self {
+        let acc = InfluenceBuffer::with_len(tree.nodes.len());
+        Self {
+            tree,
+            acc,
+            ..Self::new(cfg)
+        }
+    }
+
+    /// Restores a simulation from a scene written by
+    /// [`crate::export::scene_to_json`].
+    ///
+    /// The restored simulation continues bit for bit like the one that was
+    /// saved (see [`crate::export::scene`] for what is stored).
+    ///
+    /// ### Parameters
+    /// - `json` - The scene document.
+    ///
+    /// ### Returns
+    /// - `Ok(sim)` with the restored simulation, or
+    /// - `Err(SceneError)` if the document is malformed.
+    #[cfg(feature = "serde")]
+    pub fn from_scene_json(json: &str) -> Result<Self, crate::export::scene::SceneError> {
+        crate::export::scene::scene_from_json(json)
+    }
+
+    /// Assembles a simulation from saved parts, e.g. a loaded scene.
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_parts(
+        tree: Tree,
+        attractors: AttractorSet,
+        cfg: Config,
+        growth: GrowthState,
+        steps: usize,
+    ) -> Self {
+        let acc = InfluenceBuffer::with_len(tree.nodes.len());
+        Self {
+            tree,
+            attractors,
+            cfg,
+            follow_target: None,
+            acc,
+            growth,
+            steps,
+        }
+    }
+
+    /// Adds a new root node to the tree.
+    ///
+    /// ### Parameters
+    /// - `pos` - Position of the new root.
+    /// - `radius` - Branch radius / thickness of the new root.
+    ///
+    /// ### Returns
+    /// The [`NodeId`] of the new root.
+    pub fn add_root(&mut self, pos: Vec2, radius: f32) -> NodeId {
+        self.tree.add_free_node(pos, radius)
+    }
+
+    /// Adds a root with `spokes` children arranged evenly around it.
+    ///
+    /// Growth then starts from a star instead of a single point, which
+    /// avoids the bias toward whichever direction wins the first step.
+    /// Spoke `i` points at angle `i * 2π / spokes`, starting along `+x`.
+    /// Children use the root's radius of `1.0`.
+    ///
+    /// ### Parameters
+    /// - `center` - Position of the new root.
+    /// - `spokes` - Number of initial children.
+    /// - `step_len` - Distance of each child from the root.
+    ///
+    /// ### Returns
+    /// The [`NodeId`] of the new root.
+    pub fn seed_radial(&mut self, center: Vec2, spokes: usize, step_len: f32) -> NodeId {
+        let root = self.add_root(center, 1.0);
+        for i in 0..spokes {
+            let angle = i as f32 * std::f32::consts::TAU / spokes as f32;
+            self.tree
+                .add_child(root, center + Vec2::from_angle(angle) * step_len, 1.0);
+        }
+        root
+    }
+
+    /// Spawns a burst of attractors around `center` using the configured tool.
+    ///
+    /// The shape and count are taken from `cfg`:
+    /// - [`SpawnTool::RectAttractors`] uses `cfg.spawn_rect_half_extents`.
+    /// - [`SpawnTool::OvalAttractors`] uses `cfg.spawn_oval_radii`.
+    /// - For both filled shapes, `cfg.spawn_density` (if set) replaces the
+    ///   fixed count with `density * area`, rounded.
+    /// - [`SpawnTool::RectOutlineAttractors`] spaces the attractors evenly
+    ///   along the outline of the `cfg.spawn_rect_half_extents` rectangle.
+    /// - [`SpawnTool::RootNode`] and [`SpawnTool::Follow`] do not spawn
+    ///   attractors, and neither does [`SpawnTool::LineAttractors`], which
+    ///   needs two points (see [`Simulation::spawn_along_line`]).
+    ///
+    /// ### Parameters
+    /// - `center` - Center of the spawn shape in world coordinates.
+    /// - `rng` - Random number generator used to sample positions.
+    ///
+    /// ### Returns
+    /// The number of attractors added.
+    pub fn spawn_attractors(&mut self, center: Vec2, rng: &mut impl Rng) -> usize {
+        let new_set = match self.cfg.spawn_tool {
+            SpawnTool::RootNode | SpawnTool::LineAttractors | SpawnTool::Follow => return 0,
+            SpawnTool::RectAttractors => {
+                let h = self.cfg.spawn_rect_half_extents;
+                AttractorSet::random_in_rect(center, h, self.spawn_count(4.0 * h.x * h.y), rng)
+            }
+            SpawnTool::OvalAttractors => {
+                let r = self.cfg.spawn_oval_radii;
+                let area = std::f32::consts::PI * r.x * r.y;
+                AttractorSet::random_in_oval(center, r, self.spawn_count(area), rng)
+            }
+            SpawnTool::RectOutlineAttractors => {
+                let h = self.cfg.spawn_rect_half_extents;
+                let corners = [
+                    center + Vec2::new(-h.x, -h.y),
+                    center + Vec2::new(h.x, -h.y),
+                    center + Vec2::new(h.x, h.y),
+                    center + Vec2::new(-h.x, h.y),
+                ];
+                let perimeter = 4.0 * (h.x + h.y);
+                let count = self.cfg.spawn_attractors;
+                if count == 0 || perimeter <= 0.0 {
+                    return 0;
+                }
+                let mut set = AttractorSet::on_polygon_boundary(&corners, perimeter / count as f32);
+                // Guard against an extra point from float rounding at the end.
+                set.points.truncate(count);
+                set
+            }
+        };
+
+        let added = new_set.points.len();
+        self.attractors.points.extend(new_set.points);
+        added
+    }
+
+    /// Spawns `cfg.spawn_attractors` attractors scattered along a line.
+    ///
+    /// Uses [`AttractorSet::random_along_polyline`] with
+    /// `cfg.spawn_line_jitter` as the sideways offset.
+    ///
+    /// ### Parameters
+    /// - `from`, `to` - End points of the line in world coordinates.
+    /// - `rng` - Random number generator used to sample positions.
+    ///
+    /// ### Returns
+    /// The number of attractors added (`0` if `from == to`).
+    pub fn spawn_along_line(&mut self, from: Vec2, to: Vec2, rng: &mut impl Rng) -> usize {
+        let new_set = AttractorSet::random_along_polyline(
+            &[from, to],
+            self.cfg.spawn_line_jitter,
+            self.cfg.spawn_attractors,
+            rng,
+        );
+        let added = new_set.points.len();
+        self.attractors.points.extend(new_set.points);
+        added
+    }
+
+    /// Advances the simulation by one Attract → Grow → Kill step.
+    ///
+    /// Before attraction, moving attractors drift by one unit of time
+    /// (see [`AttractorSet::advance`]), so growth chases them step by step.
+    /// If `follow_target` is set, it pulls on its nearest node as a strong
+    /// temporary attractor (see [`phases::attract_temporary`]); it is never
+    /// added to `attractors`, so it is neither killed nor saved.
+    ///
+    /// ### Returns
+    /// A [`StepResult`] with the ids of the new nodes, the number of
+    /// attractors killed in this step, and the totals after the step.
+    pub fn step(&mut self) -> StepResult {
+        self.attractors.advance(1.0, None);
+        phases::attraction_phase(&self.tree, &mut self.attractors, &self.cfg, &mut self.acc);
+        if let Some(target) = self.follow_target {
+            phases::attract_temporary(&self.tree, target, FOLLOW_WEIGHT, &mut self.acc);
+        }
+        let new_ids =
+            phases::growth_phase_with_state(&mut self.tree, &self.acc, &self.cfg, &mut self.growth);
+
+        let alive_before = self.alive_count();
+        phases::kill_phase(&self.tree, &mut self.attractors, &self.cfg);
+        let alive_attractors = self.alive_count();
+
+        self.steps += 1;
+
+        StepResult {
+            new_ids,
+            killed: alive_before - alive_attractors,
+            node_count: self.tree.nodes.len(),
+            alive_attractors,
+        }
+    }
+
+    /// Previews the children the next [`Simulation::step`] would grow.
+    ///
+    /// Runs attractor drift and the attraction phase on scratch copies, so
+    /// the simulation itself is left untouched.
+    ///
+    /// ### Returns
+    /// `(parent, child_pos)` pairs, as from [`phases::growth_candidates`].
+    pub fn preview_growth(&self) -> Vec<(NodeId, Vec2)> {
+        let mut attractors = self.attractors.clone();
+        attractors.advance(1.0, None);
+        let mut acc = InfluenceBuffer::with_len(0);
+        phases::attraction_phase(&self.tree, &mut attractors, &self.cfg, &mut acc);
+        if let Some(target) = self.follow_target {
+            phases::attract_temporary(&self.tree, target, FOLLOW_WEIGHT, &mut acc);
+        }
+
+        phases::candidates(&self.tree, &acc, &self.cfg, Some(&self.growth))
+            .into_iter()
+            .map(|c| (c.parent, c.pos))
+            .collect()
+    }
+
+    /// Removes all nodes and attractors and resets the step counter.
+    ///
+    /// The configuration is kept.
+    pub fn clear(&mut self) {
+        self.tree.nodes.clear();
+        self.attractors.points.clear();
+        self.acc = InfluenceBuffer::with_len(0);
+        self.growth.clear();
+        self.steps = 0;
+    }
+
+    /// Returns the influence buffer filled by the most recent step.
+    pub fn influence(&self) -> &InfluenceBuffer {
+        &self.acc
+    }
+
+    /// Computes aggregate counters for the current state.
+    ///
+    /// ### Returns
+    /// A [`SimStats`] snapshot.
+    pub fn stats(&self) -> SimStats {
+        let alive_attractors = self.alive_count();
+        SimStats {
+            steps: self.steps,
+            node_count: self.tree.nodes.len(),
+            alive_attractors,
+            dead_attractors: self.attractors.points.len() - alive_attractors,
+        }
+    }
+
+    /// Collects the growth metrics of the current state.
+    ///
+    /// Call this after each [`Simulation::step`] to build a history for
+    /// [`crate::export::history_to_csv`].
+    ///
+    /// ### Returns
+    /// A [`FrameStats`] snapshot.
+    pub fn frame_stats(&self) -> FrameStats {
+        FrameStats {
+            step: self.steps,
+            node_count: self.tree.nodes.len(),
+            leaf_count: self.tree.leaf_count(),
+            alive_attractors: self.alive_count(),
+            total_length: self.tree.total_branch_length(),
+        }
+    }
+
+    /// Number of attractors to spawn in a filled shape of the given area.
+    fn spawn_count(&self, area: f32) -> usize {
+        match self.cfg.spawn_density {
+            Some(density) => (density * area.abs()).round().max(0.0) as usize,
+            None => self.cfg.spawn_attractors,
+        }
+    }
+
+    /// Stable 64-bit fingerprint of the scene, for golden tests.
+    ///
+    /// FNV-1a over the step count, every node (position and radius bits,
+    /// parent), and every attractor (position bits, alive flag). Unlike
+    /// `std`'s hashers the algorithm is fixed, so equal scenes hash equally
+    /// on every machine and Rust version; see [`crate::math`] for making
+    /// the scenes themselves reproducible across platforms.
+    pub fn content_hash(&self) -> u64 {
+        const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+        let mut hash = OFFSET;
+        let mut feed = |x: u64| {
+            for byte in x.to_le_bytes() {
+                hash = (hash ^ byte as u64).wrapping_mul(PRIME);
+            }
+        };
+
+        feed(self.steps as u64);
+        for node in &self.tree.nodes {
+            feed(node.pos.x.to_bits() as u64);
+            feed(node.pos.y.to_bits() as u64);
+            feed(node.radius.to_bits() as u64);
+            feed(node.parent.map_or(u64::MAX, |p| p as u64));
+        }
+        for a in &self.attractors.points {
+            feed(a.pos.x.to_bits() as u64);
+            feed(a.pos.y.to_bits() as u64);
+            feed(a.alive as u64);
+        }
+        hash
+    }
+
+    /// Growth history used for direction damping.
+    #[cfg(feature = "serde")]
+    pub(crate) fn growth_state(&self) -> &GrowthState {
+        &self.growth
+    }
+
+    /// Number of alive attractors.
+    fn alive_count(&self) -> usize {
+        self.attractors.points.iter().filter(|a| a.alive).count()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
+mod tests {
+    use super::*;
+    use glam::Vec2;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn new_simulation_is_empty() {
+        let sim = Simulation::new(Config::default());
+        let stats = sim.stats();
+
+        assert_eq!(stats, SimStats::default());
+        assert!(sim.tree.nodes.is_empty());
+        assert!(sim.attractors.points.is_empty());
+    }
+
+    #[test]
+    fn from_tree_continues_growth_from_leaves() {
+        // A small Y-shaped skeleton: trunk plus two leaves.
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let trunk = tree.add_child(0, Vec2::new(0.0, 10.0), 1.0);
+        let left = tree.add_child(trunk, Vec2::new(-5.0, 15.0), 1.0);
+        let right = tree.add_child(trunk, Vec2::new(5.0, 15.0), 1.0);
+
+        let mut cfg = Config::default();
+        cfg.influence_radius = 20.0;
+        cfg.kill_radius = 2.0;
+        let mut sim = Simulation::from_tree(tree, cfg);
+        assert_eq!(sim.influence().count.len(), 4);
+
+        sim.attractors =
+            AttractorSet::from_positions(vec![Vec2::new(-15.0, 25.0), Vec2::new(15.0, 25.0)]);
+        let result = sim.step();
+
+        assert_eq!(result.new_ids.len(), 2);
+        let parents: Vec<_> = result
+            .new_ids
+            .iter()
+            .map(|&id| sim.tree.nodes[id].parent)
+            .collect();
+        assert!(parents.contains(&Some(left)));
+        assert!(parents.contains(&Some(right)));
+    }
+
+    #[test]
+    fn several_steps_grow_the_tree() {
+        let mut sim = Simulation::new(Config::default());
+        sim.add_root(Vec2::new(0.0, 0.0), 1.0);
+        sim.attractors = AttractorSet::from_positions(vec![Vec2::new(0.0, 50.0)]);
+
+        let mut grown = 0;
+        for _ in 0..5 {
+            grown += sim.step().new_ids.len();
+        }
+
+        let stats = sim.stats();
+        assert_eq!(stats.steps, 5);
+        assert!(grown > 0);
+        assert_eq!(stats.node_count, 1 + grown);
+        // The influence buffer always tracks the node count at the start of the last step.
+        assert!(sim.influence().count.len() <= stats.node_count);
+    }
+
+    #[test]
+    fn step_reports_killed_attractors() {
+        let mut cfg = Config::default();
+        cfg.kill_radius = 2.0;
+        let mut sim = Simulation::new(cfg);
+        sim.add_root(Vec2::new(0.0, 0.0), 1.0);
+        sim.attractors =
+            AttractorSet::from_positions(vec![Vec2::new(0.0, 1.0), Vec2::new(0.0, 40.0)]);
+
+        let result = sim.step();
+
+        assert_eq!(result.killed, 1);
+        assert_eq!(sim.stats().alive_attractors, 1);
+        assert_eq!(sim.stats().dead_attractors, 1);
+    }
+
+    #[test]
+    fn step_result_carries_totals() {
+        let mut sim = Simulation::new(Config::default());
+        sim.add_root(Vec2::ZERO, 1.0);
+        sim.attractors =
+            AttractorSet::from_positions(vec![Vec2::new(0.0, 50.0), Vec2::new(0.0, -500.0)]);
+
+        let result = sim.step();
+        let stats = sim.stats();
+
+        assert_eq!(result.node_count, stats.node_count);
+        assert_eq!(result.alive_attractors, stats.alive_attractors);
+    }
+
+    #[test]
+    fn summary_formats_deltas_and_totals() {
+        let result = StepResult {
+            new_ids: (0..8).collect(),
+            killed: 15,
+            node_count: 320,
+            alive_attractors: 742,
+        };
+
+        assert_eq!(
+            result.summary(12),
+            "step 12: +8 nodes, -15 attractors, 320 total nodes, 742 alive"
+        );
+    }
+
+    #[test]
+    fn seed_radial_creates_evenly_spaced_spokes() {
+        let mut sim = Simulation::new(Config::default());
+        let center = Vec2::new(3.0, -2.0);
+        let root = sim.seed_radial(center, 4, 5.0);
+
+        let children = sim.tree.nodes[root].children.clone();
+        assert_eq!(children.len(), 4);
+        assert_eq!(sim.tree.nodes.len(), 5);
+
+        let expected = [
+            Vec2::new(5.0, 0.0),
+            Vec2::new(0.0, 5.0),
+            Vec2::new(-5.0, 0.0),
+            Vec2::new(0.0, -5.0),
+        ];
+        for (&child, offset) in children.iter().zip(expected) {
+            let d = sim.tree.nodes[child].pos - center;
+            assert!((d.length() - 5.0).abs() < 1e-4);
+            assert!(
+                d.distance(offset) < 1e-4,
+                "spoke at {d:?}, expected {offset:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn preview_growth_matches_next_step_without_mutating() {
+        let mut sim = Simulation::new(Config::default());
+        sim.seed_radial(Vec2::ZERO, 3, 4.0);
+        sim.attractors =
+            AttractorSet::from_positions(vec![Vec2::new(0.0, 30.0), Vec2::new(25.0, -10.0)]);
+
+        let preview = sim.preview_growth();
+        assert_eq!(sim.stats().node_count, 4);
+
+        let result = sim.step();
+        let grown: Vec<(NodeId, Vec2)> = result
+            .new_ids
+            .iter()
+            .map(|&id| (sim.tree.nodes[id].parent.unwrap(), sim.tree.nodes[id].pos))
+            .collect();
+        assert_eq!(preview, grown);
+    }
+
+    #[test]
+    fn spawn_attractors_uses_configured_tool() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut sim = Simulation::new(Config::default());
+
+        sim.cfg.spawn_tool = SpawnTool::RectAttractors;
+        sim.cfg.spawn_attractors = 12;
+        assert_eq!(sim.spawn_attractors(Vec2::ZERO, &mut rng), 12);
+
+        sim.cfg.spawn_tool = SpawnTool::RootNode;
+        assert_eq!(sim.spawn_attractors(Vec2::ZERO, &mut rng), 0);
+
+        assert_eq!(sim.attractors.points.len(), 12);
+
+        sim.cfg.spawn_tool = SpawnTool::RectOutlineAttractors;
+        sim.cfg.spawn_rect_half_extents = Vec2::new(10.0, 5.0);
+        assert_eq!(sim.spawn_attractors(Vec2::ZERO, &mut rng), 12);
+        for a in &sim.attractors.points[12..] {
+            let on_x = (a.pos.x.abs() - 10.0).abs() < 1e-4;
+            let on_y = (a.pos.y.abs() - 5.0).abs() < 1e-4;
+            assert!(on_x || on_y, "{:?} is not on the outline", a.pos);
+        }
+
+        sim.cfg.spawn_tool = SpawnTool::LineAttractors;
+        assert_eq!(sim.spawn_attractors(Vec2::ZERO, &mut rng), 0);
+        sim.cfg.spawn_line_jitter = 1.0;
+        let (from, to) = (Vec2::new(0.0, 0.0), Vec2::new(0.0, 40.0));
+        assert_eq!(sim.spawn_along_line(from, to, &mut rng), 12);
+        for a in &sim.attractors.points[24..] {
+            assert!(a.pos.x.abs() <= 1.0 && (0.0..=40.0).contains(&a.pos.y));
+        }
+    }
+
+    #[test]
+    fn spawn_density_scales_count_with_area() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut sim = Simulation::new(Config::default());
+        sim.cfg.spawn_tool = SpawnTool::OvalAttractors;
+        sim.cfg.spawn_density = Some(0.05);
+
+        sim.cfg.spawn_oval_radii = Vec2::new(20.0, 10.0);
+        let small = sim.spawn_attractors(Vec2::ZERO, &mut rng);
+        sim.cfg.spawn_oval_radii = Vec2::new(40.0, 10.0);
+        let large = sim.spawn_attractors(Vec2::ZERO, &mut rng);
+
+        assert_eq!(
+            small,
+            (0.05 * std::f32::consts::PI * 200.0).round() as usize
+        );
+        assert!(large.abs_diff(2 * small) <= 1, "{small} -> {large}");
+
+        sim.cfg.spawn_tool = SpawnTool::RectAttractors;
+        sim.cfg.spawn_rect_half_extents = Vec2::new(10.0, 10.0);
+        assert_eq!(sim.spawn_attractors(Vec2::ZERO, &mut rng), 20);
+    }
+
+    #[test]
+    fn clear_resets_scene_but_keeps_config() {
+        let mut cfg = Config::default();
+        cfg.step_len = 3.0;
+        let mut sim = Simulation::new(cfg);
+        sim.add_root(Vec2::ZERO, 1.0);
+        sim.attractors = AttractorSet::from_positions(vec![Vec2::new(0.0, 10.0)]);
+        sim.step();
+
+        sim.clear();
+
+        assert_eq!(sim.stats(), SimStats::default());
+        assert_eq!(sim.cfg.step_len, 3.0);
+    }
+
+    #[test]
+    fn follow_target_steers_growth_without_joining_the_set() {
+        let mut sim = Simulation::new(Config::default());
+        sim.add_root(Vec2::ZERO, 1.0);
+        sim.follow_target = Some(Vec2::new(500.0, 0.0));
+
+        let result = sim.step();
+
+        assert_eq!(result.new_ids.len(), 1);
+        let child = sim.tree.nodes[result.new_ids[0]].pos;
+        assert!(child.x > 0.0 && child.y.abs() < 1e-5, "{child}");
+        assert!(sim.attractors.points.is_empty());
+
+        sim.follow_target = None;
+        assert!(sim.step().new_ids.is_empty());
+    }
+
+    #[test]
+    fn content_hash_tracks_scene_changes() {
+        let mut sim = Simulation::new(Config::default());
+        sim.add_root(Vec2::ZERO, 1.0);
+        sim.attractors = AttractorSet::from_positions(vec![Vec2::new(0.0, 20.0)]);
+
+        let before = sim.content_hash();
+        assert_eq!(sim.clone().content_hash(), before);
+        sim.step();
+        assert_ne!(sim.content_hash(), before);
+    }
+
+    #[cfg(feature = "deterministic")]
+    #[test]
+    fn deterministic_golden_hash() {
+        let mut cfg = Config::default();
+        cfg.kill_radius = 5.0;
+        let mut sim = Simulation::new(cfg);
+        sim.add_root(Vec2::ZERO, 1.0);
+        sim.attractors = AttractorSet::from_positions(
+            (0..40)
+                .map(|i| Vec2::new((i % 8) as f32 * 7.0 - 24.0, 20.0 + (i / 8) as f32 * 9.0))
+                .collect(),
+        );
+        for _ in 0..30 {
+            sim.step();
+        }
+        assert!(sim.tree.nodes.len() > 10);
+        assert_eq!(sim.content_hash(), 3803389909135663399);
+    }
+}
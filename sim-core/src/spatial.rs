@@ -0,0 +1,509 @@
+//! Uniform-grid spatial index for radius queries.
+//!
+//! [`SpatialGrid`] buckets points into square cells so that "everything
+//! within `r` of `p`" only has to look at the few cells overlapping the
+//! query circle instead of every point. Nearest-neighbour queries search
+//! outward ring by ring and stop as soon as no unvisited cell can hold a
+//! closer point.
+//!
+//! [`NearestIndex`] abstracts over the nearest-node queries used by the
+//! simulation phases, so a grid, a brute-force scan over a [`Tree`], or a
+//! custom structure can answer them interchangeably.
+
+use crate::{tree::Tree, types::NodeId};
+use glam::Vec2;
+use std::collections::HashMap;
+
+/// Nearest-node queries over a set of tree nodes.
+///
+/// Implementations must rank nodes by squared distance, breaking ties by
+/// the lower id, so that every index returns exactly the same results
+/// (see [`crate::phases::attraction_phase_with`]).
+pub trait NearestIndex {
+    /// Finds the *k*-th nearest node among those accepted by `pred`.
+    ///
+    /// `k = 0` is the nearest; if fewer than `k + 1` nodes are accepted,
+    /// the farthest accepted one is returned.
+    ///
+    /// ### Returns
+    /// `Some((id, dist2))`, or `None` if no node is accepted.
+    fn kth_nearest_where(
+        &self,
+        pos: Vec2,
+        k: usize,
+        pred: impl Fn(NodeId) -> bool,
+    ) -> Option<(NodeId, f32)>;
+
+    /// Finds the `k` nearest nodes accepted by `pred`.
+    ///
+    /// ### Returns
+    /// Up to `k` `(id, dist2)` pairs sorted by ascending distance (then id).
+    fn k_nearest_where(
+        &self,
+        pos: Vec2,
+        k: usize,
+        pred: impl Fn(NodeId) -> bool,
+    ) -> Vec<(NodeId, f32)>;
+
+    /// Returns the ids of all nodes within `radius` of `pos` (inclusive),
+    /// in unspecified order.
+    fn within(&self, pos: Vec2, radius: f32) -> Vec<NodeId>;
+
+    /// Finds the node nearest to `pos`.
+    ///
+    /// ### Returns
+    /// `Some((id, dist2))`, or `None` if the index is empty.
+    fn nearest(&self, pos: Vec2) -> Option<(NodeId, f32)> {
+        self.kth_nearest_where(pos, 0, |_| true)
+    }
+
+    /// Finds the *k*-th nearest node (`k = 0` is the nearest).
+    ///
+    /// ### Returns
+    /// `Some((id, dist2))`, or `None` if the index is empty.
+    fn kth_nearest(&self, pos: Vec2, k: usize) -> Option<(NodeId, f32)> {
+        self.kth_nearest_where(pos, k, |_| true)
+    }
+}
+
+/// A uniform grid of square cells storing `(id, position)` entries.
+///
+/// Ids are opaque to the grid; typically they are [`crate::types::NodeId`]
+/// values or attractor indices.
+///
+/// ### Fields
+/// - `cell_size` - Side length of a grid cell in world units.
+/// - `cells` - Entries bucketed by integer cell coordinate.
+/// - `len` - Number of entries.
+/// - `min_cell` / `max_cell` - Bounding box of the occupied cells
+///   (meaningless while the grid is empty).
+#[derive(Debug, Clone)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<(usize, Vec2)>>,
+    len: usize,
+    min_cell: (i32, i32),
+    max_cell: (i32, i32),
+}
+
+impl SpatialGrid {
+    /// Creates an empty grid.
+    ///
+    /// ### Parameters
+    /// - `cell_size` - Side length of a cell. A good choice is the typical
+    ///   query radius. Non-positive values are clamped to a tiny minimum.
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(1e-3),
+            cells: HashMap::new(),
+            len: 0,
+            min_cell: (0, 0),
+            max_cell: (0, 0),
+        }
+    }
+
+    /// Number of entries in the grid.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the grid has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Integer cell coordinate containing `pos`.
+    fn cell_of(&self, pos: Vec2) -> (i32, i32) {
+        let c = (pos / self.cell_size).floor();
+        (c.x as i32, c.y as i32)
+    }
+
+    /// Adds an entry at `pos`.
+    ///
+    /// ### Parameters
+    /// - `id` - Caller-defined identifier returned by queries.
+    /// - `pos` - World-space position of the entry.
+    pub fn insert(&mut self, id: usize, pos: Vec2) {
+        let cell = self.cell_of(pos);
+        if self.len == 0 {
+            self.min_cell = cell;
+            self.max_cell = cell;
+        } else {
+            self.min_cell = (self.min_cell.0.min(cell.0), self.min_cell.1.min(cell.1));
+            self.max_cell = (self.max_cell.0.max(cell.0), self.max_cell.1.max(cell.1));
+        }
+        self.cells.entry(cell).or_default().push((id, pos));
+        self.len += 1;
+    }
+
+    /// Removes all entries, keeping the cell size.
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.len = 0;
+    }
+
+    /// Returns the ids of all entries within `radius` of `pos`.
+    ///
+    /// ### Parameters
+    /// - `pos` - Query center.
+    /// - `radius` - Query radius (inclusive).
+    ///
+    /// ### Returns
+    /// Matching ids in unspecified order.
+    pub fn query_radius(&self, pos: Vec2, radius: f32) -> Vec<usize> {
+        let (x0, y0) = self.cell_of(pos - Vec2::splat(radius));
+        let (x1, y1) = self.cell_of(pos + Vec2::splat(radius));
+        let r2 = radius * radius;
+
+        let mut out = Vec::new();
+        for cx in x0..=x1 {
+            for cy in y0..=y1 {
+                let Some(entries) = self.cells.get(&(cx, cy)) else {
+                    continue;
+                };
+                out.extend(
+                    entries
+                        .iter()
+                        .filter(|(_, p)| p.distance_squared(pos) <= r2)
+                        .map(|&(id, _)| id),
+                );
+            }
+        }
+        out
+    }
+
+    /// Finds the entry nearest to `pos`.
+    ///
+    /// ### Returns
+    /// - `Some((id, dist2))` with the nearest entry and its squared
+    ///   distance (ties go to the lower id), or
+    /// - `None` if the grid is empty.
+    pub fn query_nearest(&self, pos: Vec2) -> Option<(usize, f32)> {
+        self.query_kth_nearest_where(pos, 0, |_| true)
+    }
+
+    /// Finds the *k*-th nearest entry among those accepted by `pred`.
+    ///
+    /// Matches [`crate::tree::Tree::find_kth_nearest_ids_where`]: entries
+    /// are ranked by squared distance (then id), `k = 0` is the nearest,
+    /// and if fewer than `k + 1` entries are accepted the farthest accepted
+    /// one is returned.
+    ///
+    /// ### Parameters
+    /// - `pos` - Query position.
+    /// - `k` - Zero-based rank of the accepted entry to retrieve.
+    /// - `pred` - Filter deciding which ids take part in the search.
+    ///
+    /// ### Returns
+    /// - `Some((id, dist2))` with the selected entry and squared distance, or
+    /// - `None` if no entry is accepted.
+    pub fn query_kth_nearest_where(
+        &self,
+        pos: Vec2,
+        k: usize,
+        pred: impl Fn(usize) -> bool,
+    ) -> Option<(usize, f32)> {
+        // The k-th is the farthest of the k + 1 nearest (or of all, if fewer).
+        self.collect_nearest(pos, k + 1, pred)
+            .into_iter()
+            .max_by(by_dist_then_id)
+    }
+
+    /// Finds the `k` nearest entries accepted by `pred`.
+    ///
+    /// Uses the same ring search and ranking as
+    /// [`SpatialGrid::query_kth_nearest_where`].
+    ///
+    /// ### Parameters
+    /// - `pos` - Query position.
+    /// - `k` - Number of entries to return.
+    /// - `pred` - Filter deciding which ids take part in the search.
+    ///
+    /// ### Returns
+    /// Up to `k` `(id, dist2)` pairs sorted by ascending distance (then id);
+    /// fewer if fewer entries are accepted.
+    pub fn query_k_nearest_where(
+        &self,
+        pos: Vec2,
+        k: usize,
+        pred: impl Fn(usize) -> bool,
+    ) -> Vec<(usize, f32)> {
+        let mut found = self.collect_nearest(pos, k, pred);
+        found.sort_by(by_dist_then_id);
+        found
+    }
+
+    /// Collects the `count` nearest accepted entries in unspecified order
+    /// (all accepted entries if there are fewer).
+    ///
+    /// Cells are searched in square rings around the cell containing
+    /// `pos`. Once the `count`-th candidate is closer than anything outside
+    /// the rings searched so far, the search stops. If the rings would
+    /// cover more cells than are occupied, the occupied cells are scanned
+    /// directly instead.
+    fn collect_nearest(
+        &self,
+        pos: Vec2,
+        count: usize,
+        pred: impl Fn(usize) -> bool,
+    ) -> Vec<(usize, f32)> {
+        let mut found: Vec<(usize, f32)> = Vec::new();
+        if self.len == 0 || count == 0 {
+            return found;
+        }
+        let (cx, cy) = self.cell_of(pos);
+        let last_ring = (cx - self.min_cell.0)
+            .max(self.max_cell.0 - cx)
+            .max(cy - self.min_cell.1)
+            .max(self.max_cell.1 - cy)
+            .max(0);
+
+        let mut exhausted = true;
+        for ring in 0..=last_ring {
+            let ring_cells = (2 * ring as usize + 1).pow(2);
+            if ring_cells > 2 * self.cells.len() {
+                exhausted = false;
+                break;
+            }
+            for cell in ring_cells_around(cx, cy, ring) {
+                if let Some(entries) = self.cells.get(&cell) {
+                    push_accepted(&mut found, entries, pos, &pred);
+                }
+            }
+            if found.len() >= count {
+                found.select_nth_unstable_by(count - 1, by_dist_then_id);
+                // Unvisited entries are at least `ring` whole cells away.
+                let reach = ring as f32 * self.cell_size;
+                if found[count - 1].1 < reach * reach {
+                    found.truncate(count);
+                    return found;
+                }
+            }
+        }
+
+        if !exhausted {
+            found.clear();
+            for entries in self.cells.values() {
+                push_accepted(&mut found, entries, pos, &pred);
+            }
+        }
+        if found.len() > count {
+            found.select_nth_unstable_by(count - 1, by_dist_then_id);
+            found.truncate(count);
+        }
+        found
+    }
+}
+
+/// Brute-force queries that scan every node of the tree.
+impl NearestIndex for Tree {
+    fn kth_nearest_where(
+        &self,
+        pos: Vec2,
+        k: usize,
+        pred: impl Fn(NodeId) -> bool,
+    ) -> Option<(NodeId, f32)> {
+        self.find_kth_nearest_ids_where(pos, k, pred)
+    }
+
+    fn k_nearest_where(
+        &self,
+        pos: Vec2,
+        k: usize,
+        pred: impl Fn(NodeId) -> bool,
+    ) -> Vec<(NodeId, f32)> {
+        self.find_k_nearest_ids_where(pos, k, pred)
+    }
+
+    fn within(&self, pos: Vec2, radius: f32) -> Vec<NodeId> {
+        let r2 = radius * radius;
+        (0..self.nodes.len())
+            .filter(|&id| self.nodes[id].pos.distance_squared(pos) <= r2)
+            .collect()
+    }
+}
+
+/// Grid queries; the grid's ids are taken to be node ids.
+impl NearestIndex for SpatialGrid {
+    fn kth_nearest_where(
+        &self,
+        pos: Vec2,
+        k: usize,
+        pred: impl Fn(NodeId) -> bool,
+    ) -> Option<(NodeId, f32)> {
+        self.query_kth_nearest_where(pos, k, pred)
+    }
+
+    fn k_nearest_where(
+        &self,
+        pos: Vec2,
+        k: usize,
+        pred: impl Fn(NodeId) -> bool,
+    ) -> Vec<(NodeId, f32)> {
+        self.query_k_nearest_where(pos, k, pred)
+    }
+
+    fn within(&self, pos: Vec2, radius: f32) -> Vec<NodeId> {
+        self.query_radius(pos, radius)
+    }
+}
+
+/// Appends `(id, dist2)` for every entry accepted by `pred`.
+fn push_accepted(
+    found: &mut Vec<(usize, f32)>,
+    entries: &[(usize, Vec2)],
+    pos: Vec2,
+    pred: &impl Fn(usize) -> bool,
+) {
+    found.extend(
+        entries
+            .iter()
+            .filter(|&&(id, _)| pred(id))
+            .map(|&(id, p)| (id, p.distance_squared(pos))),
+    );
+}
+
+/// Orders `(id, dist2)` pairs by distance, breaking ties by id.
+fn by_dist_then_id(a: &(usize, f32), b: &(usize, f32)) -> std::cmp::Ordering {
+    a.1.total_cmp(&b.1).then(a.0.cmp(&b.0))
+}
+
+/// Cells on the square ring at Chebyshev distance `ring` from `(cx, cy)`.
+fn ring_cells_around(cx: i32, cy: i32, ring: i32) -> impl Iterator<Item = (i32, i32)> {
+    let horizontal = (cx - ring..=cx + ring).flat_map(move |x| {
+        let bottom = (x, cy - ring);
+        let top = (ring > 0).then_some((x, cy + ring));
+        std::iter::once(bottom).chain(top)
+    });
+    let vertical = (cy - ring + 1..cy + ring).flat_map(move |y| [(cx - ring, y), (cx + ring, y)]);
+    horizontal.chain(vertical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_radius_returns_only_points_inside_radius() {
+        let mut grid = SpatialGrid::new(2.0);
+        grid.insert(0, Vec2::new(0.0, 0.0));
+        grid.insert(1, Vec2::new(1.5, 0.0));
+        grid.insert(2, Vec2::new(-3.0, -3.0));
+        grid.insert(3, Vec2::new(10.0, 10.0));
+
+        let mut found = grid.query_radius(Vec2::new(0.5, 0.0), 1.5);
+        found.sort();
+        assert_eq!(found, vec![0, 1]);
+
+        let mut found = grid.query_radius(Vec2::ZERO, 5.0);
+        found.sort();
+        assert_eq!(found, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn clear_empties_the_grid() {
+        let mut grid = SpatialGrid::new(1.0);
+        grid.insert(7, Vec2::new(0.2, 0.3));
+        grid.clear();
+        assert!(grid.query_radius(Vec2::ZERO, 10.0).is_empty());
+    }
+
+    /// Brute-force reference with the grid's ranking and fallback rules.
+    fn brute_kth(
+        points: &[Vec2],
+        pos: Vec2,
+        k: usize,
+        pred: impl Fn(usize) -> bool,
+    ) -> Option<(usize, f32)> {
+        let mut all: Vec<(usize, f32)> = points
+            .iter()
+            .enumerate()
+            .filter(|&(id, _)| pred(id))
+            .map(|(id, p)| (id, p.distance_squared(pos)))
+            .collect();
+        all.sort_by(by_dist_then_id);
+        all.get(k).or(all.last()).copied()
+    }
+
+    #[test]
+    fn nearest_queries_match_brute_force_on_random_clouds() {
+        use rand::{Rng, SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(11);
+        for &(count, cell) in &[(1usize, 5.0f32), (50, 3.0), (400, 10.0), (400, 0.5)] {
+            let points: Vec<Vec2> = (0..count)
+                .map(|_| Vec2::new(rng.random_range(-50.0..50.0), rng.random_range(-50.0..50.0)))
+                .collect();
+            let mut grid = SpatialGrid::new(cell);
+            for (id, &p) in points.iter().enumerate() {
+                grid.insert(id, p);
+            }
+            assert_eq!(grid.len(), count);
+
+            for _ in 0..100 {
+                // Include queries far outside the cloud.
+                let pos = Vec2::new(
+                    rng.random_range(-200.0..200.0),
+                    rng.random_range(-200.0..200.0),
+                );
+                let k = rng.random_range(0..5);
+                assert_eq!(
+                    grid.query_nearest(pos),
+                    brute_kth(&points, pos, 0, |_| true)
+                );
+                assert_eq!(
+                    grid.query_kth_nearest_where(pos, k, |id| id % 3 != 0),
+                    brute_kth(&points, pos, k, |id| id % 3 != 0)
+                );
+                let list = grid.query_k_nearest_where(pos, k, |_| true);
+                assert_eq!(list.len(), k.min(count));
+                if k > 0 {
+                    assert_eq!(
+                        list.last().copied(),
+                        brute_kth(&points, pos, k - 1, |_| true)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn kth_query_falls_back_to_farthest_and_handles_empty() {
+        let mut grid = SpatialGrid::new(1.0);
+        assert_eq!(grid.query_nearest(Vec2::ZERO), None);
+
+        grid.insert(0, Vec2::new(1.0, 0.0));
+        grid.insert(1, Vec2::new(5.0, 0.0));
+        assert_eq!(
+            grid.query_kth_nearest_where(Vec2::ZERO, 9, |_| true),
+            Some((1, 25.0))
+        );
+        assert_eq!(grid.query_kth_nearest_where(Vec2::ZERO, 0, |_| false), None);
+    }
+
+    #[test]
+    fn tree_and_grid_indices_agree() {
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        for i in 1..40 {
+            let parent = i / 2;
+            let pos = tree.nodes[parent].pos + Vec2::new((i % 3) as f32 - 1.0, 1.0);
+            tree.add_child(parent, pos, 1.0);
+        }
+        let grid = tree.build_grid(1.5);
+
+        for pos in [
+            Vec2::new(0.3, 2.0),
+            Vec2::new(-4.0, 9.0),
+            Vec2::new(20.0, -3.0),
+        ] {
+            assert_eq!(tree.nearest(pos), grid.nearest(pos));
+            assert_eq!(tree.kth_nearest(pos, 4), grid.kth_nearest(pos, 4));
+            let mut a = tree.within(pos, 3.0);
+            let mut b = grid.within(pos, 3.0);
+            a.sort_unstable();
+            b.sort_unstable();
+            assert_eq!(a, b);
+        }
+        assert!(!grid.within(Vec2::new(0.3, 2.0), 3.0).is_empty());
+    }
+}
@@ -1,25 +1,114 @@
-use crate::types::NodeId;
+use crate::attractor::AttractorSet;
+use crate::edge_buffer::EdgeBuffer;
+use crate::spatial::SpatialGrid;
+use crate::types::{NodeId, NodeIndex};
 use glam::Vec2;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::ops::{Index, IndexMut};
 
 /// A single node in the tree structure.
 ///
 /// Each node stores its position, radius (thickness), an optional parent
-/// reference, and a list of children. The tree as a whole is stored in a
-/// contiguous `Vec<TreeNode>`, and [`NodeId`] is used as the index.
+/// reference, a list of children, and the attractor groups it responds to.
+/// The tree as a whole is stored in a contiguous `Vec<TreeNode>`, and
+/// [`NodeId`] is used as the index.
 ///
 /// ### Fields
 /// - `pos` - World-space position of this node.
 /// - `radius` - Radius or thickness of the branch at this node.
 /// - `parent` - Optional parent node ID; `None` for root / free nodes.
 /// - `children` - IDs of this node's direct children.
-#[derive(Debug)]
+/// - `group_mask` - Bit mask of attractor groups allowed to influence this
+///   node (bit `g` set = group `g` allowed). Children inherit their
+///   parent's mask, so a mask set on a root applies to its whole tree.
+/// - `born_step` - Growth step in which this node was added (`0` for the
+///   initial nodes). Set by the growth phase, see [`Tree::age`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TreeNode {
     pub pos: Vec2,
     pub radius: f32,
     pub parent: Option<NodeId>,
     pub children: Vec<NodeId>,
+    pub group_mask: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub born_step: u32,
 }
 
+/// How [`Tree::apply_radius_model`] derives branch radii from the topology.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum RadiusModel {
+    /// Keep the radii as grown: every child inherits its parent's radius.
+    #[default]
+    Inherit,
+    /// Murray / da Vinci rule, see [`Tree::recompute_radii`].
+    Murray,
+    /// Radius from the node's Strahler order (see
+    /// [`Tree::strahler_numbers`]): `leaf_radius * 2^((order - 1) / exponent)`.
+    Strahler,
+}
+
+/// Settings shared by the [`RadiusModel`]s.
+///
+/// ### Fields
+/// - `leaf_radius` - Radius of leaves (Strahler order `1`).
+/// - `exponent` - Murray exponent `n`. The Strahler model uses it too, so
+///   that both models agree on perfectly balanced binary trees.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RadiusParams {
+    pub leaf_radius: f32,
+    pub exponent: f32,
+}
+
+impl Default for RadiusParams {
+    /// Leaf radius `0.5` and exponent `2.5`.
+    fn default() -> Self {
+        Self {
+            leaf_radius: 0.5,
+            exponent: 2.5,
+        }
+    }
+}
+
+/// Summary metrics of a tree, for comparing parameter sets.
+///
+/// ### Fields
+/// - `node_count` - Number of nodes.
+/// - `leaf_count` - Number of nodes without children.
+/// - `total_length` - Summed length of all parent–child edges.
+/// - `max_depth` - Largest number of edges from a node to its root.
+/// - `avg_branching` - Mean number of children over the nodes that have
+///   any (`0` for a tree of lone roots).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TreeStats {
+    pub node_count: usize,
+    pub leaf_count: usize,
+    pub total_length: f32,
+    pub max_depth: u32,
+    pub avg_branching: f32,
+}
+
+/// Error returned by fallible [`Tree`] edits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeError {
+    /// The given id does not name a node of the tree.
+    InvalidNode(NodeId),
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeError::InvalidNode(id) => write!(f, "no node with id {id}"),
+        }
+    }
+}
+
+impl std::error::Error for TreeError {}
+
 /// A simple tree of nodes stored in a flat array.
 ///
 /// Nodes are indexed by [`NodeId`] (typically an index into `nodes`), and
@@ -28,7 +117,17 @@ pub struct TreeNode {
 ///
 /// The root node is usually created via [`Tree::new`], but additional
 /// “free” roots can be added using [`Tree::add_free_node`].
-#[derive(Debug)]
+///
+/// ### Compacted ids
+///
+/// Methods that remove nodes ([`Tree::weld_coincident`],
+/// [`Tree::trim_to_depth`], [`Tree::remove_node`],
+/// [`Tree::prune_leaves_without_attractors`]) compact the node array
+/// afterwards: kept nodes keep their relative order but may get lower ids.
+/// Any ids held outside the tree (attractor owners, growth state,
+/// recordings) become stale.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tree {
     pub nodes: Vec<TreeNode>,
 }
@@ -36,7 +135,8 @@ pub struct Tree {
 impl TreeNode {
     /// Creates a new root node with no parent.
     ///
-    /// The node is initialized with an empty `children` list.
+    /// The node is initialized with an empty `children` list and a
+    /// `group_mask` that accepts every attractor group.
     ///
     /// ### Parameters
     /// - `pos` - Position of the root node.
@@ -50,13 +150,16 @@ impl TreeNode {
             radius,
             parent: None,
             children: Vec::with_capacity(4),
+            group_mask: u32::MAX,
+            born_step: 0,
         }
     }
 
     /// Creates a new child node with a given parent.
     ///
     /// The node itself does not update the parent's `children` list;
-    /// this is handled by [`Tree::add_child`].
+    /// this is handled by [`Tree::add_child`]. The `group_mask` accepts
+    /// every attractor group.
     ///
     /// ### Parameters
     /// - `pos` - Position of the child node.
@@ -71,6 +174,8 @@ impl TreeNode {
             radius,
             parent: Some(parent),
             children: Vec::with_capacity(4),
+            group_mask: u32::MAX,
+            born_step: 0,
         }
     }
 }
@@ -93,6 +198,99 @@ impl Tree {
         }
     }
 
+    /// Returns every parent–child edge as a `(parent_pos, child_pos)` pair.
+    ///
+    /// Edges are yielded in child id order. Use [`Tree::edge_buffer`] to
+    /// walk an unchanged tree's edges repeatedly without following
+    /// `parent` links each time.
+    pub fn edges(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        self.nodes
+            .iter()
+            .filter_map(|n| n.parent.map(|p| (self.nodes[p].pos, n.pos)))
+    }
+
+    /// Collects the edges into a contiguous [`EdgeBuffer`].
+    ///
+    /// The buffer is a snapshot; rebuild it with [`EdgeBuffer::rebuild`]
+    /// after the tree changed.
+    pub fn edge_buffer(&self) -> EdgeBuffer {
+        EdgeBuffer::new(self)
+    }
+
+    /// Produces a reduced edge set with at most `max_nodes` segments, for display.
+    ///
+    /// Keeps every `N`-th node (by id, so all parts of the tree are thinned
+    /// evenly) and connects each kept node to its nearest kept ancestor,
+    /// collapsing the skipped nodes in between into one straight segment.
+    /// Roots always act as anchors. The nodes at the four extremes of the
+    /// edges' bounding box are kept too, so the overall extent is preserved
+    /// as long as `max_nodes >= 4`.
+    ///
+    /// ### Parameters
+    /// - `max_nodes` - Maximum number of segments to return.
+    ///
+    /// ### Returns
+    /// `(ancestor_pos, node_pos)` segments; all of [`Tree::edges`] if they
+    /// already fit.
+    pub fn decimate(&self, max_nodes: usize) -> Vec<(Vec2, Vec2)> {
+        let edges: Vec<_> = self.edges().collect();
+        if edges.len() <= max_nodes {
+            return edges;
+        }
+
+        // Extremes are picked among non-root nodes; an extreme root is
+        // represented by one of its children, whose segment ends at it.
+        let mut keep = vec![false; self.nodes.len()];
+        let mut budget = max_nodes;
+        let in_edges = |id: &usize| {
+            let n = &self.nodes[*id];
+            n.parent.is_some() || !n.children.is_empty()
+        };
+        let ids = || (0..self.nodes.len()).filter(in_edges);
+        let extremes = [
+            ids().min_by(|&a, &b| self.nodes[a].pos.x.total_cmp(&self.nodes[b].pos.x)),
+            ids().max_by(|&a, &b| self.nodes[a].pos.x.total_cmp(&self.nodes[b].pos.x)),
+            ids().min_by(|&a, &b| self.nodes[a].pos.y.total_cmp(&self.nodes[b].pos.y)),
+            ids().max_by(|&a, &b| self.nodes[a].pos.y.total_cmp(&self.nodes[b].pos.y)),
+        ];
+        for id in extremes.into_iter().flatten() {
+            let id = match self.nodes[id].parent {
+                Some(_) => id,
+                None => self.nodes[id].children[0],
+            };
+            if budget > 0 && !keep[id] {
+                keep[id] = true;
+                budget -= 1;
+            }
+        }
+
+        // Spread the remaining budget evenly over the other child nodes.
+        let rest: Vec<NodeId> = (0..self.nodes.len())
+            .filter(|&id| self.nodes[id].parent.is_some() && !keep[id])
+            .collect();
+        let stride = rest.len().div_ceil(budget.max(1)).max(1);
+        for &id in rest.iter().step_by(stride).take(budget) {
+            keep[id] = true;
+        }
+
+        // Nearest kept ancestor (or root) per node, in one forward pass.
+        let mut anchor: Vec<NodeId> = (0..self.nodes.len()).collect();
+        for (id, node) in self.nodes.iter().enumerate() {
+            if let Some(p) = node.parent {
+                anchor[id] = if keep[p] || self.nodes[p].parent.is_none() {
+                    p
+                } else {
+                    anchor[p]
+                };
+            }
+        }
+
+        (0..self.nodes.len())
+            .filter(|&id| keep[id])
+            .map(|id| (self.nodes[anchor[id]].pos, self.nodes[id].pos))
+            .collect()
+    }
+
     /// Adds a new “free” node that has no parent.
     ///
     /// This is effectively another root in the forest stored inside `Tree`.
@@ -112,7 +310,8 @@ impl Tree {
     /// Adds a new child node under the given parent.
     ///
     /// This method:
-    /// - Appends a new [`TreeNode`] to `nodes` with `parent = Some(parent)`.
+    /// - Appends a new [`TreeNode`] to `nodes` with `parent = Some(parent)`
+    ///   and the parent's `group_mask` and `born_step`.
     /// - Pushes the new node's id into `parent`'s `children` list.
     ///
     /// ### Parameters
@@ -124,11 +323,200 @@ impl Tree {
     /// The [`NodeId`] (index) of the newly added child node.
     pub fn add_child(&mut self, parent: NodeId, pos: Vec2, radius: f32) -> NodeId {
         let id: usize = self.nodes.len();
-        self.nodes.push(TreeNode::new_child(pos, radius, parent));
+        let mut child = TreeNode::new_child(pos, radius, parent);
+        child.group_mask = self.nodes[parent].group_mask;
+        child.born_step = self.nodes[parent].born_step;
+        self.nodes.push(child);
         self.nodes[parent].children.push(id);
         id
     }
 
+    /// Grows a chain of children from `start` that traces a path.
+    ///
+    /// Starting at `start`, children are added in steps of `step_len`
+    /// straight toward each waypoint in turn. The last step toward a
+    /// waypoint is shortened so the chain passes exactly through it.
+    /// This bypasses attractors entirely, for scripted branches.
+    ///
+    /// Children inherit the radius of the node they grow from.
+    ///
+    /// ### Parameters
+    /// - `start` - Node to grow from.
+    /// - `waypoints` - Positions to visit, in order.
+    /// - `step_len` - Maximum edge length. Non-positive values grow nothing.
+    ///
+    /// ### Returns
+    /// The ids of the new nodes, from `start` outward.
+    pub fn grow_path_to(
+        &mut self,
+        start: NodeId,
+        waypoints: &[Vec2],
+        step_len: f32,
+    ) -> Vec<NodeId> {
+        let mut new_ids = Vec::new();
+        if step_len <= 0.0 {
+            return new_ids;
+        }
+
+        let mut tip = start;
+        for &target in waypoints {
+            loop {
+                let pos = self.nodes[tip].pos;
+                let to_target = target - pos;
+                let dist = to_target.length();
+                if dist <= 1e-6 {
+                    break;
+                }
+                let next = if dist <= step_len {
+                    target
+                } else {
+                    pos + to_target / dist * step_len
+                };
+                tip = self.add_child(tip, next, self.nodes[tip].radius);
+                new_ids.push(tip);
+            }
+        }
+        new_ids
+    }
+
+    /// Sets the attractor group mask of a node and all of its descendants.
+    ///
+    /// Calling this on a root restricts which attractor groups can pull on
+    /// that whole tree; nodes grown later inherit the mask automatically.
+    ///
+    /// ### Parameters
+    /// - `id` - Node whose subtree should be updated (typically a root).
+    /// - `mask` - New group mask (bit `g` set = group `g` allowed).
+    pub fn set_group_mask(&mut self, id: NodeId, mask: u32) {
+        let mut stack = vec![id];
+        while let Some(n) = stack.pop() {
+            self.nodes[n].group_mask = mask;
+            stack.extend_from_slice(&self.nodes[n].children);
+        }
+    }
+
+    /// Axis-aligned bounding box of all node positions.
+    ///
+    /// ### Returns
+    /// The `(min, max)` corners, or `None` for an empty tree. A single
+    /// node yields a zero-sized box at its position.
+    pub fn bounds(&self) -> Option<(Vec2, Vec2)> {
+        let first = self.nodes.first()?.pos;
+        Some(self.nodes.iter().fold((first, first), |(min, max), n| {
+            (min.min(n.pos), max.max(n.pos))
+        }))
+    }
+
+    /// Axis-aligned bounding box of a node and all of its descendants.
+    ///
+    /// ### Parameters
+    /// - `id` - Root of the subtree to measure.
+    ///
+    /// ### Returns
+    /// The `(min, max)` corners of the subtree's node positions. A leaf
+    /// yields a zero-sized box at its own position.
+    pub fn subtree_bounds(&self, id: NodeId) -> (Vec2, Vec2) {
+        let (mut min, mut max) = (self.nodes[id].pos, self.nodes[id].pos);
+        let mut stack = vec![id];
+        while let Some(n) = stack.pop() {
+            min = min.min(self.nodes[n].pos);
+            max = max.max(self.nodes[n].pos);
+            stack.extend_from_slice(&self.nodes[n].children);
+        }
+        (min, max)
+    }
+
+    /// Finds nodes that cannot be reached from any root.
+    ///
+    /// A root is a node without a parent (including free nodes). Starting
+    /// from every root, the `children` lists are followed; any node never
+    /// visited is an orphan. In a tree built only through the public
+    /// methods this is empty, but editing `nodes` directly can break it,
+    /// e.g. by forming a parent cycle or by dropping a child from its
+    /// parent's `children` list. Out-of-range child ids are ignored.
+    ///
+    /// ### Returns
+    /// The orphaned node ids in ascending order.
+    pub fn orphans(&self) -> Vec<NodeId> {
+        let mut reached = vec![false; self.nodes.len()];
+        let mut stack: Vec<NodeId> = (0..self.nodes.len())
+            .filter(|&id| self.nodes[id].parent.is_none())
+            .collect();
+        while let Some(id) = stack.pop() {
+            if std::mem::replace(&mut reached[id], true) {
+                continue;
+            }
+            stack.extend(
+                self.nodes[id]
+                    .children
+                    .iter()
+                    .filter(|&&c| c < self.nodes.len() && !reached[c]),
+            );
+        }
+        (0..self.nodes.len()).filter(|&id| !reached[id]).collect()
+    }
+
+    /// Returns the depth of a node, i.e. the number of edges to its root.
+    ///
+    /// Roots and free nodes have depth `0`.
+    ///
+    /// ### Parameters
+    /// - `id` - The node to measure.
+    pub fn depth(&self, id: NodeId) -> usize {
+        let mut depth = 0;
+        let mut cur = id;
+        while let Some(parent) = self.nodes[cur].parent {
+            depth += 1;
+            cur = parent;
+        }
+        depth
+    }
+
+    /// Returns the root a node descends from.
+    ///
+    /// Walks parent links up to a node without a parent; roots and free
+    /// nodes are their own root.
+    ///
+    /// ### Parameters
+    /// - `id` - The node to start from.
+    ///
+    /// ### Returns
+    /// The id of the topmost ancestor of `id`.
+    pub fn root_of(&self, id: NodeId) -> NodeId {
+        let mut cur = id;
+        while let Some(parent) = self.nodes[cur].parent {
+            cur = parent;
+        }
+        cur
+    }
+
+    /// Ratio of a node's path length to its root over the straight-line
+    /// distance between the two.
+    ///
+    /// `1.0` means the branch runs perfectly straight; larger values mean
+    /// a wigglier path.
+    ///
+    /// ### Parameters
+    /// - `leaf` - The node to measure from (usually a leaf).
+    ///
+    /// ### Returns
+    /// The tortuosity, or `1.0` if the node coincides with its root
+    /// (including roots themselves).
+    pub fn tortuosity(&self, leaf: NodeId) -> f32 {
+        let mut path = 0.0;
+        let mut cur = leaf;
+        while let Some(parent) = self.nodes[cur].parent {
+            path += self.nodes[cur].pos.distance(self.nodes[parent].pos);
+            cur = parent;
+        }
+        let chord = self.nodes[leaf].pos.distance(self.nodes[cur].pos);
+        if chord <= f32::EPSILON {
+            1.0
+        } else {
+            path / chord
+        }
+    }
+
     /// Checks whether the given parent already has a child near `pos`.
     ///
     /// The check is performed using squared distance:
@@ -168,10 +556,30 @@ impl Tree {
     ///   the squared distance to `pos`, or
     /// - `None` if there are no nodes.
     pub fn find_nearest_node(&self, pos: Vec2) -> Option<(NodeId, f32)> {
+        self.nearest_where(pos, |_| true)
+    }
+
+    /// Finds the node nearest to `pos` among the nodes accepted by `pred`.
+    ///
+    /// Behaves like [`Tree::find_nearest_node`], except that nodes for
+    /// which `pred` returns `false` (e.g. non-leaves) are skipped.
+    ///
+    /// ### Parameters
+    /// - `pos` - Query position.
+    /// - `pred` - Filter deciding which nodes take part in the search.
+    ///
+    /// ### Returns
+    /// - `Some((id, dist2))` for the nearest accepted node, or
+    /// - `None` if no node is accepted.
+    pub fn nearest_where(
+        &self,
+        pos: Vec2,
+        pred: impl Fn(&TreeNode) -> bool,
+    ) -> Option<(NodeId, f32)> {
         let mut best = None;
         let mut best_d2 = f32::MAX;
 
-        for (id, n) in self.nodes.iter().enumerate() {
+        for (id, n) in self.nodes.iter().enumerate().filter(|(_, n)| pred(n)) {
             let d2 = (n.pos - pos).length_squared();
             if d2 < best_d2 {
                 best_d2 = d2;
@@ -202,15 +610,55 @@ impl Tree {
     /// - `Some((id, dist2))` with the selected node id and squared distance, or
     /// - `None` if there are no nodes.
     pub fn find_kth_nearest_nodes(&self, pos: Vec2, k: usize) -> Option<(NodeId, f32)> {
-        let n = self.nodes.len();
-        if n == 0 {
-            return None;
-        }
+        self.find_kth_nearest_nodes_where(pos, k, |_| true)
+    }
+
+    /// Finds the *k*-th nearest node among the nodes accepted by `pred`.
+    ///
+    /// Behaves exactly like [`Tree::find_kth_nearest_nodes`], except that
+    /// nodes for which `pred` returns `false` are ignored entirely: ranks
+    /// and the "farthest node" fallback only consider accepted nodes.
+    ///
+    /// ### Parameters
+    /// - `pos` - Query position.
+    /// - `k` - Zero-based rank of the nearest accepted node to retrieve.
+    /// - `pred` - Filter deciding which nodes take part in the search.
+    ///
+    /// ### Returns
+    /// - `Some((id, dist2))` with the selected node id and squared distance, or
+    /// - `None` if no node is accepted.
+    pub fn find_kth_nearest_nodes_where(
+        &self,
+        pos: Vec2,
+        k: usize,
+        pred: impl Fn(&TreeNode) -> bool,
+    ) -> Option<(NodeId, f32)> {
+        self.find_kth_nearest_ids_where(pos, k, |id| pred(&self.nodes[id]))
+    }
 
+    /// Like [`Tree::find_kth_nearest_nodes_where`], but `pred` is given the
+    /// node id, for filters that depend on per-node state kept outside the
+    /// tree (e.g. an influence count).
+    ///
+    /// ### Parameters
+    /// - `pos` - Query position.
+    /// - `k` - Zero-based rank of the nearest accepted node to retrieve.
+    /// - `pred` - Filter deciding which node ids take part in the search.
+    ///
+    /// ### Returns
+    /// - `Some((id, dist2))` with the selected node id and squared distance, or
+    /// - `None` if no node is accepted.
+    pub fn find_kth_nearest_ids_where(
+        &self,
+        pos: Vec2,
+        k: usize,
+        pred: impl Fn(NodeId) -> bool,
+    ) -> Option<(NodeId, f32)> {
         let mut dist_list: Vec<(NodeId, f32)> = self
             .nodes
             .iter()
             .enumerate()
+            .filter(|&(id, _)| pred(id))
             .map(|(id, node)| {
                 let d = node.pos - pos;
                 let dist2 = d.length_squared();
@@ -218,88 +666,1973 @@ impl Tree {
             })
             .collect();
 
+        let n = dist_list.len();
+        if n == 0 {
+            return None;
+        }
+
+        // Ties are broken by id so results match [`SpatialGrid`] queries.
+        let by_dist =
+            |a: &(NodeId, f32), b: &(NodeId, f32)| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0));
         if k >= n {
-            dist_list.select_nth_unstable_by(n - 1, |a, b| a.1.total_cmp(&b.1));
+            dist_list.select_nth_unstable_by(n - 1, by_dist);
             return Some(dist_list[n - 1]);
         }
-        dist_list.select_nth_unstable_by(k, |a, b| a.1.total_cmp(&b.1));
+        dist_list.select_nth_unstable_by(k, by_dist);
         Some(dist_list[k])
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use glam::Vec2;
-
-    #[test]
-    fn new_tree_creates_single_root() {
-        let root_pos = Vec2::new(0.0, 1.0);
-        let root_radius = 2.0;
-        let tree = Tree::new(root_pos, root_radius);
 
-        assert_eq!(tree.nodes.len(), 1);
-        let root = &tree.nodes[0];
-        assert_eq!(root.pos, root_pos);
-        assert_eq!(root.radius, root_radius);
-        assert!(root.parent.is_none());
-        assert!(root.children.is_empty());
+    /// Finds the `k` nodes closest to `pos`.
+    ///
+    /// Unlike [`Tree::find_kth_nearest_nodes`], which returns the single
+    /// node at rank `k`, this returns the whole list up to that rank.
+    ///
+    /// ### Parameters
+    /// - `pos` - Query position.
+    /// - `k` - Number of nodes to return; clamped to the node count.
+    ///
+    /// ### Returns
+    /// `(id, dist2)` pairs sorted by ascending squared distance (ties by
+    /// id). Empty if the tree has no nodes or `k` is `0`.
+    pub fn find_k_nearest(&self, pos: Vec2, k: usize) -> Vec<(NodeId, f32)> {
+        self.find_k_nearest_ids_where(pos, k, |_| true)
     }
 
-    #[test]
-    fn add_child_links_parent_and_child() {
-        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
-        let parent_id: NodeId = 0;
-        let child_pos = Vec2::new(1.0, 0.0);
-        let child_radius = 0.5;
+    /// Like [`Tree::find_k_nearest`], restricted to the node ids accepted
+    /// by `pred`.
+    ///
+    /// ### Parameters
+    /// - `pos` - Query position.
+    /// - `k` - Number of nodes to return; clamped to the accepted count.
+    /// - `pred` - Filter deciding which node ids take part in the search.
+    ///
+    /// ### Returns
+    /// `(id, dist2)` pairs sorted by ascending squared distance (ties by id).
+    pub fn find_k_nearest_ids_where(
+        &self,
+        pos: Vec2,
+        k: usize,
+        pred: impl Fn(NodeId) -> bool,
+    ) -> Vec<(NodeId, f32)> {
+        if k == 0 {
+            return Vec::new();
+        }
+        let mut dist_list: Vec<(NodeId, f32)> = (0..self.nodes.len())
+            .filter(|&id| pred(id))
+            .map(|id| (id, self.nodes[id].pos.distance_squared(pos)))
+            .collect();
+        let by_dist =
+            |a: &(NodeId, f32), b: &(NodeId, f32)| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0));
+        if dist_list.len() > k {
+            dist_list.select_nth_unstable_by(k - 1, by_dist);
+            dist_list.truncate(k);
+        }
+        dist_list.sort_by(by_dist);
+        dist_list
+    }
 
-        let child_id = tree.add_child(parent_id, child_pos, child_radius);
+    /// Builds a [`SpatialGrid`] over all node positions.
+    ///
+    /// The grid answers the same nearest-node queries as the linear
+    /// `find_*` methods (see [`SpatialGrid::query_kth_nearest_where`]) in
+    /// roughly constant time per query, which pays off once the tree has
+    /// a few hundred nodes. It is a snapshot: nodes added later are not
+    /// in it (see [`crate::indexed_tree::IndexedTree`] for a grid that is
+    /// kept up to date).
+    ///
+    /// ### Parameters
+    /// - `cell_size` - Grid cell size; a good choice is the typical query radius.
+    pub fn build_grid(&self, cell_size: f32) -> SpatialGrid {
+        let mut grid = SpatialGrid::new(cell_size);
+        for (id, node) in self.nodes.iter().enumerate() {
+            grid.insert(id, node.pos);
+        }
+        grid
+    }
 
-        assert_eq!(child_id, 1);
-        assert_eq!(tree.nodes.len(), 2);
+    /// Computes the minimal enclosing circle of all node positions.
+    ///
+    /// Uses the iterative form of Welzl's algorithm. The positions are
+    /// shuffled with a fixed seed first, which gives expected linear time
+    /// regardless of the node order while keeping the result deterministic.
+    ///
+    /// ### Returns
+    /// - `Some((center, radius))` of the smallest circle containing every
+    ///   node, or
+    /// - `None` if the tree has no nodes.
+    pub fn bounding_circle(&self) -> Option<(Vec2, f32)> {
+        let mut pts: Vec<Vec2> = self.nodes.iter().map(|n| n.pos).collect();
+        let first = *pts.first()?;
+        pts.shuffle(&mut StdRng::seed_from_u64(0));
 
-        let parent = &tree.nodes[parent_id];
-        assert_eq!(parent.children, vec![child_id]);
+        let mut circle = (first, 0.0);
+        for i in 0..pts.len() {
+            if circle_contains(circle, pts[i]) {
+                continue;
+            }
+            circle = (pts[i], 0.0);
+            for j in 0..i {
+                if circle_contains(circle, pts[j]) {
+                    continue;
+                }
+                circle = circle_from_two(pts[i], pts[j]);
+                for k in 0..j {
+                    if !circle_contains(circle, pts[k]) {
+                        circle = circle_from_three(pts[i], pts[j], pts[k]);
+                    }
+                }
+            }
+        }
 
-        let child = &tree.nodes[child_id];
-        assert_eq!(child.pos, child_pos);
-        assert_eq!(child.radius, child_radius);
-        assert_eq!(child.parent, Some(parent_id));
-        assert!(child.children.is_empty());
+        Some(circle)
     }
 
-    #[test]
-    fn add_free_node_creates_orphan_node() {
-        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
-        let pos = Vec2::new(5.0, 5.0);
-        let radius = 0.8;
+    /// Computes the incoming heading of every node in one pass.
+    ///
+    /// The heading of a node is the normalized direction from its parent
+    /// to it. Features that need it for many nodes (apical dominance,
+    /// angle constraints, pruning) should share this instead of
+    /// recomputing it per node.
+    ///
+    /// ### Returns
+    /// One entry per node: `Some(dir)` for child nodes, `None` for roots
+    /// and free nodes (and for children sitting exactly on their parent).
+    pub fn headings(&self) -> Vec<Option<Vec2>> {
+        self.nodes
+            .iter()
+            .map(|n| {
+                n.parent
+                    .and_then(|p| (n.pos - self.nodes[p].pos).try_normalize())
+            })
+            .collect()
+    }
 
-        let id = tree.add_free_node(pos, radius);
+    /// Computes the size of every node's subtree in one pass.
+    ///
+    /// Relies on parents having lower ids than their children, so sizes
+    /// can be accumulated by walking the ids backwards.
+    ///
+    /// ### Returns
+    /// One entry per node: the number of nodes in its subtree, itself
+    /// included (so leaves have size `1`).
+    pub fn subtree_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![1usize; self.nodes.len()];
+        for id in (0..self.nodes.len()).rev() {
+            if let Some(p) = self.nodes[id].parent {
+                sizes[p] += sizes[id];
+            }
+        }
+        sizes
+    }
 
-        assert_eq!(id, 1);
-        assert_eq!(tree.nodes.len(), 2);
+    /// Computes how far along its longest root-to-leaf path each node lies.
+    ///
+    /// The fraction of a node is `depth / (depth + height)`, where `height`
+    /// is the number of edges on the longest path down to a leaf. Roots get
+    /// `0`, leaves get `1`, and nodes in between blend along the branch, so
+    /// the value suits trunk-to-tip color gradients.
+    ///
+    /// ### Returns
+    /// One fraction in `[0, 1]` per node (`0` for isolated nodes).
+    pub fn path_fractions(&self) -> Vec<f32> {
+        self.depths()
+            .into_iter()
+            .zip(self.heights())
+            .map(|(d, h)| {
+                if d + h == 0 {
+                    0.0
+                } else {
+                    d as f32 / (d + h) as f32
+                }
+            })
+            .collect()
+    }
 
-        let node = &tree.nodes[id];
-        assert_eq!(node.pos, pos);
-        assert_eq!(node.radius, radius);
-        assert!(node.parent.is_none());
-        assert!(node.children.is_empty());
+    /// Returns the number of growth steps the tree has grown in.
+    ///
+    /// This is the latest [`TreeNode::born_step`] of any node. Steps in
+    /// which nothing grew are not counted.
+    ///
+    /// ### Returns
+    /// The largest `born_step`, or `0` for an empty tree.
+    pub fn age(&self) -> u32 {
+        self.nodes.iter().map(|n| n.born_step).max().unwrap_or(0)
     }
 
-    #[test]
-    fn has_child_near_detects_close_child() {
-        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
-        let parent_id: NodeId = 0;
-        let child_pos = Vec2::new(1.0, 0.0);
-        tree.add_child(parent_id, child_pos, 0.5);
+    /// Counts the growth rings of a node's cross-section.
+    ///
+    /// A node gains one ring for every step the tree grew in since the
+    /// node was born, whether or not its own branch kept growing. Older
+    /// nodes toward the base get more rings; nodes grown in the latest
+    /// step get none.
+    ///
+    /// ### Parameters
+    /// - `node` - The node whose rings to count.
+    ///
+    /// ### Returns
+    /// [`Tree::age`] minus the node's `born_step`.
+    ///
+    /// Use [`Tree::trunk_ring_counts`] to count the rings of every node at once.
+    pub fn trunk_rings(&self, node: NodeId) -> u32 {
+        self.age().saturating_sub(self.nodes[node].born_step)
+    }
 
-        // Close to existing child.
-        assert!(tree.has_child_near(parent_id, Vec2::new(1.05, 0.0), 0.2));
-        // Far away from existing child.
-        assert!(!tree.has_child_near(parent_id, Vec2::new(2.0, 0.0), 0.2));
+    /// Counts the growth rings of every node in one pass.
+    ///
+    /// Gives the same counts as calling [`Tree::trunk_rings`] on each node,
+    /// in time linear in the size of the tree.
+    ///
+    /// ### Returns
+    /// One ring count per node, indexed by `NodeId`.
+    pub fn trunk_ring_counts(&self) -> Vec<u32> {
+        let age = self.age();
+        self.nodes
+            .iter()
+            .map(|n| age.saturating_sub(n.born_step))
+            .collect()
     }
 
-    #[test]
+    /// Number of edges on the longest path from each node down to a leaf.
+    ///
+    /// Walks `children` breadth-first from every root, visiting each node
+    /// once, so it does not depend on id order and stops at cycles. Nodes
+    /// not reachable from any root are left at `0`.
+    fn heights(&self) -> Vec<u32> {
+        let n = self.nodes.len();
+        let mut reached_from: Vec<Option<NodeId>> = vec![None; n];
+        let mut reached = vec![false; n];
+        let mut order: Vec<NodeId> = (0..n)
+            .filter(|&id| self.nodes[id].parent.is_none())
+            .collect();
+        for &root in &order {
+            reached[root] = true;
+        }
+        let mut next = 0;
+        while let Some(&id) = order.get(next) {
+            next += 1;
+            for &child in &self.nodes[id].children {
+                if child < n && !std::mem::replace(&mut reached[child], true) {
+                    reached_from[child] = Some(id);
+                    order.push(child);
+                }
+            }
+        }
+
+        let mut height = vec![0u32; n];
+        for &id in order.iter().rev() {
+            if let Some(p) = reached_from[id] {
+                height[p] = height[p].max(height[id] + 1);
+            }
+        }
+        height
+    }
+
+    /// Sets every node's radius from its children's, tapering toward the leaves.
+    ///
+    /// Leaves get `leaf_radius`; every other node gets the radius given by
+    /// the Murray / da Vinci rule `r_parent^n = Σ r_child^n`. A straight
+    /// chain therefore keeps a constant radius, while each fork thickens
+    /// the branch below it. Every root (including free nodes added with
+    /// [`Tree::add_free_node`]) is handled independently.
+    ///
+    /// Relies on parents having lower ids than their children, so children
+    /// are always finished before their parent is visited.
+    ///
+    /// ### Parameters
+    /// - `leaf_radius` - Radius assigned to leaves.
+    /// - `exponent` - The rule's exponent `n`, typically `2.0` to `2.5`.
+    pub fn recompute_radii(&mut self, leaf_radius: f32, exponent: f32) {
+        let radii = self.murray_radii(leaf_radius, exponent);
+        for (node, radius) in self.nodes.iter_mut().zip(radii) {
+            node.radius = radius;
+        }
+    }
+
+    /// Radii computed by [`Tree::recompute_radii`], without storing them.
+    fn murray_radii(&self, leaf_radius: f32, exponent: f32) -> Vec<f32> {
+        // Sum of r_child^n per node, filled in as children are visited.
+        let mut sum = vec![0.0f32; self.nodes.len()];
+        let mut radii = vec![0.0f32; self.nodes.len()];
+        for id in (0..self.nodes.len()).rev() {
+            let radius = if self.nodes[id].children.is_empty() {
+                leaf_radius
+            } else {
+                sum[id].powf(exponent.recip())
+            };
+            radii[id] = radius;
+            if let Some(p) = self.nodes[id].parent {
+                sum[p] += radius.powf(exponent);
+            }
+        }
+        radii
+    }
+
+    /// Computes the Strahler order of every node.
+    ///
+    /// Leaves have order `1`. A node whose highest child order `k` is shared
+    /// by at least two children gets `k + 1`; otherwise it keeps `k`. Only
+    /// real confluences of equal branches raise the order, so it measures
+    /// how "main" a branch is rather than how long it is.
+    ///
+    /// ### Returns
+    /// One order per node (isolated nodes count as leaves).
+    pub fn strahler_numbers(&self) -> Vec<u32> {
+        // Highest child order and how many children reach it, per node.
+        let mut best = vec![(0u32, 0u32); self.nodes.len()];
+        let mut order = vec![1u32; self.nodes.len()];
+        for id in (0..self.nodes.len()).rev() {
+            order[id] = match best[id] {
+                (0, _) => 1,
+                (k, 1) => k,
+                (k, _) => k + 1,
+            };
+            if let Some(p) = self.nodes[id].parent {
+                let (k, n) = &mut best[p];
+                if order[id] > *k {
+                    (*k, *n) = (order[id], 1);
+                } else if order[id] == *k {
+                    *n += 1;
+                }
+            }
+        }
+        order
+    }
+
+    /// Computes every node's radius according to `model`, leaving the
+    /// stored radii untouched.
+    ///
+    /// Suits render widths: a viewer can draw with a model without changing
+    /// the radii that are exported or saved.
+    ///
+    /// ### Parameters
+    /// - `model` - Which thickness model to use.
+    /// - `params` - Leaf radius and exponent.
+    ///
+    /// ### Returns
+    /// One radius per node; the stored radii for [`RadiusModel::Inherit`].
+    pub fn model_radii(&self, model: RadiusModel, params: RadiusParams) -> Vec<f32> {
+        match model {
+            RadiusModel::Inherit => self.nodes.iter().map(|n| n.radius).collect(),
+            RadiusModel::Murray => self.murray_radii(params.leaf_radius, params.exponent),
+            RadiusModel::Strahler => self
+                .strahler_numbers()
+                .into_iter()
+                .map(|order| params.leaf_radius * 2f32.powf((order - 1) as f32 / params.exponent))
+                .collect(),
+        }
+    }
+
+    /// Sets every node's radius according to `model`.
+    ///
+    /// Stores the result of [`Tree::model_radii`];
+    /// [`RadiusModel::Inherit`] leaves the radii untouched.
+    ///
+    /// ### Parameters
+    /// - `model` - Which thickness model to apply.
+    /// - `params` - Leaf radius and exponent.
+    pub fn apply_radius_model(&mut self, model: RadiusModel, params: RadiusParams) {
+        let radii = self.model_radii(model, params);
+        for (node, radius) in self.nodes.iter_mut().zip(radii) {
+            node.radius = radius;
+        }
+    }
+
+    /// Center of mass of the nodes, each weighted by its subtree size.
+    ///
+    /// Nodes near the base carry the weight of everything above them, so
+    /// the trunk pulls the result more than the twigs do. Useful e.g. for
+    /// placing a ground anchor under the tree.
+    ///
+    /// ### Returns
+    /// The weighted centroid, or `None` if the tree is empty.
+    pub fn mass_centroid(&self) -> Option<Vec2> {
+        let sizes = self.subtree_sizes();
+        let total: usize = sizes.iter().sum();
+        if total == 0 {
+            return None;
+        }
+        let sum: Vec2 = self
+            .nodes
+            .iter()
+            .zip(&sizes)
+            .map(|(n, &s)| n.pos * s as f32)
+            .sum();
+        Some(sum / total as f32)
+    }
+
+    /// Returns node ids sorted by depth, roots first.
+    ///
+    /// Drawing nodes in this order puts tips on top of the branches they
+    /// grow from. The sort is stable, so nodes at equal depth keep their
+    /// id order.
+    ///
+    /// ### Returns
+    /// Every [`NodeId`] once, ordered by [`Tree::depths`].
+    pub fn depth_order(&self) -> Vec<NodeId> {
+        let depths = self.depths();
+        let mut order: Vec<NodeId> = (0..self.nodes.len()).collect();
+        order.sort_by_key(|&id| depths[id]);
+        order
+    }
+
+    /// Approximates how much light reaches each node.
+    ///
+    /// Light travels along `light_dir` (e.g. `(0, -1)` for light from
+    /// above). A node is shadowed by every other node that lies upstream
+    /// of it along the light and within `occlusion_radius` of its light
+    /// ray. Exposure is `1 / (1 + occluders)`, so unshadowed nodes get
+    /// `1.0` and heavily shaded ones approach `0`.
+    ///
+    /// This is a brute-force `O(n²)` estimate, meant for analysis or for
+    /// occasionally biasing growth toward exposed nodes.
+    ///
+    /// ### Parameters
+    /// - `light_dir` - Direction the light travels in (need not be normalized).
+    /// - `occlusion_radius` - Lateral distance within which a node casts shade.
+    ///
+    /// ### Returns
+    /// One exposure value in `(0, 1]` per node. With a zero `light_dir`
+    /// every node is fully exposed.
+    pub fn light_exposure(&self, light_dir: Vec2, occlusion_radius: f32) -> Vec<f32> {
+        let Some(dir) = light_dir.try_normalize() else {
+            return vec![1.0; self.nodes.len()];
+        };
+
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(id, node)| {
+                let occluders = self
+                    .nodes
+                    .iter()
+                    .enumerate()
+                    .filter(|&(other, o)| {
+                        let d = o.pos - node.pos;
+                        other != id && d.dot(dir) < 0.0 && d.perp_dot(dir).abs() < occlusion_radius
+                    })
+                    .count();
+                1.0 / (1.0 + occluders as f32)
+            })
+            .collect()
+    }
+
+    /// Number of leaves, i.e. nodes without children.
+    pub fn leaf_count(&self) -> usize {
+        self.nodes.iter().filter(|n| n.children.is_empty()).count()
+    }
+
+    /// Returns the leaves, i.e. nodes without children, in ascending order.
+    ///
+    /// Isolated free nodes count as leaves.
+    pub fn leaves(&self) -> Vec<NodeId> {
+        (0..self.nodes.len())
+            .filter(|&id| self.nodes[id].children.is_empty())
+            .collect()
+    }
+
+    /// Computes the depth of every node with one breadth-first pass.
+    ///
+    /// Every node without a parent is a root at depth `0`, so each tree of
+    /// a forest (and every free node) is measured from its own root. Nodes
+    /// not reachable from any root (see [`Tree::orphans`]) are left at `0`.
+    /// Each node is visited once, so a cycle in `children` keeps the depth
+    /// at which it was first reached.
+    ///
+    /// ### Returns
+    /// One depth per node, indexed by [`NodeId`].
+    pub fn depths(&self) -> Vec<u32> {
+        let mut depths = vec![0u32; self.nodes.len()];
+        let mut reached = vec![false; self.nodes.len()];
+        let mut queue: VecDeque<NodeId> = (0..self.nodes.len())
+            .filter(|&id| self.nodes[id].parent.is_none())
+            .collect();
+        for &root in &queue {
+            reached[root] = true;
+        }
+        while let Some(id) = queue.pop_front() {
+            for &child in &self.nodes[id].children {
+                if child < self.nodes.len() && !std::mem::replace(&mut reached[child], true) {
+                    depths[child] = depths[id] + 1;
+                    queue.push_back(child);
+                }
+            }
+        }
+        depths
+    }
+
+    /// Labels every node with the index of the tree it belongs to.
+    ///
+    /// Roots (and free nodes) are numbered `0, 1, 2, ...` in id order, and
+    /// every other node takes its root's number. Labels only change when
+    /// a root is added before or removed from among existing ones, so they
+    /// stay stable while the trees grow.
+    ///
+    /// ### Returns
+    /// One component index per node, indexed by [`NodeId`].
+    pub fn component_ids(&self) -> Vec<usize> {
+        // Parents always have lower ids than their children, so a parent's
+        // label is set before any of its children are visited.
+        let mut labels = vec![0; self.nodes.len()];
+        let mut next = 0;
+        for id in 0..self.nodes.len() {
+            labels[id] = match self.nodes[id].parent {
+                Some(p) => labels[p],
+                None => {
+                    next += 1;
+                    next - 1
+                }
+            };
+        }
+        labels
+    }
+
+    /// Summed length of all parent–child edges.
+    pub fn total_branch_length(&self) -> f32 {
+        self.nodes
+            .iter()
+            .filter_map(|n| n.parent.map(|p| self.nodes[p].pos.distance(n.pos)))
+            .sum()
+    }
+
+    /// Computes summary metrics of the tree in a few read-only passes.
+    ///
+    /// ### Returns
+    /// A [`TreeStats`] snapshot.
+    pub fn stats(&self) -> TreeStats {
+        let leaf_count = self.leaf_count();
+        let internal = self.nodes.len() - leaf_count;
+        let links: usize = self.nodes.iter().map(|n| n.children.len()).sum();
+        TreeStats {
+            node_count: self.nodes.len(),
+            leaf_count,
+            total_length: self.total_branch_length(),
+            max_depth: self.depths().into_iter().max().unwrap_or(0),
+            avg_branching: if internal == 0 {
+                0.0
+            } else {
+                links as f32 / internal as f32
+            },
+        }
+    }
+
+    /// Gini coefficient of all parent–child edge lengths.
+    ///
+    /// A single-number descriptor of how uneven the branch segments are:
+    /// `0` when every edge has the same length, approaching `1` when a
+    /// few edges account for almost all of the total length.
+    ///
+    /// ### Returns
+    /// The coefficient in `[0, 1)`, or `0.0` if there are no edges of
+    /// positive length.
+    pub fn branch_length_gini(&self) -> f32 {
+        let mut lengths: Vec<f32> = self
+            .nodes
+            .iter()
+            .filter_map(|n| n.parent.map(|p| self.nodes[p].pos.distance(n.pos)))
+            .collect();
+        let total: f32 = lengths.iter().sum();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        lengths.sort_by(f32::total_cmp);
+
+        // G = 2 * sum(i * x_i) / (n * sum(x)) - (n + 1) / n, with 1-based i.
+        let n = lengths.len() as f32;
+        let weighted: f32 = lengths
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| (i + 1) as f32 * x)
+            .sum();
+        (2.0 * weighted / (n * total) - (n + 1.0) / n).max(0.0)
+    }
+
+    /// Computes the convex hull of all node positions.
+    ///
+    /// Uses Andrew's monotone chain algorithm. Collinear points on the
+    /// hull boundary are dropped.
+    ///
+    /// ### Returns
+    /// Hull vertices in counterclockwise order. Fewer than three points
+    /// are returned when the nodes are all collinear (or there are fewer
+    /// than three distinct positions).
+    pub fn convex_hull(&self) -> Vec<Vec2> {
+        let mut pts: Vec<Vec2> = self.nodes.iter().map(|n| n.pos).collect();
+        pts.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        pts.dedup();
+        if pts.len() < 3 {
+            return pts;
+        }
+
+        let mut lower = Vec::with_capacity(pts.len());
+        for &p in &pts {
+            push_hull_point(&mut lower, p);
+        }
+        let mut upper = Vec::with_capacity(pts.len());
+        for &p in pts.iter().rev() {
+            push_hull_point(&mut upper, p);
+        }
+
+        // The last point of each chain is the first point of the other.
+        lower.pop();
+        upper.pop();
+        lower.extend(upper);
+        lower
+    }
+
+    /// Area of the convex hull of all node positions (the "canopy").
+    ///
+    /// Computed with the shoelace formula over [`Tree::convex_hull`].
+    ///
+    /// ### Returns
+    /// The enclosed area, or `0.0` if there are fewer than three
+    /// non-collinear nodes.
+    pub fn canopy_area(&self) -> f32 {
+        let hull = self.convex_hull();
+        if hull.len() < 3 {
+            return 0.0;
+        }
+        let twice_area: f32 = hull
+            .iter()
+            .zip(hull.iter().cycle().skip(1))
+            .map(|(a, b)| a.perp_dot(*b))
+            .sum();
+        twice_area.abs() * 0.5
+    }
+
+    /// Computes the boundary of the alpha shape of all node positions.
+    ///
+    /// An edge between two nodes belongs to the boundary when a disk of
+    /// radius `alpha` touching both is empty of other nodes on exactly one
+    /// side. Unlike [`Tree::convex_hull`], this follows concavities wider
+    /// than `2 * alpha` between branches. Edges that are empty on both
+    /// sides (isolated strands) are not part of any polygon.
+    ///
+    /// ### Parameters
+    /// - `alpha` - Probe disk radius; larger values approach the convex hull.
+    ///
+    /// ### Returns
+    /// Closed boundary polygons (first vertex not repeated). Outer
+    /// boundaries run counterclockwise and holes clockwise. Empty if
+    /// `alpha` is not positive or no region is enclosed.
+    pub fn alpha_shape(&self, alpha: f32) -> Vec<Vec<Vec2>> {
+        if alpha <= 0.0 {
+            return Vec::new();
+        }
+
+        let mut pts: Vec<Vec2> = self.nodes.iter().map(|n| n.pos).collect();
+        pts.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        pts.dedup();
+
+        let mut grid = SpatialGrid::new(2.0 * alpha);
+        for (i, &p) in pts.iter().enumerate() {
+            grid.insert(i, p);
+        }
+        let a2 = alpha * alpha;
+        let eps = a2 * 1e-5;
+        let empty_disk = |c: Vec2, i: usize, j: usize| {
+            grid.query_radius(c, alpha)
+                .into_iter()
+                .all(|k| k == i || k == j || pts[k].distance_squared(c) >= a2 - eps)
+        };
+
+        // Directed boundary edges, oriented so the enclosed side is on the left.
+        let mut outgoing: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (i, &p) in pts.iter().enumerate() {
+            for j in grid.query_radius(p, 2.0 * alpha) {
+                if j <= i {
+                    continue;
+                }
+                let q = pts[j];
+                let half = (q - p) * 0.5;
+                let h = (a2 - half.length_squared()).max(0.0).sqrt();
+                let left = half.perp().normalize() * h;
+                let mid = p + half;
+                match (empty_disk(mid + left, i, j), empty_disk(mid - left, i, j)) {
+                    (false, true) => outgoing.entry(i).or_default().push(j),
+                    (true, false) => outgoing.entry(j).or_default().push(i),
+                    _ => {}
+                }
+            }
+        }
+
+        // Chain the directed edges into closed loops.
+        let mut starts: Vec<usize> = outgoing.keys().copied().collect();
+        starts.sort_unstable();
+        let mut polygons = Vec::new();
+        for start in starts {
+            while outgoing.get(&start).is_some_and(|e| !e.is_empty()) {
+                let mut ring = vec![pts[start]];
+                let mut v = start;
+                let closed = loop {
+                    let Some(next) = outgoing.get_mut(&v).and_then(|e| e.pop()) else {
+                        break false;
+                    };
+                    if next == start {
+                        break true;
+                    }
+                    ring.push(pts[next]);
+                    v = next;
+                };
+                if closed && ring.len() >= 3 {
+                    polygons.push(ring);
+                }
+            }
+        }
+        polygons
+    }
+
+    /// Finds pairs of branch edges that geometrically cross.
+    ///
+    /// Every non-root node `c` defines the edge `parent(c) → c`, and edges
+    /// are identified by their child id. Edges that share a node (siblings,
+    /// or a parent edge and a child edge) always touch at that node and are
+    /// not reported. Touching or overlapping edges count as intersecting.
+    ///
+    /// This is a brute-force `O(n²)` check intended for analysis; growth
+    /// uses a local query instead.
+    ///
+    /// ### Returns
+    /// Pairs `(a, b)` of child ids with `a < b`, in ascending order.
+    pub fn self_intersections(&self) -> Vec<(NodeId, NodeId)> {
+        let edges: Vec<(NodeId, NodeId)> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(id, n)| n.parent.map(|p| (p, id)))
+            .collect();
+
+        let mut pairs = Vec::new();
+        for (i, &(pa, a)) in edges.iter().enumerate() {
+            for &(pb, b) in &edges[i + 1..] {
+                if pa == pb || pa == b || pb == a {
+                    continue;
+                }
+                if segments_intersect(
+                    self.nodes[pa].pos,
+                    self.nodes[a].pos,
+                    self.nodes[pb].pos,
+                    self.nodes[b].pos,
+                ) {
+                    pairs.push((a, b));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Rounds every node position to the nearest multiple of `cell`.
+    ///
+    /// Topology is left untouched, so a parent and child (or two siblings)
+    /// may end up at the same position. Call [`Tree::weld_coincident`]
+    /// afterwards to merge those.
+    ///
+    /// ### Parameters
+    /// - `cell` - Grid spacing. Non-positive values leave the tree unchanged.
+    pub fn snap_to_grid(&mut self, cell: f32) {
+        if cell <= 0.0 {
+            return;
+        }
+        for node in &mut self.nodes {
+            node.pos = (node.pos / cell).round() * cell;
+        }
+    }
+
+    /// Merges nodes that sit exactly on top of their parent or a sibling.
+    ///
+    /// A child at the same position as its parent is merged into the
+    /// parent, and siblings at the same position are merged into the
+    /// first of them; children of merged nodes are re-attached to the
+    /// surviving node. Coincident nodes on unrelated branches are kept,
+    /// so the result is still a tree.
+    ///
+    /// Compacts node ids (see [compacted ids](Tree#compacted-ids)).
+    ///
+    /// ### Returns
+    /// The number of nodes removed.
+    pub fn weld_coincident(&mut self) -> usize {
+        let n = self.nodes.len();
+        let mut rep: Vec<Option<NodeId>> = (0..n).map(Some).collect();
+        let mut first_at: HashMap<(NodeId, u32, u32), NodeId> = HashMap::new();
+
+        // Parents always have lower ids than their children, so `rep[p]`
+        // is final by the time a child of `p` is visited.
+        for id in 0..n {
+            let Some(p) = self.nodes[id].parent else {
+                continue;
+            };
+            let parent = rep[p].unwrap_or(p);
+            let pos = self.nodes[id].pos;
+            if pos == self.nodes[parent].pos {
+                rep[id] = Some(parent);
+                continue;
+            }
+            let key = (parent, pos.x.to_bits(), pos.y.to_bits());
+            rep[id] = Some(*first_at.entry(key).or_insert(id));
+        }
+
+        let before = self.nodes.len();
+        self.remap_nodes(&rep);
+        before - self.nodes.len()
+    }
+
+    /// Removes every node deeper than `max_depth` below its root.
+    ///
+    /// Roots and free nodes have depth `0`, so `max_depth = 0` keeps only
+    /// them. Since a node's descendants are all deeper than it, the
+    /// remaining nodes always form valid trees. Useful for previewing
+    /// earlier growth stages without re-simulating.
+    ///
+    /// Compacts node ids (see [compacted ids](Tree#compacted-ids)).
+    ///
+    /// ### Parameters
+    /// - `max_depth` - Deepest level to keep.
+    ///
+    /// ### Returns
+    /// The number of nodes removed.
+    pub fn trim_to_depth(&mut self, max_depth: usize) -> usize {
+        // Parents always have lower ids than their children.
+        let mut depth = vec![0; self.nodes.len()];
+        let rep: Vec<Option<NodeId>> = (0..self.nodes.len())
+            .map(|id| {
+                if let Some(p) = self.nodes[id].parent {
+                    depth[id] = depth[p] + 1;
+                }
+                (depth[id] <= max_depth).then_some(id)
+            })
+            .collect();
+
+        let before = self.nodes.len();
+        self.remap_nodes(&rep);
+        before - self.nodes.len()
+    }
+
+    /// Removes a single node, keeping its subtree.
+    ///
+    /// The node's children are re-attached to its parent, taking the
+    /// removed node's place among the parent's children. If the removed
+    /// node was a root, its children become roots instead. Removing the
+    /// last node leaves an empty tree.
+    ///
+    /// Compacts node ids: every id above `id` shifts down by one (see
+    /// [compacted ids](Tree#compacted-ids)).
+    ///
+    /// ### Parameters
+    /// - `id` - The node to remove.
+    ///
+    /// ### Errors
+    /// [`TreeError::InvalidNode`] if `id` is out of bounds.
+    pub fn remove_node(&mut self, id: NodeId) -> Result<(), TreeError> {
+        if id >= self.nodes.len() {
+            return Err(TreeError::InvalidNode(id));
+        }
+
+        let parent = self.nodes[id].parent;
+        let children = std::mem::take(&mut self.nodes[id].children);
+        for &c in &children {
+            self.nodes[c].parent = parent;
+        }
+        if let Some(p) = parent {
+            let siblings = &mut self.nodes[p].children;
+            let at = siblings
+                .iter()
+                .position(|&c| c == id)
+                .unwrap_or(siblings.len());
+            siblings.splice(at..(at + 1).min(siblings.len()), children);
+        }
+
+        let rep: Vec<Option<NodeId>> = (0..self.nodes.len())
+            .map(|i| (i != id).then_some(i))
+            .collect();
+        self.remap_nodes(&rep);
+        Ok(())
+    }
+
+    /// Removes dead-end twigs: leaves with no alive attractor within
+    /// `radius`, repeatedly, so a whole branch collapses once its tip is
+    /// gone. Roots and free nodes are always kept.
+    ///
+    /// Compacts node ids (see [compacted ids](Tree#compacted-ids)).
+    ///
+    /// ### Parameters
+    /// - `attractors` - Attractors that keep nearby leaves alive; dead
+    ///   ones are ignored.
+    /// - `radius` - Distance within which an alive attractor keeps a leaf.
+    ///
+    /// ### Returns
+    /// The number of nodes removed.
+    pub fn prune_leaves_without_attractors(
+        &mut self,
+        attractors: &AttractorSet,
+        radius: f32,
+    ) -> usize {
+        let r2 = radius * radius;
+        let near_attractor = |pos: Vec2| {
+            attractors
+                .points
+                .iter()
+                .any(|a| a.alive && a.pos.distance_squared(pos) <= r2)
+        };
+
+        // Children have higher ids than their parents, so visiting ids in
+        // reverse decides every child before its parent.
+        let mut rep: Vec<Option<NodeId>> = (0..self.nodes.len()).map(Some).collect();
+        for id in (0..self.nodes.len()).rev() {
+            let node = &self.nodes[id];
+            let is_leaf = node.children.iter().all(|&c| rep[c].is_none());
+            if node.parent.is_some() && is_leaf && !near_attractor(node.pos) {
+                rep[id] = None;
+            }
+        }
+
+        let before = self.nodes.len();
+        self.remap_nodes(&rep);
+        before - self.nodes.len()
+    }
+
+    /// Rebuilds the node array after merging and/or removing nodes.
+    ///
+    /// Every method that removes nodes goes through here; see
+    /// [compacted ids](Tree#compacted-ids) for what that means for callers.
+    ///
+    /// `rep[i]` names the node that `i` is merged into (`Some(i)` keeps it,
+    /// `None` removes it). Representatives must themselves be kept. Kept
+    /// nodes retain their order and fields; children of merged nodes move
+    /// to their representative, and nodes whose parent was removed become
+    /// roots.
+    ///
+    /// ### Returns
+    /// For every old id, its new id, or `None` if it was removed.
+    fn remap_nodes(&mut self, rep: &[Option<NodeId>]) -> Vec<Option<NodeId>> {
+        let mut new_id = vec![None; self.nodes.len()];
+        let mut next = 0;
+        for (id, r) in rep.iter().enumerate() {
+            if *r == Some(id) {
+                new_id[id] = Some(next);
+                next += 1;
+            }
+        }
+        let map: Vec<Option<NodeId>> = rep.iter().map(|r| r.and_then(|r| new_id[r])).collect();
+
+        let mut nodes: Vec<TreeNode> = Vec::with_capacity(next);
+        for (id, node) in self.nodes.iter().enumerate() {
+            if map[id].is_some() && rep[id] == Some(id) {
+                let mut node = node.clone();
+                node.parent = node.parent.and_then(|p| map[p]);
+                node.children.clear();
+                nodes.push(node);
+            }
+        }
+        for (id, node) in self.nodes.iter().enumerate() {
+            let Some(target) = map[id] else {
+                continue;
+            };
+            for &c in &node.children {
+                if let Some(c) = map[c]
+                    && c != target
+                    && !nodes[target].children.contains(&c)
+                {
+                    nodes[target].children.push(c);
+                }
+            }
+        }
+        for node in nodes.iter_mut() {
+            if node.parent.is_some_and(|p| node.children.contains(&p)) {
+                node.parent = None;
+            }
+        }
+
+        self.nodes = nodes;
+        map
+    }
+}
+
+/// Appends `p` to a monotone-chain hull, dropping points that no longer
+/// make a strict left turn.
+fn push_hull_point(chain: &mut Vec<Vec2>, p: Vec2) {
+    while let [.., a, b] = chain[..] {
+        if (b - a).perp_dot(p - a) > 0.0 {
+            break;
+        }
+        chain.pop();
+    }
+    chain.push(p);
+}
+
+/// Returns `true` if segment `p1–p2` intersects segment `q1–q2`.
+///
+/// Touching endpoints and collinear overlaps count as intersections.
+pub(crate) fn segments_intersect(p1: Vec2, p2: Vec2, q1: Vec2, q2: Vec2) -> bool {
+    let d1 = (p2 - p1).perp_dot(q1 - p1);
+    let d2 = (p2 - p1).perp_dot(q2 - p1);
+    let d3 = (q2 - q1).perp_dot(p1 - q1);
+    let d4 = (q2 - q1).perp_dot(p2 - q1);
+
+    if d1 * d2 < 0.0 && d3 * d4 < 0.0 {
+        return true;
+    }
+
+    let on_segment = |a: Vec2, b: Vec2, p: Vec2| p.cmpge(a.min(b)).all() && p.cmple(a.max(b)).all();
+    (d1 == 0.0 && on_segment(p1, p2, q1))
+        || (d2 == 0.0 && on_segment(p1, p2, q2))
+        || (d3 == 0.0 && on_segment(q1, q2, p1))
+        || (d4 == 0.0 && on_segment(q1, q2, p2))
+}
+
+/// Returns `true` if `p` lies inside `circle`, with a small relative tolerance.
+fn circle_contains((center, radius): (Vec2, f32), p: Vec2) -> bool {
+    center.distance(p) <= radius + 1e-5 * radius.max(1.0)
+}
+
+/// Smallest circle through `a` and `b` (the one with `ab` as diameter).
+fn circle_from_two(a: Vec2, b: Vec2) -> (Vec2, f32) {
+    ((a + b) * 0.5, a.distance(b) * 0.5)
+}
+
+/// Circumcircle of `a`, `b`, `c`.
+///
+/// For (nearly) collinear points there is no circumcircle; the circle
+/// spanning the two farthest-apart points is returned instead.
+fn circle_from_three(a: Vec2, b: Vec2, c: Vec2) -> (Vec2, f32) {
+    let ab = b - a;
+    let ac = c - a;
+    let d = 2.0 * ab.perp_dot(ac);
+    if d.abs() <= f32::EPSILON * ab.length_squared().max(ac.length_squared()) {
+        return [(a, b), (a, c), (b, c)]
+            .into_iter()
+            .map(|(p, q)| circle_from_two(p, q))
+            .max_by(|x, y| x.1.total_cmp(&y.1))
+            .unwrap();
+    }
+
+    let ab2 = ab.length_squared();
+    let ac2 = ac.length_squared();
+    let offset = Vec2::new(ac.y * ab2 - ab.y * ac2, ab.x * ac2 - ac.x * ab2) / d;
+    (a + offset, offset.length())
+}
+
+impl Index<NodeIndex> for Tree {
+    type Output = TreeNode;
+
+    #[inline]
+    fn index(&self, id: NodeIndex) -> &TreeNode {
+        &self.nodes[id.index()]
+    }
+}
+
+impl IndexMut<NodeIndex> for Tree {
+    #[inline]
+    fn index_mut(&mut self, id: NodeIndex) -> &mut TreeNode {
+        &mut self.nodes[id.index()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec2;
+
+    #[test]
+    fn new_tree_creates_single_root() {
+        let root_pos = Vec2::new(0.0, 1.0);
+        let root_radius = 2.0;
+        let tree = Tree::new(root_pos, root_radius);
+
+        assert_eq!(tree.nodes.len(), 1);
+        let root = &tree.nodes[0];
+        assert_eq!(root.pos, root_pos);
+        assert_eq!(root.radius, root_radius);
+        assert!(root.parent.is_none());
+        assert!(root.children.is_empty());
+    }
+
+    #[test]
+    fn add_child_links_parent_and_child() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let parent_id: NodeId = 0;
+        let child_pos = Vec2::new(1.0, 0.0);
+        let child_radius = 0.5;
+
+        let child_id = tree.add_child(parent_id, child_pos, child_radius);
+
+        assert_eq!(child_id, 1);
+        assert_eq!(tree.nodes.len(), 2);
+
+        let parent = &tree.nodes[parent_id];
+        assert_eq!(parent.children, vec![child_id]);
+
+        let child = &tree.nodes[child_id];
+        assert_eq!(child.pos, child_pos);
+        assert_eq!(child.radius, child_radius);
+        assert_eq!(child.parent, Some(parent_id));
+        assert!(child.children.is_empty());
+    }
+
+    #[test]
+    fn add_free_node_creates_orphan_node() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let pos = Vec2::new(5.0, 5.0);
+        let radius = 0.8;
+
+        let id = tree.add_free_node(pos, radius);
+
+        assert_eq!(id, 1);
+        assert_eq!(tree.nodes.len(), 2);
+
+        let node = &tree.nodes[id];
+        assert_eq!(node.pos, pos);
+        assert_eq!(node.radius, radius);
+        assert!(node.parent.is_none());
+        assert!(node.children.is_empty());
+    }
+
+    #[test]
+    fn has_child_near_detects_close_child() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let parent_id: NodeId = 0;
+        let child_pos = Vec2::new(1.0, 0.0);
+        tree.add_child(parent_id, child_pos, 0.5);
+
+        // Close to existing child.
+        assert!(tree.has_child_near(parent_id, Vec2::new(1.05, 0.0), 0.2));
+        // Far away from existing child.
+        assert!(!tree.has_child_near(parent_id, Vec2::new(2.0, 0.0), 0.2));
+    }
+
+    #[test]
+    fn tortuosity_of_straight_and_zigzag_paths() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let mut straight = 0;
+        for i in 1..=5 {
+            straight = tree.add_child(straight, Vec2::new(0.0, i as f32), 1.0);
+        }
+        assert!((tree.tortuosity(straight) - 1.0).abs() < 1e-5);
+        assert_eq!(tree.tortuosity(0), 1.0);
+
+        // Zig-zag of four unit-slope steps: path 4 * sqrt(2), chord 4.
+        let mut zigzag = 0;
+        for i in 1..=4 {
+            let x = if i % 2 == 1 { 1.0 } else { 0.0 };
+            zigzag = tree.add_child(zigzag, Vec2::new(x, -(i as f32)), 1.0);
+        }
+        assert!((tree.tortuosity(zigzag) - 2f32.sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn trim_to_depth_keeps_upper_levels_with_valid_links() {
+        // Depths: 0 -> {1, 2} at 1 -> 3 at 2 -> 4 at 3, plus 5 at 2 under 2.
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let a = tree.add_child(0, Vec2::new(-1.0, 1.0), 1.0);
+        let b = tree.add_child(0, Vec2::new(1.0, 1.0), 1.0);
+        let c = tree.add_child(a, Vec2::new(-1.0, 2.0), 1.0);
+        tree.add_child(c, Vec2::new(-1.0, 3.0), 1.0);
+        tree.add_child(b, Vec2::new(1.0, 2.0), 1.0);
+
+        assert_eq!(tree.trim_to_depth(2), 1);
+
+        let positions: Vec<Vec2> = tree.nodes.iter().map(|n| n.pos).collect();
+        assert_eq!(
+            positions,
+            vec![
+                Vec2::new(0.0, 0.0),
+                Vec2::new(-1.0, 1.0),
+                Vec2::new(1.0, 1.0),
+                Vec2::new(-1.0, 2.0),
+                Vec2::new(1.0, 2.0),
+            ]
+        );
+        assert_eq!(tree.nodes[0].children, vec![1, 2]);
+        assert_eq!(tree.nodes[1].children, vec![3]);
+        assert_eq!(tree.nodes[2].children, vec![4]);
+        assert!(tree.nodes[3].children.is_empty());
+        for (id, node) in tree.nodes.iter().enumerate() {
+            if let Some(p) = node.parent {
+                assert!(tree.nodes[p].children.contains(&id));
+            }
+            assert!(tree.depth(id) <= 2);
+        }
+
+        assert_eq!(tree.trim_to_depth(0), 4);
+        assert_eq!(tree.nodes.len(), 1);
+        assert!(tree.nodes[0].children.is_empty());
+    }
+
+    #[test]
+    fn find_k_nearest_sorts_and_clamps() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        tree.add_child(0, Vec2::new(3.0, 0.0), 1.0);
+        tree.add_child(0, Vec2::new(1.0, 0.0), 1.0);
+        tree.add_child(0, Vec2::new(-2.0, 0.0), 1.0);
+
+        let pos = Vec2::new(0.5, 0.0);
+        assert_eq!(
+            tree.find_k_nearest(pos, 3),
+            vec![(0, 0.25), (2, 0.25), (1, 6.25)]
+        );
+        assert_eq!(tree.find_k_nearest(pos, 10).len(), 4);
+        assert_eq!(tree.find_k_nearest(pos, 10)[3], (3, 6.25));
+        assert!(tree.find_k_nearest(pos, 0).is_empty());
+        assert!(Tree { nodes: Vec::new() }.find_k_nearest(pos, 3).is_empty());
+    }
+
+    #[test]
+    fn decimate_respects_budget_and_preserves_extent() {
+        use rand::Rng;
+
+        let mut rng = StdRng::seed_from_u64(8);
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        for id in 1..3000usize {
+            let parent = rng.random_range(id.saturating_sub(40)..id);
+            let step = Vec2::new(rng.random_range(-3.0..3.0), rng.random_range(-1.0..3.0));
+            tree.add_child(parent, tree.nodes[parent].pos + step, 1.0);
+        }
+        let extent = |edges: &[(Vec2, Vec2)]| {
+            edges
+                .iter()
+                .flat_map(|&(a, b)| [a, b])
+                .fold((Vec2::MAX, Vec2::MIN), |(lo, hi), p| (lo.min(p), hi.max(p)))
+        };
+
+        let reduced = tree.decimate(100);
+        assert!(
+            !reduced.is_empty() && reduced.len() <= 100,
+            "{}",
+            reduced.len()
+        );
+        let all: Vec<_> = tree.edges().collect();
+        assert_eq!(extent(&reduced), extent(&all));
+
+        // Small trees come back unchanged.
+        assert_eq!(tree.decimate(5000), all);
+    }
+
+    #[test]
+    fn edges_follow_growth_and_pruning() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        let b = tree.add_child(a, Vec2::new(1.0, 2.0), 1.0);
+        tree.add_child(b, Vec2::new(1.0, 3.0), 1.0);
+        tree.add_child(a, Vec2::new(-1.0, 2.0), 1.0);
+        let grown: Vec<_> = tree.edges().collect();
+        assert_eq!(grown.len(), 4);
+        assert_eq!(grown[0], (Vec2::ZERO, Vec2::new(0.0, 1.0)));
+        assert_eq!(grown[2], (Vec2::new(1.0, 2.0), Vec2::new(1.0, 3.0)));
+
+        tree.trim_to_depth(2);
+        assert_eq!(tree.edges().count(), 3);
+
+        tree.nodes[1].pos = Vec2::new(0.0, 5.0);
+        assert_eq!(tree.edges().next(), Some((Vec2::ZERO, Vec2::new(0.0, 5.0))));
+    }
+
+    #[test]
+    fn depth_counts_edges_to_root() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        let b = tree.add_child(a, Vec2::new(0.0, 2.0), 1.0);
+        let free = tree.add_free_node(Vec2::new(5.0, 0.0), 1.0);
+
+        assert_eq!(tree.depth(0), 0);
+        assert_eq!(tree.depth(a), 1);
+        assert_eq!(tree.depth(b), 2);
+        assert_eq!(tree.depth(free), 0);
+    }
+
+    #[test]
+    fn tree_can_be_indexed_by_node_index() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let child = NodeIndex::from(tree.add_child(0, Vec2::new(0.0, 5.0), 0.5));
+
+        assert_eq!(tree[child].pos, Vec2::new(0.0, 5.0));
+        assert_eq!(tree[child].parent, Some(0));
+
+        tree[child].radius = 2.0;
+        assert_eq!(tree.nodes[child.index()].radius, 2.0);
+    }
+
+    #[test]
+    fn bounding_circle_contains_all_nodes_and_is_tight() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        tree.add_free_node(Vec2::new(4.0, 0.0), 1.0);
+        tree.add_free_node(Vec2::new(0.0, 3.0), 1.0);
+        tree.add_free_node(Vec2::new(1.0, 1.0), 1.0);
+        tree.add_free_node(Vec2::new(2.0, 0.5), 1.0);
+
+        let (center, radius) = tree.bounding_circle().unwrap();
+
+        // Right triangle: the hypotenuse is the diameter.
+        assert!(center.distance(Vec2::new(2.0, 1.5)) < 1e-4);
+        assert!((radius - 2.5).abs() < 1e-4);
+
+        let eps = 1e-4;
+        let mut on_boundary = 0;
+        for node in &tree.nodes {
+            let d = center.distance(node.pos);
+            assert!(d <= radius + eps, "node {:?} outside circle", node.pos);
+            if (d - radius).abs() <= eps {
+                on_boundary += 1;
+            }
+        }
+        assert!(on_boundary >= 2);
+
+        assert!(Tree { nodes: Vec::new() }.bounding_circle().is_none());
+    }
+
+    #[test]
+    fn bounding_circle_handles_collinear_nodes() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        tree.add_free_node(Vec2::new(5.0, 0.0), 1.0);
+        tree.add_free_node(Vec2::new(10.0, 0.0), 1.0);
+
+        let (center, radius) = tree.bounding_circle().unwrap();
+        assert!(center.distance(Vec2::new(5.0, 0.0)) < 1e-4);
+        assert!((radius - 5.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn self_intersections_detects_crossing_edges_only() {
+        // Two separate roots whose edges form an X.
+        let mut tree = Tree::new(Vec2::new(-1.0, -1.0), 1.0);
+        let a = tree.add_child(0, Vec2::new(1.0, 1.0), 1.0);
+        let r2 = tree.add_free_node(Vec2::new(-1.0, 1.0), 1.0);
+        let b = tree.add_child(r2, Vec2::new(1.0, -1.0), 1.0);
+
+        assert_eq!(tree.self_intersections(), vec![(a, b)]);
+
+        // Parallel edges side by side, plus adjacent edges sharing nodes.
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let c1 = tree.add_child(0, Vec2::new(0.0, 5.0), 1.0);
+        tree.add_child(c1, Vec2::new(1.0, 8.0), 1.0);
+        tree.add_child(0, Vec2::new(3.0, 4.0), 1.0);
+        let r2 = tree.add_free_node(Vec2::new(10.0, 0.0), 1.0);
+        tree.add_child(r2, Vec2::new(10.0, 5.0), 1.0);
+
+        assert!(tree.self_intersections().is_empty());
+    }
+
+    #[test]
+    fn snap_to_grid_rounds_positions_and_keeps_topology() {
+        let mut tree = Tree::new(Vec2::new(0.4, -0.3), 1.0);
+        let a = tree.add_child(0, Vec2::new(2.6, 4.9), 1.0);
+        tree.add_child(a, Vec2::new(-3.1, 7.6), 1.0);
+        let before = tree.clone();
+
+        tree.snap_to_grid(2.0);
+
+        for node in &tree.nodes {
+            let q = node.pos / 2.0;
+            assert_eq!(q, q.round(), "{:?} is not on the grid", node.pos);
+        }
+        assert_eq!(tree.nodes[1].pos, Vec2::new(2.0, 4.0));
+        for (snapped, original) in tree.nodes.iter().zip(&before.nodes) {
+            assert_eq!(snapped.parent, original.parent);
+            assert_eq!(snapped.children, original.children);
+        }
+    }
+
+    #[test]
+    fn prune_leaves_without_attractors_collapses_isolated_twigs() {
+        // Root -> trunk -> {near leaf, isolated twig of two nodes}.
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let trunk = tree.add_child(0, Vec2::new(0.0, 5.0), 1.0);
+        tree.add_child(trunk, Vec2::new(-5.0, 10.0), 1.0);
+        let twig = tree.add_child(trunk, Vec2::new(5.0, 10.0), 1.0);
+        tree.add_child(twig, Vec2::new(10.0, 15.0), 1.0);
+
+        let mut attractors =
+            AttractorSet::from_positions(vec![Vec2::new(-6.0, 11.0), Vec2::new(11.0, 16.0)]);
+        // A dead attractor doesn't keep the twig alive.
+        attractors.points[1].alive = false;
+
+        assert_eq!(tree.prune_leaves_without_attractors(&attractors, 3.0), 2);
+
+        let positions: Vec<Vec2> = tree.nodes.iter().map(|n| n.pos).collect();
+        assert_eq!(
+            positions,
+            vec![
+                Vec2::new(0.0, 0.0),
+                Vec2::new(0.0, 5.0),
+                Vec2::new(-5.0, 10.0)
+            ]
+        );
+        assert_eq!(tree.nodes[0].children, vec![1]);
+        assert_eq!(tree.nodes[1].children, vec![2]);
+        assert_eq!(tree.nodes[2].parent, Some(1));
+        assert!(tree.orphans().is_empty());
+
+        // Nothing near at all: everything but the root goes.
+        assert_eq!(
+            tree.prune_leaves_without_attractors(&AttractorSet::from_positions(vec![]), 3.0),
+            2
+        );
+        assert_eq!(tree.nodes.len(), 1);
+        assert!(tree.nodes[0].children.is_empty());
+    }
+
+    #[test]
+    fn remove_node_reparents_children_of_a_middle_node() {
+        // 0 -> 1 -> {2, 3}, 0 -> 4.
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let mid = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        tree.add_child(mid, Vec2::new(-1.0, 2.0), 1.0);
+        tree.add_child(mid, Vec2::new(1.0, 2.0), 1.0);
+        tree.add_child(0, Vec2::new(2.0, 1.0), 1.0);
+
+        tree.remove_node(mid).unwrap();
+
+        // Old ids 2, 3, 4 shift down to 1, 2, 3; the grandchildren take
+        // the middle node's place before the root's other child.
+        assert_eq!(tree.nodes.len(), 4);
+        assert_eq!(tree.nodes[0].children, vec![1, 2, 3]);
+        assert_eq!(tree.nodes[1].pos, Vec2::new(-1.0, 2.0));
+        assert_eq!(tree.nodes[2].pos, Vec2::new(1.0, 2.0));
+        assert_eq!(tree.nodes[3].pos, Vec2::new(2.0, 1.0));
+        assert!((1..4).all(|id| tree.nodes[id].parent == Some(0)));
+        assert!(tree.orphans().is_empty());
+    }
+
+    #[test]
+    fn remove_node_handles_leaves_roots_and_bad_ids() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        let leaf = tree.add_child(a, Vec2::new(0.0, 2.0), 1.0);
+        let b = tree.add_child(0, Vec2::new(1.0, 1.0), 1.0);
+        tree.add_child(b, Vec2::new(1.0, 2.0), 1.0);
+
+        assert_eq!(tree.remove_node(9), Err(TreeError::InvalidNode(9)));
+
+        // Removing a leaf remaps its later sibling subtree.
+        tree.remove_node(leaf).unwrap();
+        assert_eq!(tree.nodes.len(), 4);
+        assert!(tree.nodes[1].children.is_empty());
+        assert_eq!(tree.nodes[0].children, vec![1, 2]);
+        assert_eq!(tree.nodes[2].children, vec![3]);
+        assert_eq!(tree.nodes[3].parent, Some(2));
+
+        // Removing the root turns its children into roots.
+        tree.remove_node(0).unwrap();
+        assert_eq!(tree.nodes.len(), 3);
+        assert_eq!(tree.nodes[0].parent, None);
+        assert_eq!(tree.nodes[1].parent, None);
+        assert_eq!(tree.nodes[1].children, vec![2]);
+
+        let mut single = Tree::new(Vec2::ZERO, 1.0);
+        single.remove_node(0).unwrap();
+        assert!(single.nodes.is_empty());
+    }
+
+    #[test]
+    fn weld_coincident_merges_onto_parent_and_siblings() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0); // 1
+        let dup_sibling = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0); // 2 == a
+        let on_parent = tree.add_child(a, Vec2::new(0.0, 1.0), 1.0); // 3 == a
+        tree.add_child(on_parent, Vec2::new(0.0, 2.0), 1.0); // 4
+        tree.add_child(dup_sibling, Vec2::new(1.0, 1.0), 1.0); // 5
+
+        assert_eq!(tree.weld_coincident(), 2);
+        assert_eq!(tree.nodes.len(), 4);
+
+        // Root -> a -> {leaf, (1, 1)}.
+        assert_eq!(tree.nodes[0].children, vec![1]);
+        assert_eq!(tree.nodes[1].parent, Some(0));
+        let mut children = tree.nodes[1].children.clone();
+        children.sort();
+        assert_eq!(children, vec![2, 3]);
+        assert_eq!(tree.nodes[2].pos, Vec2::new(0.0, 2.0));
+        assert_eq!(tree.nodes[3].pos, Vec2::new(1.0, 1.0));
+        assert!(tree.nodes[2..].iter().all(|n| n.parent == Some(1)));
+    }
+
+    #[test]
+    fn canopy_area_of_unit_square_is_one() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        tree.add_child(0, Vec2::new(1.0, 0.0), 1.0);
+        tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        tree.add_child(0, Vec2::new(1.0, 1.0), 1.0);
+        tree.add_child(0, Vec2::new(0.5, 0.5), 1.0); // interior
+        tree.add_child(0, Vec2::new(0.5, 0.0), 1.0); // on an edge
+
+        assert_eq!(tree.convex_hull().len(), 4);
+        assert!((tree.canopy_area() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn canopy_area_is_zero_for_degenerate_trees() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        assert_eq!(tree.canopy_area(), 0.0);
+
+        tree.add_child(0, Vec2::new(1.0, 1.0), 1.0);
+        tree.add_child(1, Vec2::new(2.0, 2.0), 1.0);
+        assert_eq!(tree.canopy_area(), 0.0);
+    }
+
+    #[test]
+    fn alpha_shape_follows_concavity_of_c_shape() {
+        // A 7x7 grid with a 5x3 notch cut out of the right side: a "C".
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        for x in 0..7 {
+            for y in 0..7 {
+                let notch = x >= 2 && (2..=4).contains(&y);
+                if (x, y) != (0, 0) && !notch {
+                    tree.add_free_node(Vec2::new(x as f32, y as f32), 1.0);
+                }
+            }
+        }
+
+        let polygons = tree.alpha_shape(1.0);
+        assert_eq!(polygons.len(), 1);
+        let ring = &polygons[0];
+
+        // Counterclockwise, enclosing the 6x6 square minus the 5x4 notch,
+        // except for the two notch corners, which are cut diagonally.
+        let twice_area: f32 = ring
+            .iter()
+            .zip(ring.iter().cycle().skip(1))
+            .map(|(a, b)| a.perp_dot(*b))
+            .sum();
+        assert!((twice_area * 0.5 - 17.0).abs() < 1e-4);
+
+        // The notch's inner wall lies on the boundary; the hull skips it.
+        assert!(ring.contains(&Vec2::new(1.0, 3.0)));
+        assert!(!tree.convex_hull().contains(&Vec2::new(1.0, 3.0)));
+        assert!((tree.canopy_area() - 36.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn alpha_shape_is_empty_for_nonpositive_alpha_or_a_line() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        tree.add_child(0, Vec2::new(1.0, 0.0), 1.0);
+        tree.add_child(1, Vec2::new(2.0, 0.0), 1.0);
+        assert!(tree.alpha_shape(1.0).is_empty());
+
+        tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        assert!(tree.alpha_shape(0.0).is_empty());
+    }
+
+    #[test]
+    fn grow_path_to_traces_l_shaped_path() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let corner = Vec2::new(0.0, 10.0);
+        let end = Vec2::new(10.0, 10.0);
+        let step_len = 3.0;
+
+        let ids = tree.grow_path_to(0, &[corner, end], step_len);
+
+        // 10 units per leg at step 3 → 4 nodes per leg (the last one shortened).
+        assert_eq!(ids.len(), 8);
+        assert_eq!(tree.nodes[ids[3]].pos, corner);
+        assert_eq!(tree.nodes[ids[7]].pos, end);
+
+        let mut prev = 0;
+        for &id in &ids {
+            let node = &tree.nodes[id];
+            assert_eq!(node.parent, Some(prev));
+            assert!(node.pos.distance(tree.nodes[prev].pos) <= step_len + 1e-4);
+            // Every node lies on one of the two legs.
+            let on_first = node.pos.x.abs() < 1e-4 && (0.0..=10.0).contains(&node.pos.y);
+            let on_second = (node.pos.y - 10.0).abs() < 1e-4;
+            assert!(on_first || on_second, "{:?} is off the path", node.pos);
+            prev = id;
+        }
+    }
+
+    #[test]
+    fn headings_point_from_parent_and_are_none_at_roots() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let up = tree.add_child(0, Vec2::new(0.0, 4.0), 1.0);
+        let diag = tree.add_child(up, Vec2::new(3.0, 8.0), 1.0);
+        let free = tree.add_free_node(Vec2::new(9.0, 9.0), 1.0);
+
+        let headings = tree.headings();
+
+        assert_eq!(headings.len(), 4);
+        assert_eq!(headings[0], None);
+        assert_eq!(headings[free], None);
+        assert_eq!(headings[up], Some(Vec2::new(0.0, 1.0)));
+        let h = headings[diag].unwrap();
+        assert!(h.distance(Vec2::new(0.6, 0.8)) < 1e-6);
+    }
+
+    #[test]
+    fn path_fractions_run_from_root_to_tips() {
+        // Root -> a -> b -> c, plus a short twig root -> t.
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        let b = tree.add_child(a, Vec2::new(0.0, 2.0), 1.0);
+        let c = tree.add_child(b, Vec2::new(0.0, 3.0), 1.0);
+        let t = tree.add_child(0, Vec2::new(1.0, 0.0), 1.0);
+        let free = tree.add_free_node(Vec2::new(9.0, 9.0), 1.0);
+
+        let f = tree.path_fractions();
+
+        assert_eq!(f[0], 0.0);
+        assert!((f[a] - 1.0 / 3.0).abs() < 1e-6);
+        assert!((f[b] - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(f[c], 1.0);
+        assert_eq!(f[t], 1.0);
+        assert_eq!(f[free], 0.0);
+    }
+
+    #[test]
+    fn recompute_radii_keeps_chains_and_thickens_forks() {
+        // A straight chain keeps the leaf radius all the way down.
+        let mut chain = Tree::new(Vec2::ZERO, 5.0);
+        let a = chain.add_child(0, Vec2::new(0.0, 1.0), 5.0);
+        chain.add_child(a, Vec2::new(0.0, 2.0), 5.0);
+        chain.recompute_radii(0.5, 2.0);
+        for node in &chain.nodes {
+            assert!((node.radius - 0.5).abs() < 1e-6);
+        }
+
+        // A binary split: r_parent = r_leaf * 2^(1/n). The second root
+        // (a free node with one leaf) is handled on its own.
+        let mut fork = Tree::new(Vec2::ZERO, 5.0);
+        fork.add_child(0, Vec2::new(-1.0, 1.0), 5.0);
+        fork.add_child(0, Vec2::new(1.0, 1.0), 5.0);
+        let free = fork.add_free_node(Vec2::new(9.0, 0.0), 5.0);
+        fork.add_child(free, Vec2::new(9.0, 1.0), 5.0);
+        fork.recompute_radii(1.0, 2.5);
+        assert!((fork.nodes[0].radius - 2f32.powf(1.0 / 2.5)).abs() < 1e-6);
+        assert_eq!(fork.nodes[1].radius, 1.0);
+        assert!((fork.nodes[free].radius - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn strahler_numbers_rise_only_at_equal_confluences() {
+        //      3   4
+        //       \ /
+        //   5    2
+        //    \  /
+        //      1
+        //      |
+        //      0
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        let n1 = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        let n2 = tree.add_child(n1, Vec2::new(1.0, 2.0), 1.0);
+        tree.add_child(n2, Vec2::new(0.5, 3.0), 1.0);
+        tree.add_child(n2, Vec2::new(1.5, 3.0), 1.0);
+        tree.add_child(n1, Vec2::new(-1.0, 2.0), 1.0);
+        let free = tree.add_free_node(Vec2::new(9.0, 0.0), 1.0);
+
+        // Node 1 joins an order-2 and an order-1 branch, so it stays at 2.
+        assert_eq!(tree.strahler_numbers(), vec![2, 2, 2, 1, 1, 1, 1]);
+        assert_eq!(tree.strahler_numbers()[free], 1);
+    }
+
+    #[test]
+    fn strahler_radius_model_makes_the_root_thickest() {
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        for (i, x) in [-1.0, 1.0].into_iter().enumerate() {
+            let b = tree.add_child(a, Vec2::new(x, 2.0), 1.0);
+            tree.add_child(b, Vec2::new(x - 0.5, 3.0 + i as f32), 1.0);
+            tree.add_child(b, Vec2::new(x + 0.5, 3.0), 1.0);
+        }
+        let params = RadiusParams {
+            leaf_radius: 0.5,
+            exponent: 2.0,
+        };
+
+        let mut inherit = tree.clone();
+        inherit.apply_radius_model(RadiusModel::Inherit, params);
+        assert!(inherit.nodes.iter().all(|n| n.radius == 1.0));
+
+        let widths = tree.model_radii(RadiusModel::Strahler, params);
+        assert!(tree.nodes.iter().all(|n| n.radius == 1.0));
+        tree.apply_radius_model(RadiusModel::Strahler, params);
+        let stored: Vec<f32> = tree.nodes.iter().map(|n| n.radius).collect();
+        assert_eq!(widths, stored);
+        let root = tree.nodes[0].radius;
+        assert!((root - 0.5 * 2.0).abs() < 1e-6, "order 3 root: {root}");
+        assert!(tree.nodes.iter().all(|n| n.radius <= root));
+        assert_eq!(tree.nodes.last().unwrap().radius, 0.5);
+
+        // On this balanced binary tree both models agree.
+        let mut murray = tree.clone();
+        murray.apply_radius_model(RadiusModel::Murray, params);
+        for (m, s) in murray.nodes.iter().zip(&tree.nodes) {
+            assert!((m.radius - s.radius).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn orphans_reports_nodes_cut_off_from_every_root() {
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        let b = tree.add_child(a, Vec2::new(0.0, 2.0), 1.0);
+        let c = tree.add_child(b, Vec2::new(0.0, 3.0), 1.0);
+        tree.add_free_node(Vec2::new(5.0, 0.0), 1.0);
+        assert!(tree.orphans().is_empty());
+
+        // Point `a` at its own grandchild: a <- c <- b <- a is a cycle
+        // with no root above it.
+        tree.nodes[0].children.clear();
+        tree.nodes[a].parent = Some(c);
+        tree.nodes[c].children.push(a);
+
+        assert_eq!(tree.orphans(), vec![a, b, c]);
+    }
+
+    #[test]
+    fn path_fractions_handle_out_of_order_parents_and_cycles() {
+        // Node 1 hangs below node 2, so a parent has a higher id than its child.
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        let late = tree.add_free_node(Vec2::new(0.0, 2.0), 1.0);
+        let mid = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        tree.nodes[late].parent = Some(mid);
+        tree.nodes[mid].children.push(late);
+        assert_eq!(tree.path_fractions(), vec![0.0, 1.0, 0.5]);
+
+        // `late` also lists the root as a child: 0 -> mid -> late -> 0.
+        tree.nodes[late].children.push(0);
+        assert_eq!(tree.path_fractions(), vec![0.0, 1.0, 0.5]);
+        assert_eq!(tree.trunk_ring_counts(), vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn depths_visit_each_node_once_despite_a_cycle() {
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        let b = tree.add_child(a, Vec2::new(0.0, 2.0), 1.0);
+        let c = tree.add_child(b, Vec2::new(0.0, 3.0), 1.0);
+
+        // `c` also lists `a` as a child: a -> b -> c -> a loops below the root.
+        tree.nodes[c].children.push(a);
+
+        assert_eq!(tree.depths(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn root_of_and_component_ids_separate_a_two_tree_forest() {
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        let other = tree.add_free_node(Vec2::new(10.0, 0.0), 1.0);
+        let b = tree.add_child(other, Vec2::new(10.0, 1.0), 1.0);
+        let a2 = tree.add_child(a, Vec2::new(0.0, 2.0), 1.0);
+        let b2 = tree.add_child(b, Vec2::new(10.0, 2.0), 1.0);
+
+        for id in [0, a, a2] {
+            assert_eq!(tree.root_of(id), 0);
+        }
+        for id in [other, b, b2] {
+            assert_eq!(tree.root_of(id), other);
+        }
+
+        let labels = tree.component_ids();
+        assert_eq!(labels, vec![0, 0, 1, 1, 0, 1]);
+
+        // Growing either tree keeps the existing labels.
+        tree.add_child(b2, Vec2::new(10.0, 3.0), 1.0);
+        tree.add_child(a2, Vec2::new(0.0, 3.0), 1.0);
+        let grown = tree.component_ids();
+        assert_eq!(&grown[..labels.len()], &labels[..]);
+        assert_eq!(&grown[labels.len()..], &[1, 0]);
+    }
+
+    #[test]
+    fn leaves_and_depths_of_a_small_forest() {
+        //   2   3
+        //    \ /
+        //     1   5
+        //     |   |
+        //     0   4    6 (free)
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        let n1 = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        tree.add_child(n1, Vec2::new(-1.0, 2.0), 1.0);
+        tree.add_child(n1, Vec2::new(1.0, 2.0), 1.0);
+        let r2 = tree.add_free_node(Vec2::new(5.0, 0.0), 1.0);
+        tree.add_child(r2, Vec2::new(5.0, 1.0), 1.0);
+        let free = tree.add_free_node(Vec2::new(9.0, 0.0), 1.0);
+
+        assert_eq!(tree.leaves(), vec![2, 3, 5, free]);
+        assert_eq!(tree.leaves().len(), tree.leaf_count());
+        assert_eq!(tree.depths(), vec![0, 1, 2, 2, 0, 1, 0]);
+        for id in 0..tree.nodes.len() {
+            assert_eq!(tree.depths()[id] as usize, tree.depth(id));
+        }
+    }
+
+    #[test]
+    fn bounds_cover_all_nodes() {
+        assert_eq!(Tree { nodes: Vec::new() }.bounds(), None);
+
+        let mut tree = Tree::new(Vec2::new(1.0, 2.0), 1.0);
+        assert_eq!(
+            tree.bounds(),
+            Some((Vec2::new(1.0, 2.0), Vec2::new(1.0, 2.0)))
+        );
+
+        let a = tree.add_child(0, Vec2::new(-3.0, 5.0), 1.0);
+        tree.add_child(a, Vec2::new(4.0, -1.0), 1.0);
+        tree.add_free_node(Vec2::new(0.0, 7.0), 1.0);
+        assert_eq!(
+            tree.bounds(),
+            Some((Vec2::new(-3.0, -1.0), Vec2::new(4.0, 7.0)))
+        );
+    }
+
+    #[test]
+    fn subtree_bounds_cover_only_descendants() {
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        let a = tree.add_child(0, Vec2::new(-1.0, 2.0), 1.0);
+        let b = tree.add_child(a, Vec2::new(-3.0, 4.0), 1.0);
+        tree.add_child(a, Vec2::new(0.5, 5.0), 1.0);
+        tree.add_child(0, Vec2::new(8.0, -1.0), 1.0);
+
+        assert_eq!(
+            tree.subtree_bounds(a),
+            (Vec2::new(-3.0, 2.0), Vec2::new(0.5, 5.0))
+        );
+        assert_eq!(
+            tree.subtree_bounds(0),
+            (Vec2::new(-3.0, -1.0), Vec2::new(8.0, 5.0))
+        );
+        assert_eq!(
+            tree.subtree_bounds(b),
+            (tree.nodes[b].pos, tree.nodes[b].pos)
+        );
+    }
+
+    #[test]
+    fn trunk_rings_increase_toward_the_base_of_the_main_path() {
+        // A main path grown one node per step, with a side twig grown at
+        // step 3 that stopped growing right away.
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        let mut main = vec![0];
+        for i in 1..6 {
+            let id = tree.add_child(main[i - 1], Vec2::new(0.0, i as f32), 1.0);
+            tree.nodes[id].born_step = i as u32;
+            main.push(id);
+        }
+        let twig = tree.add_child(main[2], Vec2::new(1.0, 2.5), 1.0);
+        tree.nodes[twig].born_step = 3;
+
+        assert_eq!(tree.age(), 5);
+        let rings: Vec<u32> = main.iter().map(|&id| tree.trunk_rings(id)).collect();
+        assert_eq!(rings, vec![5, 4, 3, 2, 1, 0]);
+        // The twig keeps adding rings while the rest of the tree grows.
+        assert_eq!(tree.trunk_rings(twig), 2);
+
+        let all = tree.trunk_ring_counts();
+        assert_eq!(all.len(), tree.nodes.len());
+        for (id, &rings) in all.iter().enumerate() {
+            assert_eq!(rings, tree.trunk_rings(id));
+        }
+    }
+
+    #[test]
+    fn mass_centroid_leans_toward_the_heavier_side() {
+        // A three-node chain to the left, a single twig to the right.
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        let l1 = tree.add_child(0, Vec2::new(-1.0, 0.0), 1.0);
+        let l2 = tree.add_child(l1, Vec2::new(-2.0, 0.0), 1.0);
+        tree.add_child(l2, Vec2::new(-3.0, 0.0), 1.0);
+        tree.add_child(0, Vec2::new(5.0, 0.0), 1.0);
+
+        assert_eq!(tree.subtree_sizes(), vec![5, 3, 2, 1, 1]);
+
+        let plain = tree.nodes.iter().map(|n| n.pos).sum::<Vec2>() / tree.nodes.len() as f32;
+        let mass = tree.mass_centroid().unwrap();
+        assert!((plain.x - -0.2).abs() < 1e-6);
+        assert!((mass.x - -5.0 / 12.0).abs() < 1e-6);
+        assert!(mass.x < plain.x);
+        assert_eq!(Tree { nodes: Vec::new() }.mass_centroid(), None);
+    }
+
+    #[test]
+    fn depth_order_sorts_nodes_by_depth() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0); // depth 0
+        let a = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0); // depth 1
+        let b = tree.add_child(a, Vec2::new(0.0, 2.0), 1.0); // depth 2
+        let free = tree.add_free_node(Vec2::new(5.0, 0.0), 1.0); // depth 0
+        let c = tree.add_child(free, Vec2::new(5.0, 1.0), 1.0); // depth 1
+
+        let order = tree.depth_order();
+        assert_eq!(order, vec![0, free, a, c, b]);
+        assert!(
+            order
+                .windows(2)
+                .all(|w| tree.depth(w[0]) <= tree.depth(w[1]))
+        );
+    }
+
+    #[test]
+    fn light_exposure_shades_nodes_below_others() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let above = tree.add_child(0, Vec2::new(0.0, 10.0), 1.0);
+        let aside = tree.add_free_node(Vec2::new(20.0, 0.0), 1.0);
+
+        // Light from above, travelling downward.
+        let exposure = tree.light_exposure(Vec2::new(0.0, -1.0), 2.0);
+
+        assert_eq!(exposure[above], 1.0);
+        assert_eq!(exposure[aside], 1.0);
+        assert!(exposure[0] < exposure[above]);
+        assert_eq!(exposure[0], 0.5);
+
+        // Light from the right: now `aside` shades the root instead.
+        let exposure = tree.light_exposure(Vec2::new(-1.0, 0.0), 2.0);
+        assert_eq!(exposure[0], 0.5);
+        assert_eq!(exposure[above], 1.0);
+    }
+
+    #[test]
+    fn leaf_count_and_total_branch_length() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 3.0), 1.0);
+        tree.add_child(a, Vec2::new(4.0, 3.0), 1.0);
+        tree.add_child(a, Vec2::new(0.0, 5.0), 1.0);
+        tree.add_free_node(Vec2::new(9.0, 9.0), 1.0);
+
+        assert_eq!(tree.leaf_count(), 3);
+        assert!((tree.total_branch_length() - 9.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn stats_of_a_handmade_tree() {
+        // Root -> a (3 up) -> {b (4 right), c (2 up) -> d (1 left)}.
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 3.0), 1.0);
+        tree.add_child(a, Vec2::new(4.0, 3.0), 1.0);
+        let c = tree.add_child(a, Vec2::new(0.0, 5.0), 1.0);
+        tree.add_child(c, Vec2::new(-1.0, 5.0), 1.0);
+
+        let stats = tree.stats();
+        assert_eq!(stats.node_count, 5);
+        assert_eq!(stats.leaf_count, 2);
+        assert!((stats.total_length - 10.0).abs() < 1e-6);
+        assert_eq!(stats.max_depth, 3);
+        // Four links over the three nodes with children.
+        assert!((stats.avg_branching - 4.0 / 3.0).abs() < 1e-6);
+
+        let lone = Tree::new(Vec2::ZERO, 1.0).stats();
+        assert_eq!(lone.leaf_count, 1);
+        assert_eq!(lone.avg_branching, 0.0);
+    }
+
+    #[test]
+    fn branch_length_gini_of_equal_and_skewed_edges() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        assert_eq!(tree.branch_length_gini(), 0.0);
+
+        let mut tip = 0;
+        for i in 1..=4 {
+            tip = tree.add_child(tip, Vec2::new(0.0, i as f32), 1.0);
+        }
+        assert!(tree.branch_length_gini().abs() < 1e-6);
+
+        // Nine tiny edges and one huge one.
+        let mut skewed = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let mut tip = 0;
+        for i in 1..=9 {
+            tip = skewed.add_child(tip, Vec2::new(0.0, i as f32 * 0.01), 1.0);
+        }
+        skewed.add_child(tip, Vec2::new(0.0, 1000.0), 1.0);
+        let g = skewed.branch_length_gini();
+        assert!(g > 0.85 && g < 1.0, "gini = {g}");
+    }
+
+    #[test]
     fn find_nearest_node_returns_none_for_empty_tree() {
         let tree = Tree { nodes: Vec::new() };
         let pos = Vec2::new(0.0, 0.0);
@@ -349,6 +2682,48 @@ mod tests {
         assert_eq!(idf, 2);
     }
 
+    #[test]
+    fn add_child_inherits_group_mask() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        assert_eq!(tree.nodes[0].group_mask, u32::MAX);
+
+        let child = tree.add_child(0, Vec2::new(1.0, 0.0), 1.0);
+        tree.set_group_mask(0, 0b10);
+        assert_eq!(tree.nodes[child].group_mask, 0b10);
+
+        let grandchild = tree.add_child(child, Vec2::new(2.0, 0.0), 1.0);
+        assert_eq!(tree.nodes[grandchild].group_mask, 0b10);
+    }
+
+    #[test]
+    fn find_kth_nearest_nodes_where_skips_rejected_nodes() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0); // id 0
+        tree.add_free_node(Vec2::new(1.0, 0.0), 1.0); // id 1
+        tree.add_free_node(Vec2::new(5.0, 0.0), 1.0); // id 2
+        tree.nodes[1].group_mask = 0;
+
+        let accept = |n: &TreeNode| n.group_mask != 0;
+        let pos = Vec2::new(1.0, 0.0);
+
+        assert_eq!(
+            tree.find_kth_nearest_nodes_where(pos, 0, accept).unwrap().0,
+            0
+        );
+        assert_eq!(
+            tree.find_kth_nearest_nodes_where(pos, 1, accept).unwrap().0,
+            2
+        );
+        // k beyond the accepted count falls back to the farthest accepted node.
+        assert_eq!(
+            tree.find_kth_nearest_nodes_where(pos, 9, accept).unwrap().0,
+            2
+        );
+        assert!(
+            tree.find_kth_nearest_nodes_where(pos, 0, |_| false)
+                .is_none()
+        );
+    }
+
     #[test]
     fn find_kth_nearest_nodes_empty_returns_none() {
         let tree = Tree { nodes: Vec::new() };
@@ -2,4 +2,54 @@
 ///
 /// This is an index into `Tree::nodes`, and is only meaningful within
 /// the lifetime of a given `Tree` instance.
+///
+/// Kept as a plain alias while the API migrates to [`NodeIndex`].
 pub type NodeId = usize;
+
+/// Strongly typed index of a node in a [`crate::tree::Tree`].
+///
+/// Wraps the same index as [`NodeId`], but can't be mixed up with
+/// attractor indices, step counts, or lengths by accident. Converts
+/// to and from `usize` for free, and indexes a tree directly via
+/// `tree[node_index]`.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct NodeIndex(pub usize);
+
+impl NodeIndex {
+    /// Returns the raw index into `Tree::nodes`.
+    #[inline]
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl From<usize> for NodeIndex {
+    #[inline]
+    fn from(id: usize) -> Self {
+        Self(id)
+    }
+}
+
+impl From<NodeIndex> for usize {
+    #[inline]
+    fn from(id: NodeIndex) -> Self {
+        id.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_index_conversions_round_trip() {
+        let raw: NodeId = 42;
+        let idx = NodeIndex::from(raw);
+        assert_eq!(idx.index(), 42);
+
+        let back: usize = idx.into();
+        assert_eq!(back, raw);
+        assert_eq!(NodeIndex::from(back), idx);
+    }
+}
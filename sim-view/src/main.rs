@@ -3,6 +3,8 @@
 //! This binary sets up eframe/egui and delegates all interactive
 //! logic and rendering to [`Viewer`] from the `viewer` module.
 
+mod sweep;
+mod theme;
 mod viewer;
 
 use viewer::Viewer;
@@ -0,0 +1,301 @@
+//! Parameter sweep previews for tuning.
+//!
+//! A sweep grows several small trees headlessly, each with one
+//! configuration parameter set to a different value but all from the same
+//! seed, so the thumbnails differ only by the effect of that parameter.
+//! A [`SweepRun`] grows one sample per call, so the viewer can spread a
+//! sweep across frames instead of blocking the UI.
+
+use egui::{Color32, Painter, Rect, Stroke};
+use glam::Vec2;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use sim_core::{config::Config, simulation::Simulation, tree::Tree};
+
+/// Configuration parameter varied by a sweep.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SweepParam {
+    TropismY,
+    InfluenceRadius,
+    KillRadius,
+    StepLen,
+    Momentum,
+}
+
+impl SweepParam {
+    /// Every sweepable parameter, in display order.
+    pub const ALL: [SweepParam; 5] = [
+        SweepParam::TropismY,
+        SweepParam::InfluenceRadius,
+        SweepParam::KillRadius,
+        SweepParam::StepLen,
+        SweepParam::Momentum,
+    ];
+
+    /// Name shown in the UI (the `Config` field path).
+    pub fn label(self) -> &'static str {
+        match self {
+            SweepParam::TropismY => "tropism.y",
+            SweepParam::InfluenceRadius => "influence_radius",
+            SweepParam::KillRadius => "kill_radius",
+            SweepParam::StepLen => "step_len",
+            SweepParam::Momentum => "momentum",
+        }
+    }
+
+    /// Writes `value` into the matching field of `cfg`.
+    pub fn apply(self, cfg: &mut Config, value: f32) {
+        match self {
+            SweepParam::TropismY => cfg.tropism.y = value,
+            SweepParam::InfluenceRadius => cfg.influence_radius = value,
+            SweepParam::KillRadius => cfg.kill_radius = value,
+            SweepParam::StepLen => cfg.step_len = value,
+            SweepParam::Momentum => cfg.momentum = value,
+        }
+    }
+}
+
+/// What to sweep and how.
+///
+/// ### Fields
+/// - `param` - The parameter to vary.
+/// - `min` / `max` - Range of values; samples are spaced evenly, inclusive.
+/// - `samples` - Number of trees to grow.
+/// - `steps` - Simulation steps per tree.
+/// - `seed` - Seed shared by every tree's scene.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct SweepSettings {
+    pub param: SweepParam,
+    pub min: f32,
+    pub max: f32,
+    pub samples: usize,
+    pub steps: usize,
+    pub seed: u64,
+}
+
+impl Default for SweepSettings {
+    fn default() -> Self {
+        Self {
+            param: SweepParam::TropismY,
+            min: -0.5,
+            max: 0.5,
+            samples: 6,
+            steps: 60,
+            seed: 1,
+        }
+    }
+}
+
+impl SweepSettings {
+    /// The parameter values of all samples, evenly spaced from `min` to `max`.
+    pub fn values(&self) -> Vec<f32> {
+        match self.samples {
+            0 => Vec::new(),
+            1 => vec![self.min],
+            n => (0..n)
+                .map(|i| self.min + (self.max - self.min) * i as f32 / (n - 1) as f32)
+                .collect(),
+        }
+    }
+}
+
+/// One grown sample of a sweep.
+///
+/// ### Fields
+/// - `value` - The parameter value the tree was grown with.
+/// - `tree` - The resulting tree.
+#[derive(Clone, Debug)]
+pub struct Thumbnail {
+    pub value: f32,
+    pub tree: Tree,
+}
+
+/// A sweep in progress, grown one sample at a time.
+///
+/// The settings are copied when the run starts, so editing the sweep
+/// controls afterwards changes neither the remaining samples nor the
+/// labels of the finished ones.
+///
+/// ### Fields
+/// - `settings` - The settings the run was started with.
+/// - `base` - Configuration shared by all samples.
+/// - `values` - Parameter value of every sample.
+/// - `thumbnails` - The samples grown so far, in `values` order.
+#[derive(Clone, Debug)]
+pub struct SweepRun {
+    pub settings: SweepSettings,
+    base: Config,
+    values: Vec<f32>,
+    pub thumbnails: Vec<Thumbnail>,
+}
+
+impl SweepRun {
+    /// Starts a sweep without growing anything yet.
+    ///
+    /// ### Parameters
+    /// - `base` - Configuration shared by all samples.
+    /// - `settings` - Parameter, range, sample count, steps, and seed.
+    pub fn new(base: Config, settings: SweepSettings) -> Self {
+        Self {
+            settings,
+            base,
+            values: settings.values(),
+            thumbnails: Vec::new(),
+        }
+    }
+
+    /// Whether every sample has been grown.
+    pub fn is_done(&self) -> bool {
+        self.thumbnails.len() == self.values.len()
+    }
+
+    /// Number of samples grown so far and in total.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.thumbnails.len(), self.values.len())
+    }
+
+    /// Grows the next sample, if any.
+    ///
+    /// The sample starts from `base` with the swept parameter overridden,
+    /// and a scene filled by `populate` from an RNG seeded with
+    /// `settings.seed`.
+    ///
+    /// ### Parameters
+    /// - `populate` - Fills an empty simulation with roots and attractors.
+    pub fn advance(&mut self, populate: impl Fn(&mut Simulation, &mut StdRng)) {
+        let Some(&value) = self.values.get(self.thumbnails.len()) else {
+            return;
+        };
+        let mut cfg = self.base;
+        self.settings.param.apply(&mut cfg, value);
+        let mut sim = Simulation::new(cfg);
+        populate(&mut sim, &mut StdRng::seed_from_u64(self.settings.seed));
+        for _ in 0..self.settings.steps {
+            sim.step();
+        }
+        self.thumbnails.push(Thumbnail {
+            value,
+            tree: sim.tree,
+        });
+    }
+}
+
+/// Draws a tree's edges scaled to fit inside `rect`, preserving aspect.
+///
+/// World `+y` points up, matching the main view.
+pub fn draw_thumbnail(painter: &Painter, rect: Rect, tree: &Tree) {
+    painter.rect_filled(rect, 2.0, Color32::from_gray(20));
+    let Some(first) = tree.nodes.first() else {
+        return;
+    };
+    let (min, max) = tree
+        .nodes
+        .iter()
+        .fold((first.pos, first.pos), |(lo, hi), n| {
+            (lo.min(n.pos), hi.max(n.pos))
+        });
+
+    let inner = rect.shrink(4.0);
+    let extent = (max - min).max(Vec2::splat(f32::EPSILON));
+    let scale = (inner.width() / extent.x).min(inner.height() / extent.y);
+    let center = (min + max) * 0.5;
+    let to_screen = |p: Vec2| {
+        let local = (p - center) * scale;
+        inner.center() + egui::vec2(local.x, -local.y)
+    };
+
+    let stroke = Stroke::new(1.0, Color32::LIGHT_GREEN);
+    for (a, b) in tree.edges() {
+        painter.line_segment([to_screen(a), to_screen(b)], stroke);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sim_core::attractor::AttractorSet;
+
+    fn small_scene(sim: &mut Simulation, rng: &mut StdRng) {
+        sim.add_root(Vec2::ZERO, 1.0);
+        sim.attractors =
+            AttractorSet::random_in_oval(Vec2::new(0.0, 40.0), Vec2::splat(30.0), 150, rng);
+    }
+
+    fn run_sweep(base: &Config, settings: &SweepSettings) -> Vec<Thumbnail> {
+        let mut run = SweepRun::new(*base, *settings);
+        while !run.is_done() {
+            run.advance(small_scene);
+        }
+        run.thumbnails
+    }
+
+    #[test]
+    fn values_are_evenly_spaced_and_inclusive() {
+        let settings = SweepSettings {
+            min: 0.0,
+            max: 1.0,
+            samples: 5,
+            ..SweepSettings::default()
+        };
+        assert_eq!(settings.values(), vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+
+        let one = SweepSettings {
+            samples: 1,
+            ..settings
+        };
+        assert_eq!(one.values(), vec![0.0]);
+    }
+
+    #[test]
+    fn sweep_grows_one_tree_per_value_from_the_same_scene() {
+        let settings = SweepSettings {
+            param: SweepParam::TropismY,
+            min: -0.5,
+            max: 0.5,
+            samples: 3,
+            steps: 15,
+            seed: 4,
+        };
+        let thumbs = run_sweep(&Config::default(), &settings);
+
+        assert_eq!(thumbs.len(), 3);
+        assert_eq!(thumbs[1].value, 0.0);
+        assert!(thumbs.iter().all(|t| t.tree.nodes.len() > 1));
+
+        // Same seed, same trees; different tropism, different trees.
+        let again = run_sweep(&Config::default(), &settings);
+        let positions = |t: &Thumbnail| t.tree.nodes.iter().map(|n| n.pos).collect::<Vec<_>>();
+        assert_eq!(positions(&thumbs[0]), positions(&again[0]));
+        assert_ne!(positions(&thumbs[0]), positions(&thumbs[2]));
+    }
+
+    #[test]
+    fn incremental_run_grows_one_sample_per_call() {
+        let settings = SweepSettings {
+            samples: 3,
+            steps: 10,
+            ..SweepSettings::default()
+        };
+        let mut run = SweepRun::new(Config::default(), settings);
+        assert_eq!(run.progress(), (0, 3));
+
+        run.advance(small_scene);
+        assert_eq!(run.progress(), (1, 3));
+        assert!(!run.is_done());
+        run.advance(small_scene);
+        run.advance(small_scene);
+        run.advance(small_scene);
+        assert!(run.is_done());
+        assert_eq!(run.settings, settings);
+
+        let batch = run_sweep(&Config::default(), &run.settings);
+        let positions = |t: &Thumbnail| t.tree.nodes.iter().map(|n| n.pos).collect::<Vec<_>>();
+        assert_eq!(run.thumbnails.len(), batch.len());
+        assert!(
+            run.thumbnails
+                .iter()
+                .zip(&batch)
+                .all(|(a, b)| a.value == b.value && positions(a) == positions(b))
+        );
+    }
+}
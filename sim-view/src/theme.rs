@@ -0,0 +1,226 @@
+//! Viewer color theme.
+//!
+//! The scene colors (edges, nodes, attractors, overlays) are derived from
+//! a [`Theme`] instead of being hardcoded at each draw call, so the scene
+//! stays legible on both a dark and a light background.
+
+use egui::Color32;
+use egui::ecolor::Hsva;
+
+/// Background brightness of the scene.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Background {
+    #[default]
+    Dark,
+    Light,
+}
+
+/// Colors of the central scene.
+///
+/// ### Fields
+/// - `background` - Dark or light background; the palette of edges,
+///   nodes, attractors, and overlays follows it.
+/// - `accent` - Highlight color for tool hints and growth-tip outlines.
+/// - `grid` - Color of the world grid lines.
+/// - `show_grid` - Whether the world grid is drawn.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Theme {
+    pub background: Background,
+    pub accent: Color32,
+    pub grid: Color32,
+    pub show_grid: bool,
+}
+
+impl Default for Theme {
+    /// A dark background with a gold accent and the grid hidden.
+    fn default() -> Self {
+        Self {
+            background: Background::Dark,
+            accent: Color32::GOLD,
+            grid: Color32::from_rgba_unmultiplied(128, 128, 128, 40),
+            show_grid: false,
+        }
+    }
+}
+
+impl Theme {
+    /// Fill color of the scene.
+    pub fn background_color(&self) -> Color32 {
+        match self.background {
+            Background::Dark => Color32::from_gray(27),
+            Background::Light => Color32::from_gray(245),
+        }
+    }
+
+    /// Color of tree edges.
+    pub fn edge(&self) -> Color32 {
+        match self.background {
+            Background::Dark => Color32::LIGHT_GREEN,
+            Background::Light => Color32::from_rgb(30, 120, 40),
+        }
+    }
+
+    /// Color of tree nodes.
+    pub fn node(&self) -> Color32 {
+        match self.background {
+            Background::Dark => Color32::LIGHT_BLUE,
+            Background::Light => Color32::from_rgb(40, 70, 190),
+        }
+    }
+
+    /// Color of ordinary alive attractors.
+    pub fn attractor(&self) -> Color32 {
+        match self.background {
+            Background::Dark => Color32::LIGHT_RED,
+            Background::Light => Color32::from_rgb(210, 50, 50),
+        }
+    }
+
+    /// Color of locked (permanent guide) attractors.
+    pub fn locked_attractor(&self) -> Color32 {
+        match self.background {
+            Background::Dark => Color32::from_rgb(170, 110, 255),
+            Background::Light => Color32::from_rgb(110, 40, 200),
+        }
+    }
+
+    /// Color newly grown nodes are highlighted with before fading to their
+    /// own color.
+    pub fn highlight(&self) -> Color32 {
+        match self.background {
+            Background::Dark => Color32::RED,
+            Background::Light => Color32::from_rgb(200, 0, 0),
+        }
+    }
+
+    /// Color of the influence direction arrows.
+    pub fn influence(&self) -> Color32 {
+        match self.background {
+            Background::Dark => Color32::from_rgb(120, 200, 255),
+            Background::Light => Color32::from_rgb(20, 90, 170),
+        }
+    }
+
+    /// Color of the growth rings drawn on top of the node fill.
+    pub fn rings(&self) -> Color32 {
+        match self.background {
+            Background::Dark => Color32::from_black_alpha(90),
+            Background::Light => Color32::from_white_alpha(120),
+        }
+    }
+
+    /// Color of attractors that no node can reach.
+    pub fn unreachable(&self) -> Color32 {
+        match self.background {
+            Background::Dark => Color32::ORANGE,
+            Background::Light => Color32::from_rgb(210, 110, 0),
+        }
+    }
+
+    /// Color of an attractor density heatmap cell with the given opacity.
+    ///
+    /// ### Parameters
+    /// - `alpha` - Opacity from `0` (transparent) to `255` (opaque).
+    pub fn heat(&self, alpha: u8) -> Color32 {
+        match self.background {
+            Background::Dark => Color32::from_rgba_unmultiplied(255, 60, 40, alpha),
+            Background::Light => Color32::from_rgba_unmultiplied(200, 30, 20, alpha),
+        }
+    }
+
+    /// Distinct color for the tree with the given component index (see
+    /// [`sim_core::tree::Tree::component_ids`]).
+    ///
+    /// Hues step by the golden ratio, so neighbouring indices differ
+    /// strongly and no two of the first few trees look alike. Darker
+    /// shades are used on a light background.
+    ///
+    /// ### Parameters
+    /// - `index` - Component index.
+    pub fn component(&self, index: usize) -> Color32 {
+        const GOLDEN: f32 = 0.618_034;
+        let hue = (0.3 + index as f32 * GOLDEN).fract();
+        let value = match self.background {
+            Background::Dark => 0.95,
+            Background::Light => 0.6,
+        };
+        Hsva::new(hue, 0.65, value, 1.0).into()
+    }
+
+    /// Contrast color for neutral overlays (selection, previews), with the
+    /// given opacity: white on a dark background, black on a light one.
+    ///
+    /// ### Parameters
+    /// - `alpha` - Opacity from `0` (transparent) to `255` (opaque).
+    pub fn ink(&self, alpha: u8) -> Color32 {
+        match self.background {
+            Background::Dark => Color32::from_white_alpha(alpha),
+            Background::Light => Color32::from_black_alpha(alpha),
+        }
+    }
+
+    /// World-space spacing of the grid lines at the given zoom.
+    ///
+    /// Picks the smallest power of ten that keeps lines at least
+    /// `min_px` pixels apart on screen.
+    ///
+    /// ### Parameters
+    /// - `zoom` - Pixels per world unit.
+    /// - `min_px` - Minimum screen distance between neighbouring lines.
+    pub fn grid_spacing(zoom: f32, min_px: f32) -> f32 {
+        10f32.powf((min_px / zoom.max(f32::EPSILON)).log10().ceil())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switching_background_updates_scene_colors() {
+        let mut theme = Theme::default();
+        let dark = (theme.edge(), theme.node(), theme.background_color());
+        assert_eq!(dark.0, Color32::LIGHT_GREEN);
+
+        theme.background = Background::Light;
+        assert_ne!(theme.edge(), dark.0);
+        assert_ne!(theme.node(), dark.1);
+        assert_ne!(theme.background_color(), dark.2);
+        assert_eq!(theme.ink(255), Color32::BLACK);
+    }
+
+    #[test]
+    fn overlay_colors_follow_the_background() {
+        let dark = Theme::default();
+        let light = Theme {
+            background: Background::Light,
+            ..Theme::default()
+        };
+        assert_ne!(dark.highlight(), light.highlight());
+        assert_ne!(dark.influence(), light.influence());
+        assert_ne!(dark.rings(), light.rings());
+        assert_ne!(dark.unreachable(), light.unreachable());
+        assert_ne!(dark.heat(128), light.heat(128));
+    }
+
+    #[test]
+    fn component_colors_are_distinct() {
+        let theme = Theme::default();
+        let colors: Vec<Color32> = (0..6).map(|i| theme.component(i)).collect();
+        for (i, a) in colors.iter().enumerate() {
+            for b in &colors[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+        assert_eq!(theme.component(2), colors[2]);
+    }
+
+    #[test]
+    fn grid_spacing_is_a_power_of_ten_above_the_minimum() {
+        assert_eq!(Theme::grid_spacing(1.0, 40.0), 100.0);
+        assert_eq!(Theme::grid_spacing(4.0, 40.0), 10.0);
+        assert_eq!(Theme::grid_spacing(0.5, 40.0), 100.0);
+        let spacing = Theme::grid_spacing(3.0, 40.0);
+        assert!(spacing * 3.0 >= 40.0 && spacing * 3.0 < 400.0);
+    }
+}
@@ -4,22 +4,192 @@
 //! (tree, attractors, configuration, etc.) and implements [`eframe::App`]
 //! to render and control the simulation through an egui UI.
 
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+
 use eframe::App;
 use glam::Vec2;
-use rand::rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use sim_core::{
     attractor::AttractorSet,
-    config::{Config, SpawnTool},
-    influence_buffer::InfluenceBuffer,
+    config::{Config, InfluenceFalloff, KillAnchor, PhyllotaxyParams, SpawnTool},
+    edge_buffer::EdgeBuffer,
+    export::{
+        SvgOptions, edges_to_csv_with, load_state,
+        raster::{RenderStyle, rasterize_tree_with},
+        save_state, to_csv, to_svg_with,
+    },
     phases,
-    tree::Tree,
+    recorder::GrowthRecorder,
+    simulation::Simulation,
+    tree::{RadiusModel, RadiusParams, Tree, TreeStats},
     types::NodeId,
 };
 
+use crate::sweep::{self, SweepParam, SweepRun, SweepSettings};
+use crate::theme::{Background, Theme};
+
+/// How alive attractors are drawn in the central panel.
+///
+/// - `Points` draws one small dot per attractor (the classic view).
+/// - `Heatmap` bins attractors into a screen-space grid and draws one
+///   translucent cell per occupied bin, colored by count. This stays
+///   readable (and cheap) for clouds with thousands of points.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum AttractorView {
+    Points,
+    Heatmap,
+}
+
+/// Where a node's rendering depth (`z`) comes from.
+///
+/// `z` only affects drawing (draw order, parallax); the simulation itself
+/// stays strictly 2-D.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum ZSource {
+    /// All nodes share `z = 0`; nodes are drawn in id order.
+    None,
+    /// `z` is the node's depth (edges to its root), so tips draw on top.
+    Depth,
+    /// `z` is the node's [`born_step`](sim_core::tree::TreeNode::born_step),
+    /// so newer nodes draw on top.
+    BornStep,
+}
+
+/// Fraction of the pan offset added to the deepest nodes for a parallax cue.
+const PARALLAX: f32 = 0.05;
+
+/// Maximum number of snapshots kept on the undo stack.
+const MAX_UNDO: usize = 16;
+
+/// Zoom (pixels per world unit) of a fresh viewer and after "Center".
+const DEFAULT_ZOOM: f32 = 3.0;
+
+/// Arrow-key pan speed in screen pixels per second.
+const KEY_PAN_SPEED: f32 = 400.0;
+
+/// Pan speed multiplier while Shift is held.
+const KEY_PAN_FAST: f32 = 4.0;
+
+/// Fraction of the view filled by the selected subtree after zooming to it.
+const ZOOM_TO_FILL: f32 = 0.8;
+
+/// Smallest on-screen node radius (pixels) at which growth rings are drawn.
+const RING_MIN_RADIUS: f32 = 8.0;
+
+/// Smallest on-screen spacing (pixels) between two drawn growth rings.
+const RING_SPACING: f32 = 2.0;
+
+/// On-screen radius (pixels) of an attractor with weight `1.0`.
+const ATTRACTOR_RADIUS: f32 = 2.0;
+
+/// Largest on-screen radius (pixels) of a heavily weighted attractor.
+const ATTRACTOR_RADIUS_MAX: f32 = 8.0;
+
+/// Seed of the attractor cloud a viewer starts with.
+const DEFAULT_SEED: u64 = 1;
+
+/// File written by "Save" and read by "Load", inside the output directory.
+const STATE_FILE: &str = "sca_state.json";
+
+/// How long the "undo" toast stays visible after a destructive action (seconds).
+const UNDO_TOAST_SECS: f64 = 4.0;
+
+/// Side length of one parameter-sweep thumbnail (points).
+const SWEEP_THUMB_SIZE: f32 = 120.0;
+
+/// Thumbnails per row in the parameter-sweep window.
+const SWEEP_COLUMNS: usize = 4;
+
+/// Edge budget range for level-of-detail rendering of huge trees.
+const LOD_MIN_EDGES: usize = 5_000;
+const LOD_MAX_EDGES: usize = 100_000;
+
+/// Frame times (seconds) above which the LOD budget shrinks, and below
+/// which it grows back.
+const LOD_SLOW_FRAME: f32 = 1.0 / 30.0;
+const LOD_FAST_FRAME: f32 = 1.0 / 55.0;
+
+/// A value that counts how often it was borrowed mutably.
+///
+/// Reads go through [`Deref`]; every [`DerefMut`] bumps `revision`, so
+/// data derived from the value can tell when it may be stale without each
+/// mutation site having to invalidate it.
+///
+/// ### Fields
+/// - `value` - The wrapped value.
+/// - `revision` - Number of mutable borrows so far.
+struct Tracked<T> {
+    value: T,
+    revision: u64,
+}
+
+impl<T> Tracked<T> {
+    fn new(value: T) -> Self {
+        Self { value, revision: 0 }
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Tracked<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.revision += 1;
+        &mut self.value
+    }
+}
+
+/// Overlay data derived from the live simulation.
+///
+/// Rebuilt by [`Viewer::refresh_derived`] only when the simulation's
+/// revision changes, instead of on every frame. Overlays that are
+/// switched off are left as `None` until they are turned on.
+///
+/// ### Fields
+/// - `revision` - Simulation revision the data was computed at.
+/// - `unreachable` - [`AttractorSet::unreachable`] for the unreachable highlight.
+/// - `preview` - [`Simulation::preview_growth`] for the ghost markers.
+/// - `tree_stats` - [`Tree::stats`] shown in the status bar.
+/// - `edges` - [`EdgeBuffer`] of the live tree, shared by drawing and export.
+/// - `rings` - [`Tree::trunk_ring_counts`] for the growth rings.
+/// - `radius_settings` - Thickness model and parameters `radii` were
+///   computed with.
+/// - `radii` - [`Tree::model_radii`] the live nodes are drawn with.
+#[derive(Default)]
+struct Derived {
+    revision: Option<u64>,
+    unreachable: Option<Vec<usize>>,
+    preview: Option<Vec<(NodeId, Vec2)>>,
+    tree_stats: TreeStats,
+    edges: EdgeBuffer,
+    rings: Option<Vec<u32>>,
+    radius_settings: Option<(RadiusModel, RadiusParams)>,
+    radii: Vec<f32>,
+}
+
+/// A full copy of the undoable viewer state.
+///
+/// ### Fields
+/// - `sim` - The simulation at the time of the snapshot.
+/// - `recorder` - The growth recording at the time of the snapshot.
+struct Snapshot {
+    sim: Simulation,
+    recorder: GrowthRecorder,
+}
+
 /// Main application state for the interactive viewer.
 ///
 /// [`Viewer`] glues together:
-/// - The simulation core: [`Tree`], [`AttractorSet`], [`InfluenceBuffer`], [`Config`].
+/// - The simulation core: a [`Simulation`] owning the tree, attractors,
+///   configuration, and influence buffer.
 /// - UI configuration (pan/zoom, spawn tool, timing).
 /// - eframe/egui callbacks for drawing and user interaction.
 ///
@@ -29,100 +199,227 @@ use sim_core::{
 /// 3. Render the tree, attractors, and tool hints.
 ///
 /// ### Fields
-/// - `tree` - Current tree structure being grown.
-/// - `attractors` - Set of attractor points driving the growth.
-/// - `acc` - Per-node influence buffer used between phases.
-/// - `cfg` - Global simulation configuration (radii, k-NN, tropism, spawn settings).
+/// - `sim` - The simulation being displayed (tree, attractors, config, buffer).
+/// - `derived` - Overlay data cached for the current revision of `sim`.
 ///
 /// - `rng` - Random number generator used for spawning attractors.
+/// - `seed` - Seed `rng` is reset to by [`Viewer::reset`], so the same seed
+///   always regenerates the same scene.
 ///
 /// - `running` - Whether the simulation is currently auto-advancing.
 /// - `zoom` - Zoom factor for world-to-screen coordinate mapping.
 /// - `pan` - Screen-space pan offset in pixels.
+/// - `selected` - Node picked with a right click; its subtree is framed by
+///   zoom-to-selection (`F`).
+/// - `fit_pending` - "Fit view" was clicked; the camera frames the whole
+///   tree once the central panel's size is known.
+/// - `line_start` - Last point placed with the line tool; the next click
+///   spawns attractors along the line from here.
 ///
-/// - `last_new_ids` - Node ids created in the last simulation step (for highlighting).
+/// - `new_node_births` - egui time at which each highlighted node was created.
+/// - `highlight_fade` - Seconds over which a new node fades from the highlight
+///   color to the normal color. With `0` only the nodes of the latest step are highlighted.
+/// - `frame_time` - egui time of the current frame.
 ///
 /// - `step_interval` - Target time step between automatic simulation steps (seconds).
 /// - `last_step_time` - Time stamp of the last step (egui time).
 /// - `last_step_dt` - Actual time delta between the last two steps (for display only).
+///
+/// - `attractor_view` - Whether attractors are drawn as points or as a density heatmap.
+/// - `heatmap_cell` - Side length of a heatmap cell in screen pixels.
+/// - `highlight_unreachable` - Draw attractors out of reach of every node in a warning color.
+/// - `show_preview` - Draw ghost markers where the next step would grow.
+/// - `highlight_frontier` - Outline the influenced leaves (active growth tips).
+/// - `show_influence` - Debug overlay drawing each influenced node's average pull as an arrow.
+/// - `z_from` - Source of per-node depth used for draw order and pan parallax.
+/// - `branch_gradient` - Color branches from `gradient_root` at the trunk to
+///   `gradient_tip` at the tips.
+/// - `color_trees` - Color each tree of a forest in its own hue (ignored
+///   while `branch_gradient` is on).
+/// - `lod_budget` - Most edges drawn per frame before switching to a decimated tree.
+/// - `radius_model` / `radius_params` - Thickness model nodes are drawn
+///   with. The tree's own radii only change when "Apply radii" stores them.
+/// - `show_rings` - Draw age rings inside nodes that appear large enough.
+/// - `theme` - Background, grid, and accent the scene colors derive from.
+/// - `spawn_weight` - Weight given to newly spawned attractors.
+/// - `auto_compact` - Drop dead attractors after a step once they make up
+///   more than half of the set.
+///
+/// - `recorder` - Per-step growth record used by the timeline slider.
+/// - `scrub_frame` - Recorded frame currently displayed, or `None` to show the live tree.
+///
+/// - `export_style` - Size and antialiasing settings for PNG export.
+/// - `output_dir` - Directory the export, save, and load buttons use.
+/// - `file_status` - Outcome of the last file action, shown in the status
+///   bar (`Err` for failures).
+///
+/// - `undo_stack` - Snapshots taken before destructive actions (newest last).
+/// - `undo_toast_until` - egui time until which the undo toast is shown.
+///
+/// - `show_sweep` - Whether the parameter sweep window is open.
+/// - `sweep` - Settings of the next sweep.
+/// - `sweep_run` - The last sweep started, grown one sample per frame and
+///   labeled with the parameter it was started with.
 pub struct Viewer {
-    tree: Tree,
-    attractors: AttractorSet,
-    acc: InfluenceBuffer,
-    cfg: Config,
+    sim: Tracked<Simulation>,
+    derived: Derived,
 
-    rng: rand::rngs::ThreadRng,
+    rng: StdRng,
+    seed: u64,
 
     running: bool,
     zoom: f32,
     pan: egui::Vec2,
+    selected: Option<NodeId>,
+    line_start: Option<Vec2>,
+    fit_pending: bool,
 
-    last_new_ids: Vec<NodeId>,
+    new_node_births: HashMap<NodeId, f64>,
+    highlight_fade: f64,
+    frame_time: f64,
 
     step_interval: f64,
     last_step_time: f64,
     last_step_dt: f64,
+
+    attractor_view: AttractorView,
+    heatmap_cell: f32,
+    highlight_unreachable: bool,
+    show_preview: bool,
+    highlight_frontier: bool,
+    show_influence: bool,
+    z_from: ZSource,
+    branch_gradient: bool,
+    color_trees: bool,
+    gradient_root: egui::Color32,
+    gradient_tip: egui::Color32,
+    lod_budget: usize,
+    radius_model: RadiusModel,
+    radius_params: RadiusParams,
+    show_rings: bool,
+    theme: Theme,
+    spawn_weight: f32,
+    auto_compact: bool,
+
+    recorder: GrowthRecorder,
+    scrub_frame: Option<usize>,
+
+    export_style: RenderStyle,
+    output_dir: String,
+    file_status: Option<Result<String, String>>,
+
+    undo_stack: Vec<Snapshot>,
+    undo_toast_until: Option<f64>,
+
+    show_sweep: bool,
+    sweep: SweepSettings,
+    sweep_run: Option<SweepRun>,
 }
 
 impl Viewer {
-    /// Creates a new viewer with a single root node and a random attractor cloud.
+    /// Creates a new viewer seeded with [`DEFAULT_SEED`].
+    ///
+    /// See [`Viewer::with_seed`].
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+
+    /// Creates a new viewer with a single root node and a seeded attractor cloud.
     ///
     /// The default setup is:
     /// - A tree with one root at `(0, 0)` and radius `1.0`.
     /// - An oval of attractors centered around `(0, 120)` with radii `(100, 100)`.
-    /// - A fresh [`InfluenceBuffer`] sized to the current number of nodes.
     /// - [`Config::default`] for simulation parameters.
     ///
     /// The camera starts with a moderate zoom and no pan.
     ///
+    /// ### Parameters
+    /// - `seed` - Seed for the attractor cloud and later spawns; viewers
+    ///   created with the same seed start from identical scenes.
+    ///
     /// ### Returns
     /// A fully-initialized [`Viewer`] ready to be passed to `eframe::run_native`.
-    pub fn new() -> Self {
-        let mut rng = rng();
-        let tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
-        let attractors = AttractorSet::random_in_oval(
-            Vec2::new(0.0, 120.0),
-            Vec2::new(100.0, 100.0),
-            1000,
-            &mut rng,
-        );
-        let acc = InfluenceBuffer::with_len(tree.nodes.len());
-        let cfg = Config::default();
+    pub fn with_seed(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut sim = Simulation::new(Config::default());
+        Self::populate_default_scene(&mut sim, &mut rng);
+        let mut recorder = GrowthRecorder::new();
+        recorder.record(&sim.tree);
 
         Self {
-            tree,
-            attractors,
-            acc,
-            cfg,
+            sim: Tracked::new(sim),
+            derived: Derived::default(),
             rng,
+            seed,
             running: false,
-            zoom: 3.0,
+            zoom: DEFAULT_ZOOM,
             pan: egui::vec2(0.0, 0.0),
-            last_new_ids: Vec::with_capacity(16),
+            selected: None,
+            line_start: None,
+            fit_pending: false,
+            new_node_births: HashMap::new(),
+            highlight_fade: 0.5,
+            frame_time: 0.0,
             step_interval: 0.1,
             last_step_time: 0.0,
             last_step_dt: 0.0,
+            attractor_view: AttractorView::Points,
+            heatmap_cell: 16.0,
+            highlight_unreachable: false,
+            show_preview: false,
+            highlight_frontier: false,
+            show_influence: false,
+            z_from: ZSource::None,
+            branch_gradient: false,
+            color_trees: false,
+            gradient_root: egui::Color32::from_rgb(110, 70, 40),
+            gradient_tip: egui::Color32::from_rgb(90, 200, 80),
+            lod_budget: LOD_MAX_EDGES,
+            radius_model: RadiusModel::Inherit,
+            radius_params: RadiusParams::default(),
+            show_rings: false,
+            theme: Theme::default(),
+            spawn_weight: 1.0,
+            auto_compact: false,
+            recorder,
+            scrub_frame: None,
+            export_style: RenderStyle::default(),
+            output_dir: ".".to_owned(),
+            file_status: None,
+            undo_stack: Vec::new(),
+            undo_toast_until: None,
+            show_sweep: false,
+            sweep: SweepSettings::default(),
+            sweep_run: None,
         }
     }
 
+    /// Fills an empty simulation with the default scene.
+    ///
+    /// Adds a root at `(0, 0)` with radius `1.0` and an oval of 1000
+    /// attractors centered around `(0, 120)` with radii `(100, 100)`.
+    fn populate_default_scene(sim: &mut Simulation, rng: &mut impl rand::Rng) {
+        sim.add_root(Vec2::new(0.0, 0.0), 1.0);
+        sim.attractors =
+            AttractorSet::random_in_oval(Vec2::new(0.0, 120.0), Vec2::new(100.0, 100.0), 1000, rng);
+    }
+
     /// Resets the simulation to a fresh tree and attractor set.
     ///
     /// This keeps the current configuration (`cfg`) and camera settings,
     /// but:
     /// - Replaces the tree with a single root at `(0, 0)`.
-    /// - Generates a new random attractor set in the default oval region.
-    /// - Resizes the influence buffer to match the new tree.
-    /// - Clears `last_new_ids` and stops auto-running.
+    /// - Reseeds the RNG with `seed` and regenerates the attractor set in
+    ///   the default oval region from it, so a given seed always yields
+    ///   the same cloud.
+    /// - Restarts the growth recording from the new scene.
+    /// - Clears the new-node highlights and stops auto-running.
     fn reset(&mut self) {
-        self.tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
-        self.attractors = AttractorSet::random_in_oval(
-            Vec2::new(0.0, 120.0),
-            Vec2::new(100.0, 100.0),
-            1000,
-            &mut self.rng,
-        );
-        self.acc = InfluenceBuffer::with_len(self.tree.nodes.len());
-        self.last_new_ids.clear();
+        self.sim.clear();
+        self.rng = StdRng::seed_from_u64(self.seed);
+        Self::populate_default_scene(&mut self.sim, &mut self.rng);
+        self.restart_recording();
+        self.new_node_births.clear();
         self.running = false;
     }
 
@@ -132,31 +429,552 @@ impl Viewer {
     /// - The tree has no nodes.
     /// - There are no attractors.
     /// - The influence buffer is empty.
-    /// - `last_new_ids` is cleared.
+    /// - The growth recording is restarted.
+    /// - The new-node highlights are cleared.
+    ///
+    /// The previous state is pushed onto the undo stack first, so an
+    /// accidental clear can be reverted with [`Viewer::undo`].
     ///
     /// This is mainly useful as a “blank canvas” for manual spawning.
     fn clear(&mut self) {
-        self.tree.nodes.clear();
-        self.attractors.points.clear();
-        self.acc = InfluenceBuffer::with_len(0);
-        self.last_new_ids.clear();
+        self.push_undo();
+        self.sim.clear();
+        self.restart_recording();
+        self.new_node_births.clear();
     }
 
-    /// Advances the simulation by a single step.
+    /// Saves the current simulation and recording onto the undo stack.
     ///
-    /// The step consists of:
-    /// 1. [`phases::attraction_phase`] — accumulate influences into [`InfluenceBuffer`].
-    /// 2. [`phases::growth_phase`] — grow new nodes based on the influences.
-    /// 3. [`phases::kill_phase`] — mark attractors near the tree as dead.
+    /// The oldest snapshot is dropped once [`MAX_UNDO`] is reached.
+    fn push_undo(&mut self) {
+        if self.undo_stack.len() == MAX_UNDO {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(Snapshot {
+            sim: self.sim.clone(),
+            recorder: self.recorder.clone(),
+        });
+    }
+
+    /// Restores the most recent snapshot from the undo stack.
+    ///
+    /// Auto-running is stopped and the view returns to the live tree.
+    ///
+    /// ### Returns
+    /// `true` if a snapshot was restored, `false` if the stack was empty.
+    fn undo(&mut self) -> bool {
+        let Some(snapshot) = self.undo_stack.pop() else {
+            return false;
+        };
+        *self.sim = snapshot.sim;
+        self.recorder = snapshot.recorder;
+        self.scrub_frame = None;
+        self.new_node_births.clear();
+        self.running = false;
+        self.undo_toast_until = None;
+        true
+    }
+
+    /// Discards all recorded frames and records the current tree as frame `0`.
+    fn restart_recording(&mut self) {
+        self.recorder.clear();
+        self.recorder.record(&self.sim.tree);
+        self.scrub_frame = None;
+    }
+
+    /// Advances the simulation by a single step via [`Simulation::step`].
     ///
-    /// The ids of nodes created in this step are stored in `last_new_ids`
-    /// so they can be highlighted in the next frame.
+    /// The nodes created in this step are highlighted via
+    /// [`Viewer::highlight_new`].
+    /// With `auto_compact`, dead attractors are dropped once they outnumber
+    /// the alive ones. Auto-running stops once a step changes nothing. The
+    /// new state is recorded and the view jumps back to the live tree.
     fn step_once(&mut self) {
-        phases::attraction_phase(&self.tree, &mut self.attractors, &self.cfg, &mut self.acc);
-        let new_ids = phases::growth_phase(&mut self.tree, &self.acc, &self.cfg);
-        phases::kill_phase(&self.tree, &mut self.attractors, &self.cfg);
+        let result = self.sim.step();
+        if result.is_converged() && self.sim.follow_target.is_none() {
+            self.running = false;
+        }
+        let new_ids = result.new_ids;
+        let attractors = &mut self.sim.attractors;
+        if self.auto_compact && attractors.dead_count() * 2 > attractors.points.len() {
+            attractors.retain_alive();
+        }
+        self.highlight_new(&new_ids);
+        self.recorder.record(&self.sim.tree);
+        self.scrub_frame = None;
+    }
+
+    /// Starts the highlight of freshly created nodes at the current frame time.
+    ///
+    /// Highlights that have fully faded are dropped. With a fade duration of
+    /// `0` all previous highlights are dropped, so only `ids` stay red.
+    ///
+    /// ### Parameters
+    /// - `ids` - Nodes created since the last call.
+    fn highlight_new(&mut self, ids: &[NodeId]) {
+        let now = self.frame_time;
+        if self.highlight_fade <= 0.0 {
+            self.new_node_births.clear();
+        } else {
+            let fade = self.highlight_fade;
+            self.new_node_births.retain(|_, born| now - *born < fade);
+        }
+        for &id in ids {
+            self.new_node_births.insert(id, now);
+        }
+    }
+
+    /// Returns the fill color of a node, fading new nodes from the theme's
+    /// highlight color to `base`.
+    ///
+    /// ### Parameters
+    /// - `id` - Node to color.
+    /// - `base` - Color of the node when it is not highlighted.
+    ///
+    /// ### Returns
+    /// The theme's highlight color for a node created at the current frame (or at any time since the
+    /// last step when the fade duration is `0`), `base` for nodes that are
+    /// not highlighted, and a blend of the two in between.
+    fn node_color(&self, id: NodeId, base: egui::Color32) -> egui::Color32 {
+        let Some(&born) = self.new_node_births.get(&id) else {
+            return base;
+        };
+        if self.highlight_fade <= 0.0 {
+            return self.theme.highlight();
+        }
+        let t = ((self.frame_time - born) / self.highlight_fade).clamp(0.0, 1.0) as f32;
+        self.theme.highlight().lerp_to_gamma(base, t)
+    }
+
+    /// Adapts the level-of-detail edge budget to the last frame time.
+    ///
+    /// Slow frames shrink the budget by 20%, fast ones grow it by 10%,
+    /// within `LOD_MIN_EDGES..=LOD_MAX_EDGES`.
+    ///
+    /// ### Parameters
+    /// - `frame_dt` - Duration of the last frame in seconds.
+    fn adapt_lod(&mut self, frame_dt: f32) {
+        let budget = if frame_dt > LOD_SLOW_FRAME {
+            self.lod_budget * 4 / 5
+        } else if frame_dt < LOD_FAST_FRAME {
+            self.lod_budget * 11 / 10
+        } else {
+            self.lod_budget
+        };
+        self.lod_budget = budget.clamp(LOD_MIN_EDGES, LOD_MAX_EDGES);
+    }
+
+    /// Per-node branch colors along the trunk-to-tip gradient.
+    ///
+    /// ### Returns
+    /// One color per node of `tree`, blending `gradient_root` into
+    /// `gradient_tip` by [`Tree::path_fractions`].
+    fn gradient_colors(&self, tree: &Tree) -> Vec<egui::Color32> {
+        tree.path_fractions()
+            .into_iter()
+            .map(|f| self.gradient_root.lerp_to_gamma(self.gradient_tip, f))
+            .collect()
+    }
+
+    /// Computes a rendering depth for every node of `tree`.
+    ///
+    /// ### Parameters
+    /// - `tree` - The tree being drawn.
+    /// - `source` - Which property to use as `z`.
+    ///
+    /// ### Returns
+    /// One `z` value per node (`0` or larger).
+    fn z_values(tree: &Tree, source: ZSource) -> Vec<f32> {
+        match source {
+            ZSource::None => vec![0.0; tree.nodes.len()],
+            ZSource::Depth => tree.depths().into_iter().map(|d| d as f32).collect(),
+            ZSource::BornStep => tree.nodes.iter().map(|n| n.born_step as f32).collect(),
+        }
+    }
+
+    /// Returns node ids sorted back-to-front by their `z` value.
+    ///
+    /// The sort is stable, so nodes with equal `z` keep their id order.
+    ///
+    /// ### Parameters
+    /// - `z` - Per-node depth from [`Viewer::z_values`].
+    fn draw_order(z: &[f32]) -> Vec<NodeId> {
+        let mut order: Vec<NodeId> = (0..z.len()).collect();
+        order.sort_by(|&a, &b| z[a].total_cmp(&z[b]));
+        order
+    }
+
+    /// Per-node colors with one hue per tree of the forest.
+    ///
+    /// ### Returns
+    /// One color per node of `tree`, from [`Theme::component`] of its
+    /// [`Tree::component_ids`] label.
+    fn component_colors(&self, tree: &Tree) -> Vec<egui::Color32> {
+        tree.component_ids()
+            .into_iter()
+            .map(|c| self.theme.component(c))
+            .collect()
+    }
+
+    /// Toggles the lock of the alive attractor nearest to `pos`.
+    ///
+    /// Locked attractors act as permanent guides: they keep pulling on the
+    /// tree but are never killed.
+    ///
+    /// ### Parameters
+    /// - `pos` - World-space click position.
+    /// - `radius` - Maximum world distance from `pos` to the attractor.
+    ///
+    /// ### Returns
+    /// The index of the toggled attractor, or `None` if none was in range.
+    fn toggle_lock_near(&mut self, pos: Vec2, radius: f32) -> Option<usize> {
+        let i = self.attractor_near(pos, radius)?;
+        let a = &mut self.sim.attractors.points[i];
+        a.locked = !a.locked;
+        Some(i)
+    }
+
+    /// Index of the alive attractor nearest to `pos`, if any lies within
+    /// `radius` (world units).
+    fn attractor_near(&self, pos: Vec2, radius: f32) -> Option<usize> {
+        self.sim
+            .attractors
+            .points
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| a.alive)
+            .map(|(i, a)| (i, a.pos.distance_squared(pos)))
+            .filter(|&(_, d2)| d2 <= radius * radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// On-screen radius of an attractor point with the given weight.
+    ///
+    /// Proportional to the weight, so weight `1.0` draws at
+    /// [`ATTRACTOR_RADIUS`], clamped to stay visible but not cover the tree.
+    fn attractor_radius(weight: f32) -> f32 {
+        (ATTRACTOR_RADIUS * weight).clamp(1.0, ATTRACTOR_RADIUS_MAX)
+    }
+
+    /// Places the next point of a line with the line tool.
+    ///
+    /// If a previous point exists, attractors are spawned along the line
+    /// between the two via [`Simulation::spawn_along_line`]. The new point
+    /// becomes the start of the next segment, so consecutive clicks trace
+    /// a polyline.
+    ///
+    /// ### Parameters
+    /// - `pos` - World-space click position.
+    ///
+    /// ### Returns
+    /// The number of attractors spawned.
+    fn place_line_point(&mut self, pos: Vec2) -> usize {
+        let added = match self.line_start {
+            Some(start) => self.sim.spawn_along_line(start, pos, &mut self.rng),
+            None => 0,
+        };
+        self.weigh_newest_attractors(added);
+        self.line_start = Some(pos);
+        added
+    }
+
+    /// Gives the `added` most recently spawned attractors `spawn_weight`.
+    fn weigh_newest_attractors(&mut self, added: usize) {
+        let points = &mut self.sim.attractors.points;
+        let start = points.len() - added;
+        for a in &mut points[start..] {
+            a.weight = self.spawn_weight;
+        }
+    }
+
+    /// Selects the tree node nearest to `pos`, or clears the selection.
+    ///
+    /// ### Parameters
+    /// - `pos` - World-space click position.
+    /// - `radius` - Maximum world distance from `pos` to the node.
+    ///
+    /// ### Returns
+    /// The newly selected node, or `None` if none was in range.
+    fn select_near(&mut self, pos: Vec2, radius: f32) -> Option<NodeId> {
+        self.selected = self
+            .sim
+            .tree
+            .find_nearest_node(pos)
+            .filter(|&(_, d2)| d2 <= radius * radius)
+            .map(|(id, _)| id);
+        self.selected
+    }
+
+    /// The selected node, if it still exists in the live tree.
+    ///
+    /// Clearing, undoing, or loading can shrink the tree below the id.
+    fn selection(&self) -> Option<NodeId> {
+        self.selected.filter(|&id| id < self.sim.tree.nodes.len())
+    }
+
+    /// Centers the view on the selected subtree and zooms to fit it.
+    ///
+    /// The subtree's bounding box fills [`ZOOM_TO_FILL`] of `rect`; a
+    /// single leaf keeps the current zoom and is only centered.
+    ///
+    /// ### Parameters
+    /// - `rect` - Screen rectangle of the view.
+    ///
+    /// ### Returns
+    /// Whether a node was selected (and the camera moved).
+    fn zoom_to_selection(&mut self, rect: egui::Rect) -> bool {
+        let Some(id) = self.selection() else {
+            return false;
+        };
+        let (min, max) = self.sim.tree.subtree_bounds(id);
+        self.frame_box(min, max, rect);
+        true
+    }
+
+    /// Centers the view on the whole tree and zooms to fit it, like
+    /// [`Viewer::zoom_to_selection`] does for the selected subtree.
+    ///
+    /// ### Parameters
+    /// - `rect` - Screen rectangle of the view.
+    ///
+    /// ### Returns
+    /// Whether the tree had any nodes (and the camera moved).
+    fn fit_view(&mut self, rect: egui::Rect) -> bool {
+        let Some((min, max)) = self.sim.tree.bounds() else {
+            return false;
+        };
+        self.frame_box(min, max, rect);
+        true
+    }
+
+    /// Moves the camera so the world box `min..max` fills [`ZOOM_TO_FILL`]
+    /// of `rect`, centered. A zero-sized box keeps the current zoom.
+    fn frame_box(&mut self, min: Vec2, max: Vec2, rect: egui::Rect) {
+        let extent = max - min;
+        if extent.max_element() > 0.0 {
+            let fit_x = rect.width() / extent.x.max(f32::EPSILON);
+            let fit_y = rect.height() / extent.y.max(f32::EPSILON);
+            self.zoom = (fit_x.min(fit_y) * ZOOM_TO_FILL).clamp(0.1, 10.0);
+        }
+        let center = (min + max) * 0.5;
+        self.pan = egui::vec2(-center.x * self.zoom, center.y * self.zoom);
+    }
+
+    /// Resets the camera to the world origin at the default zoom.
+    fn center_view(&mut self) {
+        self.pan = egui::Vec2::ZERO;
+        self.zoom = DEFAULT_ZOOM;
+    }
+
+    /// Pan offset after holding the arrow keys for one frame.
+    ///
+    /// ### Parameters
+    /// - `pan` - Current pan offset.
+    /// - `dir` - Held arrow keys as an axis vector: `+x` for Left and `+y`
+    ///   for Up, since the pan moves the scene opposite to the camera.
+    /// - `fast` - Whether Shift is held.
+    /// - `dt` - Frame duration in seconds.
+    ///
+    /// ### Returns
+    /// `pan` moved by [`KEY_PAN_SPEED`] pixels per second along `dir`,
+    /// times [`KEY_PAN_FAST`] when `fast`.
+    fn key_pan(pan: egui::Vec2, dir: egui::Vec2, fast: bool, dt: f32) -> egui::Vec2 {
+        let speed = if fast {
+            KEY_PAN_SPEED * KEY_PAN_FAST
+        } else {
+            KEY_PAN_SPEED
+        };
+        pan + dir * speed * dt
+    }
+
+    /// Pans with the arrow keys (faster with Shift), zooms to the
+    /// selection with `F`, and centers the view on the origin with `Home`.
+    ///
+    /// Nothing happens while a text field has keyboard focus, so typing
+    /// into the config panel doesn't move the camera.
+    ///
+    /// ### Parameters
+    /// - `ctx` - egui context to read the keyboard from.
+    /// - `rect` - Screen rectangle of the view.
+    fn keyboard_camera(&mut self, ctx: &egui::Context, rect: egui::Rect) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let (dir, fast, dt, zoom_to, center) = ctx.input(|i| {
+            let axis = |neg, pos| i.key_down(pos) as i32 as f32 - i.key_down(neg) as i32 as f32;
+            (
+                egui::vec2(
+                    axis(egui::Key::ArrowRight, egui::Key::ArrowLeft),
+                    axis(egui::Key::ArrowDown, egui::Key::ArrowUp),
+                ),
+                i.modifiers.shift,
+                i.stable_dt,
+                i.key_pressed(egui::Key::F),
+                i.key_pressed(egui::Key::Home),
+            )
+        });
+        if dir != egui::Vec2::ZERO {
+            self.pan = Self::key_pan(self.pan, dir, fast, dt);
+            ctx.request_repaint();
+        }
+        if zoom_to {
+            self.zoom_to_selection(rect);
+        }
+        if center {
+            self.center_view();
+        }
+    }
+
+    /// Path of `file_name` inside the configured output directory.
+    fn output_path(&self, file_name: &str) -> PathBuf {
+        Path::new(&self.output_dir).join(file_name)
+    }
+
+    /// Runs a file action on `file_name` in the output directory and
+    /// records its outcome in `file_status`.
+    ///
+    /// ### Parameters
+    /// - `verb` - Past-tense description of the action, e.g. `"Exported"`.
+    /// - `file_name` - File name joined onto `output_dir`.
+    /// - `action` - Reads or writes the file at the given path.
+    fn run_file_action<E: std::fmt::Display>(
+        &mut self,
+        verb: &str,
+        file_name: &str,
+        action: impl FnOnce(&mut Self, &Path) -> Result<(), E>,
+    ) {
+        let path = self.output_path(file_name);
+        self.file_status = Some(match action(self, &path) {
+            Ok(()) => Ok(format!("{verb} {}", path.display())),
+            Err(err) => Err(format!("{}: {err}", path.display())),
+        });
+    }
+
+    /// Rasterizes the current tree and writes it as a grayscale PNG.
+    ///
+    /// The tree is drawn dark on a white background using `export_style`.
+    ///
+    /// ### Parameters
+    /// - `path` - Output file path.
+    ///
+    /// ### Returns
+    /// - `Ok(())` if the file was written.
+    /// - `Err` if the file could not be created or the PNG encoding failed.
+    fn export_png(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        self.refresh_derived();
+        let raster = rasterize_tree_with(&self.sim.tree, &self.derived.edges, &self.export_style);
+        // Raster values are ink coverage; invert for a white background.
+        let pixels: Vec<u8> = raster.pixels.iter().map(|&v| 255 - v).collect();
+
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let mut encoder = png::Encoder::new(file, raster.width as u32, raster.height as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&pixels)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Writes the current tree as an SVG image with default [`SvgOptions`].
+    ///
+    /// ### Parameters
+    /// - `path` - Output file path.
+    ///
+    /// ### Returns
+    /// - `Ok(())` if the file was written.
+    /// - `Err` if the file could not be written.
+    fn export_svg(&mut self, path: &Path) -> std::io::Result<()> {
+        self.refresh_derived();
+        let svg = to_svg_with(&self.sim.tree, &self.derived.edges, &SvgOptions::default());
+        std::fs::write(path, svg)
+    }
 
-        self.last_new_ids = new_ids;
+    /// Writes the current tree's nodes and edges as two CSV tables (see
+    /// [`to_csv`] and [`edges_to_csv_with`]).
+    ///
+    /// ### Parameters
+    /// - `nodes_path` - Output file for the node table.
+    /// - `edges_path` - Output file for the edge table.
+    ///
+    /// ### Returns
+    /// - `Ok(())` if both files were written.
+    /// - `Err` if either file could not be written.
+    fn export_csv(&mut self, nodes_path: &Path, edges_path: &Path) -> std::io::Result<()> {
+        self.refresh_derived();
+        std::fs::write(nodes_path, to_csv(&self.sim.tree))?;
+        std::fs::write(edges_path, edges_to_csv_with(&self.derived.edges))
+    }
+
+    /// Saves the tree, attractors, and configuration via [`save_state`].
+    ///
+    /// ### Parameters
+    /// - `path` - Output file path.
+    fn save_state(&self, path: &Path) -> std::io::Result<()> {
+        save_state(path, &self.sim.tree, &self.sim.attractors, &self.sim.cfg)
+    }
+
+    /// Replaces the simulation with a state loaded via [`load_state`].
+    ///
+    /// The previous state is pushed onto the undo stack first. The loaded
+    /// tree starts a fresh recording and auto-running is stopped.
+    ///
+    /// ### Parameters
+    /// - `path` - The file to read.
+    fn load_state(&mut self, path: &Path) -> std::io::Result<()> {
+        let (tree, attractors, cfg) = load_state(path)?;
+        self.push_undo();
+        *self.sim = Simulation::from_tree(tree, cfg);
+        self.sim.attractors = attractors;
+        self.restart_recording();
+        self.new_node_births.clear();
+        self.running = false;
+        Ok(())
+    }
+
+    /// Recomputes the cached overlay data if the simulation changed since it
+    /// was computed, or if an overlay was switched on since.
+    fn refresh_derived(&mut self) {
+        let revision = self.sim.revision;
+        if self.derived.revision != Some(revision) {
+            let mut edges = std::mem::take(&mut self.derived.edges);
+            edges.rebuild(&self.sim.tree);
+            self.derived = Derived {
+                revision: Some(revision),
+                tree_stats: self.sim.tree.stats(),
+                edges,
+                ..Derived::default()
+            };
+        }
+        if self.highlight_unreachable && self.derived.unreachable.is_none() {
+            self.derived.unreachable = Some(
+                self.sim
+                    .attractors
+                    .unreachable(&self.sim.tree, self.sim.cfg.influence_radius),
+            );
+        }
+        if self.show_preview && self.derived.preview.is_none() {
+            self.derived.preview = Some(self.sim.preview_growth());
+        }
+        if self.show_rings && self.derived.rings.is_none() {
+            self.derived.rings = Some(self.sim.tree.trunk_ring_counts());
+        }
+        let settings = (self.radius_model, self.radius_params);
+        if self.derived.radius_settings != Some(settings) {
+            self.derived.radius_settings = Some(settings);
+            self.derived.radii = self.sim.tree.model_radii(settings.0, settings.1);
+        }
+    }
+
+    /// Returns the tree to draw: the recorded frame being scrubbed, if any.
+    ///
+    /// ### Returns
+    /// - `Some(tree)` reconstructed from the recorder when scrubbing, or
+    /// - `None` when the live tree should be drawn.
+    fn scrubbed_tree(&self) -> Option<Tree> {
+        let frame = self.scrub_frame?;
+        self.recorder.reconstruct(&self.sim.tree, frame)
     }
 
     /// Converts a world-space position to screen-space.
@@ -197,32 +1015,73 @@ impl Viewer {
         Vec2::new(x, y)
     }
 
+    /// Bins alive attractors into a screen-space grid covering `rect`.
+    ///
+    /// The grid has `cols × rows` cells of `cell` pixels each, anchored at
+    /// `rect.min`. Attractors that fall outside `rect` are ignored.
+    ///
+    /// ### Parameters
+    /// - `rect` - Screen-space rectangle representing the drawing area.
+    /// - `cell` - Cell side length in pixels (clamped to at least `1.0`).
+    ///
+    /// ### Returns
+    /// `(cols, rows, counts)` where `counts[row * cols + col]` is the number
+    /// of alive attractors in that cell.
+    fn bin_attractors(&self, rect: egui::Rect, cell: f32) -> (usize, usize, Vec<u32>) {
+        let cell = cell.max(1.0);
+        let cols = (rect.width() / cell).ceil().max(1.0) as usize;
+        let rows = (rect.height() / cell).ceil().max(1.0) as usize;
+        let mut counts = vec![0u32; cols * rows];
+
+        for a in self.sim.attractors.points.iter().filter(|a| a.alive) {
+            let p = self.world_to_screen(a.pos, rect);
+            if !rect.contains(p) {
+                continue;
+            }
+            let col = (((p.x - rect.min.x) / cell) as usize).min(cols - 1);
+            let row = (((p.y - rect.min.y) / cell) as usize).min(rows - 1);
+            counts[row * cols + col] += 1;
+        }
+
+        (cols, rows, counts)
+    }
+
     /// Helper to draw a labeled `usize` [`egui::DragValue`].
+    ///
+    /// ### Returns
+    /// `true` if the value was changed this frame.
     fn labeled_drag_usize(
         ui: &mut egui::Ui,
         label: &str,
         value: &mut usize,
         range: std::ops::RangeInclusive<usize>,
         speed: f64,
-    ) {
+    ) -> bool {
         ui.horizontal(|ui| {
             ui.label(label);
-            ui.add(egui::DragValue::new(value).range(range).speed(speed));
-        });
+            ui.add(egui::DragValue::new(value).range(range).speed(speed))
+                .changed()
+        })
+        .inner
     }
 
     /// Helper to draw a labeled `f32` [`egui::DragValue`].
+    ///
+    /// ### Returns
+    /// `true` if the value was changed this frame.
     fn labeled_drag_f32(
         ui: &mut egui::Ui,
         label: &str,
         value: &mut f32,
         range: std::ops::RangeInclusive<f32>,
         speed: f64,
-    ) {
+    ) -> bool {
         ui.horizontal(|ui| {
             ui.label(label);
-            ui.add(egui::DragValue::new(value).range(range).speed(speed));
-        });
+            ui.add(egui::DragValue::new(value).range(range).speed(speed))
+                .changed()
+        })
+        .inner
     }
 
     /// Builds the top panel UI (run controls, stepping, zoom).
@@ -256,34 +1115,243 @@ impl Viewer {
                     self.reset();
                 }
 
+                ui.add(egui::DragValue::new(&mut self.seed).prefix("seed "))
+                    .on_hover_text("Seed used by Reset to generate the attractors");
+                if ui.button("Reseed").clicked() {
+                    self.seed = rand::random();
+                    self.reset();
+                }
+
                 if ui.button("Clear").clicked() {
                     self.clear();
+                    self.undo_toast_until = Some(ctx.input(|i| i.time) + UNDO_TOAST_SECS);
+                }
+
+                ui.add(egui::TextEdit::singleline(&mut self.output_dir).desired_width(100.0))
+                    .on_hover_text("Directory used by Export, Save, and Load");
+
+                if ui.button("Export PNG").clicked() {
+                    self.run_file_action("Exported", "sca_tree.png", |v, path| v.export_png(path));
+                }
+
+                if ui.button("Export SVG").clicked() {
+                    self.run_file_action("Exported", "sca_tree.svg", |v, path| v.export_svg(path));
+                }
+
+                if ui.button("Export CSV").clicked() {
+                    self.run_file_action("Exported", "sca_nodes.csv", |v, path| {
+                        v.export_csv(path, &path.with_file_name("sca_edges.csv"))
+                    });
+                }
+
+                if ui.button("Save").clicked() {
+                    self.run_file_action("Saved", STATE_FILE, |v, path| v.save_state(path));
                 }
 
+                if ui.button("Load").clicked() {
+                    self.run_file_action("Loaded", STATE_FILE, |v, path| v.load_state(path));
+                }
+
+                ui.toggle_value(&mut self.show_sweep, "Sweep");
+
                 ui.separator();
                 ui.add(egui::Slider::new(&mut self.zoom, 0.1..=10.0).text("Zoom"));
+                if ui.button("Fit view").clicked() {
+                    self.fit_pending = true;
+                }
+                if ui
+                    .button("Center")
+                    .on_hover_text("Reset pan and zoom to the origin (Home)")
+                    .clicked()
+                {
+                    self.center_view();
+                }
+
+                // Timeline: scrub through recorded frames without re-simulating.
+                let last = self.recorder.frame_count().saturating_sub(1);
+                if last > 0 {
+                    ui.separator();
+                    let mut frame = self.scrub_frame.unwrap_or(last);
+                    let changed = ui
+                        .add(egui::Slider::new(&mut frame, 0..=last).text("Frame"))
+                        .changed();
+                    if changed {
+                        self.running = false;
+                        self.scrub_frame = (frame < last).then_some(frame);
+                    }
+                    if self.scrub_frame.is_some() && ui.button("Live").clicked() {
+                        self.scrub_frame = None;
+                    }
+                }
             });
         });
     }
 
-    /// Builds the bottom status bar (time step, node count, alive attractors).
+    /// Shows a short-lived toast offering to undo the last destructive action.
+    fn ui_undo_toast(&mut self, ctx: &egui::Context) {
+        let Some(until) = self.undo_toast_until else {
+            return;
+        };
+        let now = ctx.input(|i| i.time);
+        if now >= until {
+            self.undo_toast_until = None;
+            return;
+        }
+
+        egui::Area::new("undo_toast".into())
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -40.0))
+            .movable(false)
+            .show(ctx, |ui| {
+                egui::Frame::new()
+                    .fill(egui::Color32::from_rgba_unmultiplied(0, 0, 0, 160))
+                    .inner_margin(8.0)
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Canvas cleared.");
+                            if ui.button("Undo (Ctrl+Z)").clicked() {
+                                self.undo();
+                            }
+                        });
+                    });
+            });
+
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(until - now));
+    }
+
+    /// Grows the next thumbnail of the running sweep, if any.
+    ///
+    /// Every sample uses the default scene seeded with the run's seed, so
+    /// only the swept parameter differs between them. One sample per
+    /// frame keeps the UI responsive while a sweep runs.
+    ///
+    /// ### Returns
+    /// `true` if a sample was grown and more remain.
+    fn advance_sweep(&mut self) -> bool {
+        let Some(run) = &mut self.sweep_run else {
+            return false;
+        };
+        run.advance(Self::populate_default_scene);
+        !run.is_done()
+    }
+
+    /// Shows the parameter sweep window: controls plus a grid of thumbnails.
+    fn ui_sweep_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_sweep;
+        egui::Window::new("Parameter sweep")
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("sweep_param")
+                        .selected_text(self.sweep.param.label())
+                        .show_ui(ui, |ui| {
+                            for param in SweepParam::ALL {
+                                ui.selectable_value(&mut self.sweep.param, param, param.label());
+                            }
+                        });
+                    ui.add(
+                        egui::DragValue::new(&mut self.sweep.min)
+                            .prefix("from ")
+                            .speed(0.05),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut self.sweep.max)
+                            .prefix("to ")
+                            .speed(0.05),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut self.sweep.samples)
+                            .prefix("samples = ")
+                            .range(1..=16),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut self.sweep.steps)
+                            .prefix("steps = ")
+                            .range(1..=500),
+                    );
+                    ui.add(egui::DragValue::new(&mut self.sweep.seed).prefix("seed = "));
+                    if ui.button("Run").clicked() {
+                        self.sweep_run = Some(SweepRun::new(self.sim.cfg, self.sweep));
+                    }
+                });
+
+                let Some(run) = &self.sweep_run else {
+                    return;
+                };
+                if !run.is_done() {
+                    let (done, total) = run.progress();
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!("growing {}/{total}", done + 1));
+                    });
+                }
+                ui.separator();
+                let label = run.settings.param.label();
+                egui::Grid::new("sweep_grid").show(ui, |ui| {
+                    for (i, thumb) in run.thumbnails.iter().enumerate() {
+                        ui.vertical(|ui| {
+                            let (rect, _) = ui.allocate_exact_size(
+                                egui::vec2(SWEEP_THUMB_SIZE, SWEEP_THUMB_SIZE),
+                                egui::Sense::hover(),
+                            );
+                            sweep::draw_thumbnail(ui.painter(), rect, &thumb.tree);
+                            ui.label(format!("{label} = {:.3}", thumb.value));
+                        });
+                        if (i + 1) % SWEEP_COLUMNS == 0 {
+                            ui.end_row();
+                        }
+                    }
+                });
+            });
+        self.show_sweep = open;
+    }
+
+    /// Builds the bottom status bar (time step, tree metrics, alive attractors).
     fn ui_status_bar(&self, ctx: &egui::Context) {
         egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 ui.label(format!("dt target = {:.3} s", self.step_interval));
                 ui.label(format!("dt last = {:.3} s", self.last_step_dt));
                 ui.separator();
-                ui.label(format!("nodes = {}", self.tree.nodes.len()));
-                ui.label(format!(
-                    "alive attractors = {}",
-                    self.attractors.points.iter().filter(|a| a.alive).count()
-                ));
+                if self.derived.edges.len() > self.lod_budget {
+                    ui.label(format!("LOD: {} edges", self.lod_budget));
+                }
+                let stats = self.sim.stats();
+                if let Some(id) = self.selection() {
+                    ui.label(format!("selected = #{id}"));
+                }
+                let tree = &self.derived.tree_stats;
+                ui.label(format!("branching = {:.2}", tree.avg_branching));
+                ui.label(format!("depth = {}", tree.max_depth));
+                ui.label(format!("length = {:.1}", tree.total_length));
+                ui.label(format!("leaves = {}", tree.leaf_count));
+                ui.label(format!("nodes = {}", stats.node_count));
+                ui.label(format!("alive attractors = {}", stats.alive_attractors));
+                match &self.file_status {
+                    Some(Ok(message)) => {
+                        ui.separator();
+                        ui.label(message);
+                    }
+                    Some(Err(message)) => {
+                        ui.separator();
+                        ui.colored_label(ui.visuals().error_fg_color, message);
+                    }
+                    None => {}
+                }
             });
         });
     }
 
     /// Builds the right-hand configuration panel for simulation parameters.
+    ///
+    /// The widgets edit a copy of the configuration that is written back
+    /// only when one of them changed, so merely showing the panel doesn't
+    /// count as a change to the simulation.
     fn ui_config_panel(&mut self, ctx: &egui::Context) {
+        let mut cfg = self.sim.cfg;
+        let mut cfg_changed = false;
         egui::SidePanel::right("config_panel")
             .resizable(true)
             .default_width(220.0)
@@ -292,94 +1360,400 @@ impl Viewer {
 
                 ui.separator();
                 ui.label("K-nearest settings");
-                Self::labeled_drag_usize(
+                cfg_changed |= Self::labeled_drag_usize(
                     ui,
                     "attract_from_kn:",
-                    &mut self.cfg.attract_from_kn,
+                    &mut cfg.attract_from_kn,
                     0..=10,
                     1.0,
                 );
-                Self::labeled_drag_usize(
+                cfg_changed |= ui
+                    .checkbox(&mut cfg.attract_k_nearest, "pull all k nearest")
+                    .on_hover_text(
+                        "Each attractor pulls every node up to rank attract_from_kn in range",
+                    )
+                    .changed();
+                cfg_changed |= Self::labeled_drag_usize(
                     ui,
                     "kill_from_kn:",
-                    &mut self.cfg.kill_from_kn,
+                    &mut cfg.kill_from_kn,
                     0..=10,
                     1.0,
                 );
+                ui.horizontal(|ui| {
+                    ui.label("kill anchor:");
+                    for (anchor, label) in [
+                        (KillAnchor::KthNode, "k-th"),
+                        (KillAnchor::NearestNode, "nearest"),
+                        (KillAnchor::Owner, "owner"),
+                    ] {
+                        cfg_changed |= ui
+                            .radio_value(&mut cfg.kill_anchor, anchor, label)
+                            .changed();
+                    }
+                });
 
                 ui.separator();
                 ui.label("Radii");
-                Self::labeled_drag_f32(
+                cfg_changed |= Self::labeled_drag_f32(
                     ui,
                     "influence_radius:",
-                    &mut self.cfg.influence_radius,
+                    &mut cfg.influence_radius,
                     0.0..=200.0,
                     0.5,
                 );
-                Self::labeled_drag_f32(
+                if ui
+                    .button("suggest (reach 90%)")
+                    .on_hover_text("Smallest radius that reaches 90% of the alive attractors")
+                    .clicked()
+                {
+                    let r = phases::suggest_influence_radius(
+                        &self.sim.tree,
+                        &self.sim.attractors,
+                        90.0,
+                    );
+                    if r > 0.0 {
+                        cfg.influence_radius = r;
+                        cfg_changed = true;
+                    }
+                }
+                cfg_changed |= Self::labeled_drag_f32(
                     ui,
                     "kill_radius:",
-                    &mut self.cfg.kill_radius,
+                    &mut cfg.kill_radius,
                     0.0..=200.0,
                     0.5,
                 );
 
+                ui.separator();
+                ui.label("Attraction falloff");
+                ui.horizontal(|ui| {
+                    for (falloff, label) in [
+                        (InfluenceFalloff::Constant, "constant"),
+                        (InfluenceFalloff::Linear, "linear"),
+                        (InfluenceFalloff::InverseSquare, "1/d²"),
+                    ] {
+                        cfg_changed |= ui.radio_value(&mut cfg.falloff, falloff, label).changed();
+                    }
+                });
+                let mut clamp = cfg.max_attractor_weight.is_finite();
+                if ui.checkbox(&mut clamp, "clamp weight").changed() {
+                    cfg.max_attractor_weight = if clamp { 4.0 } else { f32::INFINITY };
+                    cfg_changed = true;
+                }
+                if clamp {
+                    cfg_changed |= Self::labeled_drag_f32(
+                        ui,
+                        "max_attractor_weight:",
+                        &mut cfg.max_attractor_weight,
+                        0.1..=100.0,
+                        0.1,
+                    );
+                }
+
                 ui.separator();
                 ui.label("Growth");
-                Self::labeled_drag_f32(ui, "step_len:", &mut self.cfg.step_len, 0.0..=20.0, 0.2);
+                cfg_changed |=
+                    Self::labeled_drag_f32(ui, "step_len:", &mut cfg.step_len, 0.0..=20.0, 0.2);
+                cfg_changed |= ui
+                    .checkbox(&mut cfg.adaptive_step, "adaptive step")
+                    .on_hover_text(
+                        "Scale each step by the summed pull, from min_step up to step_len",
+                    )
+                    .changed();
+                if cfg.adaptive_step {
+                    cfg_changed |=
+                        Self::labeled_drag_f32(ui, "min_step:", &mut cfg.min_step, 0.0..=20.0, 0.1);
+                }
+                cfg_changed |= Self::labeled_drag_f32(
+                    ui,
+                    "direction_damping:",
+                    &mut cfg.direction_damping,
+                    0.0..=0.95,
+                    0.01,
+                );
+                cfg_changed |=
+                    Self::labeled_drag_f32(ui, "momentum:", &mut cfg.momentum, 0.0..=0.95, 0.01);
+                let mut limit_turn = cfg.max_turn_angle.is_some();
+                if ui
+                    .checkbox(&mut limit_turn, "limit turn angle")
+                    .on_hover_text("Keep growth within a cone around the incoming direction")
+                    .changed()
+                {
+                    cfg.max_turn_angle = limit_turn.then_some(std::f32::consts::FRAC_PI_4);
+                    cfg_changed = true;
+                }
+                if let Some(angle) = &mut cfg.max_turn_angle {
+                    ui.horizontal(|ui| {
+                        ui.label("max turn:");
+                        cfg_changed |= ui.drag_angle(angle).changed();
+                    });
+                    *angle = angle.clamp(0.0, std::f32::consts::PI);
+                }
+                cfg_changed |= ui
+                    .checkbox(&mut cfg.require_attraction, "require attraction")
+                    .on_hover_text("Don't grow nodes whose pulls cancel out, even with tropism")
+                    .changed();
+                cfg_changed |= ui
+                    .checkbox(&mut cfg.dedup_children, "dedup near children")
+                    .changed();
+                if cfg.dedup_children {
+                    cfg_changed |= Self::labeled_drag_f32(
+                        ui,
+                        "min_child_spacing:",
+                        &mut cfg.min_child_spacing,
+                        0.0..=20.0,
+                        0.05,
+                    );
+                }
+                cfg_changed |= ui
+                    .checkbox(&mut cfg.avoid_crossings, "avoid crossings")
+                    .changed();
+                let mut capped = cfg.max_nodes.is_some();
+                if ui
+                    .checkbox(&mut capped, "node budget")
+                    .on_hover_text("Stop growing once the tree has this many nodes")
+                    .changed()
+                {
+                    cfg.max_nodes = capped.then_some(10_000);
+                    cfg_changed = true;
+                }
+                if let Some(max_nodes) = &mut cfg.max_nodes {
+                    cfg_changed |=
+                        Self::labeled_drag_usize(ui, "max_nodes:", max_nodes, 1..=1_000_000, 10.0);
+                }
+                ui.checkbox(&mut self.show_preview, "preview next step");
+                ui.checkbox(&mut self.highlight_frontier, "highlight frontier");
+                let mut alternate = cfg.phyllotaxy.is_some();
+                if ui.checkbox(&mut alternate, "alternate sides").changed() {
+                    cfg.phyllotaxy = alternate.then_some(PhyllotaxyParams { divergence: 0.3 });
+                    cfg_changed = true;
+                }
+                if let Some(phyllotaxy) = &mut cfg.phyllotaxy {
+                    ui.horizontal(|ui| {
+                        ui.label("divergence:");
+                        cfg_changed |= ui.drag_angle(&mut phyllotaxy.divergence).changed();
+                    });
+                }
 
                 ui.separator();
                 ui.label("Tropism (gravity-like)");
-                Self::labeled_drag_f32(ui, "tropism.x:", &mut self.cfg.tropism.x, -2.0..=2.0, 0.05);
-                Self::labeled_drag_f32(ui, "tropism.y:", &mut self.cfg.tropism.y, -2.0..=2.0, 0.05);
+                cfg_changed |=
+                    Self::labeled_drag_f32(ui, "tropism.x:", &mut cfg.tropism.x, -2.0..=2.0, 0.05);
+                cfg_changed |=
+                    Self::labeled_drag_f32(ui, "tropism.y:", &mut cfg.tropism.y, -2.0..=2.0, 0.05);
+                cfg_changed |= Self::labeled_drag_f32(
+                    ui,
+                    "depth factor:",
+                    &mut cfg.tropism_depth_factor,
+                    0.0..=2.0,
+                    0.01,
+                );
 
                 ui.separator();
                 ui.label("Spawning");
-                Self::labeled_drag_usize(
+                cfg_changed |= Self::labeled_drag_usize(
                     ui,
                     "spawn_attractors:",
-                    &mut self.cfg.spawn_attractors,
+                    &mut cfg.spawn_attractors,
                     1..=1000,
                     1.0,
                 );
+                let mut by_density = cfg.spawn_density.is_some();
+                if ui
+                    .checkbox(&mut by_density, "count from density")
+                    .on_hover_text("Scale rect/oval counts with the shape's area")
+                    .changed()
+                {
+                    cfg.spawn_density = by_density.then_some(0.05);
+                    cfg_changed = true;
+                }
+                if let Some(density) = &mut cfg.spawn_density {
+                    cfg_changed |=
+                        Self::labeled_drag_f32(ui, "per unit area:", density, 0.001..=1.0, 0.001);
+                }
+                Self::labeled_drag_f32(ui, "weight:", &mut self.spawn_weight, 0.1..=4.0, 0.05);
 
                 ui.label("Rect half extents");
-                Self::labeled_drag_f32(
+                cfg_changed |= Self::labeled_drag_f32(
                     ui,
                     "hx:",
-                    &mut self.cfg.spawn_rect_half_extents.x,
+                    &mut cfg.spawn_rect_half_extents.x,
                     0.0..=1000.0,
                     1.0,
                 );
-                Self::labeled_drag_f32(
+                cfg_changed |= Self::labeled_drag_f32(
                     ui,
                     "hy:",
-                    &mut self.cfg.spawn_rect_half_extents.y,
+                    &mut cfg.spawn_rect_half_extents.y,
                     0.0..=1000.0,
                     1.0,
                 );
 
                 ui.label("Oval radii");
-                Self::labeled_drag_f32(
+                cfg_changed |= Self::labeled_drag_f32(
                     ui,
                     "rx:",
-                    &mut self.cfg.spawn_oval_radii.x,
+                    &mut cfg.spawn_oval_radii.x,
                     0.0..=1000.0,
                     1.0,
                 );
-                Self::labeled_drag_f32(
+                cfg_changed |= Self::labeled_drag_f32(
                     ui,
                     "ry:",
-                    &mut self.cfg.spawn_oval_radii.y,
+                    &mut cfg.spawn_oval_radii.y,
                     0.0..=1000.0,
                     1.0,
                 );
 
+                cfg_changed |= Self::labeled_drag_f32(
+                    ui,
+                    "line jitter:",
+                    &mut cfg.spawn_line_jitter,
+                    0.0..=100.0,
+                    0.5,
+                );
+
+                ui.separator();
+                ui.label(format!(
+                    "Dead attractors: {} / {}",
+                    self.sim.attractors.dead_count(),
+                    self.sim.attractors.points.len()
+                ));
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Compact")
+                        .on_hover_text("Drop dead attractors to reclaim memory")
+                        .clicked()
+                    {
+                        self.sim.attractors.retain_alive();
+                    }
+                    ui.checkbox(&mut self.auto_compact, "auto (> 50% dead)");
+                });
+
+                ui.separator();
+                ui.label("Attractor display");
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.attractor_view, AttractorView::Points, "points");
+                    ui.radio_value(&mut self.attractor_view, AttractorView::Heatmap, "heatmap");
+                });
+                if self.attractor_view == AttractorView::Points {
+                    ui.checkbox(&mut self.highlight_unreachable, "highlight unreachable");
+                }
+                if self.attractor_view == AttractorView::Heatmap {
+                    Self::labeled_drag_f32(
+                        ui,
+                        "cell (px):",
+                        &mut self.heatmap_cell,
+                        4.0..=64.0,
+                        0.5,
+                    );
+                }
+
+                ui.separator();
+                ui.label("Export");
+                ui.checkbox(&mut self.export_style.antialias, "antialias PNG");
+
+                ui.separator();
+                ui.label("New nodes");
+                ui.add(
+                    egui::DragValue::new(&mut self.highlight_fade)
+                        .prefix("fade (s) = ")
+                        .range(0.0..=5.0)
+                        .speed(0.05),
+                )
+                .on_hover_text("0 highlights only the latest step");
+
+                ui.separator();
+                ui.label("Theme");
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.theme.background, Background::Dark, "dark");
+                    ui.radio_value(&mut self.theme.background, Background::Light, "light");
+                    ui.label("accent");
+                    ui.color_edit_button_srgba(&mut self.theme.accent);
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.theme.show_grid, "grid");
+                    ui.color_edit_button_srgba(&mut self.theme.grid);
+                });
+
+                ui.separator();
+                ui.checkbox(&mut self.branch_gradient, "Branch gradient")
+                    .on_hover_text("Color branches from trunk to tips along their longest path");
+                if self.branch_gradient {
+                    ui.horizontal(|ui| {
+                        ui.label("trunk");
+                        ui.color_edit_button_srgba(&mut self.gradient_root);
+                        ui.label("tips");
+                        ui.color_edit_button_srgba(&mut self.gradient_tip);
+                    });
+                }
+                ui.add_enabled(
+                    !self.branch_gradient,
+                    egui::Checkbox::new(&mut self.color_trees, "Color trees"),
+                )
+                .on_hover_text("Give every tree of the forest its own hue");
+
+                ui.separator();
+                ui.label("Thickness");
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.radius_model, RadiusModel::Inherit, "inherit")
+                        .on_hover_text("Keep the radii as grown");
+                    ui.radio_value(&mut self.radius_model, RadiusModel::Murray, "Murray")
+                        .on_hover_text("r_parent^n = sum of r_child^n");
+                    ui.radio_value(&mut self.radius_model, RadiusModel::Strahler, "Strahler")
+                        .on_hover_text("Thickness from the branch's Strahler order");
+                });
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut self.radius_params.leaf_radius)
+                            .prefix("leaf r = ")
+                            .range(0.05..=5.0)
+                            .speed(0.05),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut self.radius_params.exponent)
+                            .prefix("n = ")
+                            .range(1.0..=4.0)
+                            .speed(0.05),
+                    );
+                });
+                let apply = ui
+                    .add_enabled(
+                        self.radius_model != RadiusModel::Inherit,
+                        egui::Button::new("Apply radii"),
+                    )
+                    .on_hover_text("Store the drawn radii in the tree, e.g. for export");
+                if apply.clicked() {
+                    self.sim
+                        .tree
+                        .apply_radius_model(self.radius_model, self.radius_params);
+                }
+                ui.checkbox(&mut self.show_rings, "growth rings")
+                    .on_hover_text("Draw age rings inside thick nodes");
+
+                ui.separator();
+                ui.label("Depth cue (draw order)");
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.z_from, ZSource::None, "none");
+                    ui.radio_value(&mut self.z_from, ZSource::Depth, "depth");
+                    ui.radio_value(&mut self.z_from, ZSource::BornStep, "birth");
+                });
+
+                ui.separator();
+                ui.label("Debug");
+                ui.checkbox(&mut self.show_influence, "influence arrows")
+                    .on_hover_text("Average pull on each influenced node from the last step");
+
                 ui.separator();
                 if ui.button("Reset cfg to default").clicked() {
-                    self.cfg = Config::default();
+                    cfg = Config::default();
+                    cfg_changed = true;
                 }
             });
+        if cfg_changed {
+            self.sim.cfg = cfg;
+        }
     }
 
     /// Builds the small floating toolbar for choosing the spawn tool.
@@ -394,55 +1768,147 @@ impl Viewer {
                         ui.vertical(|ui| {
                             if ui
                                 .selectable_label(
-                                    matches!(self.cfg.spawn_tool, SpawnTool::RootNode),
+                                    matches!(self.sim.cfg.spawn_tool, SpawnTool::RootNode),
                                     "◎ Root",
                                 )
                                 .clicked()
                             {
-                                self.cfg.spawn_tool = SpawnTool::RootNode;
+                                self.sim.cfg.spawn_tool = SpawnTool::RootNode;
                             }
 
                             if ui
                                 .selectable_label(
-                                    matches!(self.cfg.spawn_tool, SpawnTool::RectAttractors),
+                                    matches!(self.sim.cfg.spawn_tool, SpawnTool::RectAttractors),
                                     "■ Rect",
                                 )
                                 .clicked()
                             {
-                                self.cfg.spawn_tool = SpawnTool::RectAttractors;
+                                self.sim.cfg.spawn_tool = SpawnTool::RectAttractors;
                             }
 
                             if ui
                                 .selectable_label(
-                                    matches!(self.cfg.spawn_tool, SpawnTool::OvalAttractors),
+                                    matches!(self.sim.cfg.spawn_tool, SpawnTool::OvalAttractors),
                                     "○ Oval",
                                 )
                                 .clicked()
                             {
-                                self.cfg.spawn_tool = SpawnTool::OvalAttractors;
+                                self.sim.cfg.spawn_tool = SpawnTool::OvalAttractors;
+                            }
+
+                            if ui
+                                .selectable_label(
+                                    matches!(self.sim.cfg.spawn_tool, SpawnTool::LineAttractors),
+                                    "╱ Line",
+                                )
+                                .on_hover_text("Click points along a path; Esc ends it")
+                                .clicked()
+                            {
+                                self.sim.cfg.spawn_tool = SpawnTool::LineAttractors;
+                            }
+
+                            if ui
+                                .selectable_label(
+                                    matches!(
+                                        self.sim.cfg.spawn_tool,
+                                        SpawnTool::RectOutlineAttractors
+                                    ),
+                                    "□ Outline",
+                                )
+                                .clicked()
+                            {
+                                self.sim.cfg.spawn_tool = SpawnTool::RectOutlineAttractors;
+                            }
+
+                            if ui
+                                .selectable_label(
+                                    matches!(self.sim.cfg.spawn_tool, SpawnTool::Follow),
+                                    "➹ Follow",
+                                )
+                                .on_hover_text("Hold to steer growth toward the cursor")
+                                .clicked()
+                            {
+                                self.sim.cfg.spawn_tool = SpawnTool::Follow;
                             }
+
+                            ui.separator();
+                            ui.small("Shift+click: lock")
+                                .on_hover_text("Toggle a permanent guide attractor");
                         });
                     });
             });
     }
 
+    /// Draws an arrow at every influenced node in its average influence direction.
+    ///
+    /// The arrow length is proportional to the coherence of the pull, i.e.
+    /// the length of the averaged direction (capped at `1`): attractors
+    /// pulling the same way give a long arrow, opposing ones a short one.
+    /// Nodes outside the visible rect are skipped.
+    fn draw_influence_arrows(&self, painter: &egui::Painter, rect: egui::Rect) {
+        let acc = self.sim.influence();
+        let full_len = self.sim.cfg.step_len * self.zoom * 2.0;
+        let stroke = egui::Stroke::new(1.5, self.theme.influence());
+        let visible = rect.expand(full_len);
+
+        for id in acc.influenced_indices() {
+            let Some(node) = self.sim.tree.nodes.get(id) else {
+                continue;
+            };
+            let origin = self.world_to_screen(node.pos, rect);
+            if !visible.contains(origin) {
+                continue;
+            }
+
+            let dir = acc.avg_dir(id);
+            let coherence = dir.length().min(1.0);
+            let screen_dir = dir.normalize_or_zero() * coherence * full_len;
+            // World y points up, screen y points down.
+            let vec = egui::vec2(screen_dir.x, -screen_dir.y);
+            painter.arrow(origin, vec, stroke);
+        }
+    }
+
+    /// Draws world grid lines behind the scene, spaced by
+    /// [`Theme::grid_spacing`] so they stay at least 40 pixels apart.
+    fn draw_grid(&self, painter: &egui::Painter, rect: egui::Rect) {
+        let spacing = Theme::grid_spacing(self.zoom, 40.0);
+        let a = self.screen_to_world(rect.left_top(), rect);
+        let b = self.screen_to_world(rect.right_bottom(), rect);
+        let (min, max) = (a.min(b), a.max(b));
+        let stroke = egui::Stroke::new(1.0, self.theme.grid);
+
+        let mut x = (min.x / spacing).floor() * spacing;
+        while x <= max.x {
+            let sx = self.world_to_screen(Vec2::new(x, 0.0), rect).x;
+            painter.vline(sx, rect.y_range(), stroke);
+            x += spacing;
+        }
+        let mut y = (min.y / spacing).floor() * spacing;
+        while y <= max.y {
+            let sy = self.world_to_screen(Vec2::new(0.0, y), rect).y;
+            painter.hline(rect.x_range(), sy, stroke);
+            y += spacing;
+        }
+    }
+
     /// Draws a visual hint for the current spawn tool at the hovered world position.
     fn ui_tool_hint(&self, painter: &egui::Painter, rect: egui::Rect, hover_world: Option<Vec2>) {
         let Some(center) = hover_world else {
             return;
         };
 
-        let stroke = egui::Stroke::new(1.5, egui::Color32::YELLOW);
+        let stroke = egui::Stroke::new(1.5, self.theme.accent);
 
-        match self.cfg.spawn_tool {
+        match self.sim.cfg.spawn_tool {
             SpawnTool::RootNode => {
                 let p_screen = self.world_to_screen(center, rect);
-                let r = self.cfg.step_len * self.zoom * 0.5;
-                painter.circle_filled(p_screen, r, egui::Color32::GREEN);
+                let r = self.sim.cfg.step_len * self.zoom * 0.5;
+                painter.circle_filled(p_screen, r, self.theme.node());
             }
 
-            SpawnTool::RectAttractors => {
-                let half_extents = self.cfg.spawn_rect_half_extents;
+            SpawnTool::RectAttractors | SpawnTool::RectOutlineAttractors => {
+                let half_extents = self.sim.cfg.spawn_rect_half_extents;
                 let corners = [
                     Vec2::new(-half_extents.x, -half_extents.y),
                     Vec2::new(half_extents.x, -half_extents.y),
@@ -457,7 +1923,7 @@ impl Viewer {
             }
 
             SpawnTool::OvalAttractors => {
-                let radii = self.cfg.spawn_oval_radii;
+                let radii = self.sim.cfg.spawn_oval_radii;
                 let segments = 64;
                 let mut pts = Vec::with_capacity(segments);
                 use std::f32::consts::TAU;
@@ -466,7 +1932,24 @@ impl Viewer {
                     let local = Vec2::new(t.cos() * radii.x, t.sin() * radii.y);
                     pts.push(self.world_to_screen(center + local, rect));
                 }
-                painter.add(egui::Shape::closed_line(pts, stroke));
+                painter.add(egui::Shape::closed_line(pts, stroke));
+            }
+
+            SpawnTool::LineAttractors => {
+                let p_screen = self.world_to_screen(center, rect);
+                painter.circle_stroke(p_screen, 4.0, stroke);
+                if let Some(start) = self.line_start {
+                    let a = self.world_to_screen(start, rect);
+                    let band = self.sim.cfg.spawn_line_jitter * self.zoom * 2.0;
+                    painter
+                        .line_segment([a, p_screen], egui::Stroke::new(band, self.theme.ink(30)));
+                    painter.line_segment([a, p_screen], stroke);
+                }
+            }
+
+            SpawnTool::Follow => {
+                let p_screen = self.world_to_screen(center, rect);
+                painter.circle_stroke(p_screen, 6.0, stroke);
             }
         }
     }
@@ -478,44 +1961,80 @@ impl Viewer {
             let rect = response.rect;
             let painter = ui.painter_at(rect);
 
+            let hover_world = response.hover_pos().map(|p| self.screen_to_world(p, rect));
+
+            // Hovering an attractor shows its weight.
+            let response = match hover_world.and_then(|p| self.attractor_near(p, 6.0 / self.zoom)) {
+                Some(i) => {
+                    let weight = self.sim.attractors.points[i].weight;
+                    response.on_hover_text_at_pointer(format!("weight {weight:.2}"))
+                }
+                None => response,
+            };
+
+            // A line in progress ends with Escape or when switching tools.
+            if self.sim.cfg.spawn_tool != SpawnTool::LineAttractors
+                || ui.input(|i| i.key_pressed(egui::Key::Escape))
+            {
+                self.line_start = None;
+            }
+
+            // In follow mode, holding the pointer steers growth instead of panning.
+            let follow = self.sim.cfg.spawn_tool == SpawnTool::Follow;
+            let target = if follow && response.is_pointer_button_down_on() {
+                hover_world
+            } else {
+                None
+            };
+            if self.sim.follow_target != target {
+                self.sim.follow_target = target;
+            }
+
             // Pan with drag.
-            if response.dragged() {
+            if response.dragged() && !follow {
                 let delta = response.drag_delta();
                 self.pan += delta;
             }
 
-            let hover_world = response.hover_pos().map(|p| self.screen_to_world(p, rect));
+            // Right click selects a node; arrows and F move the camera.
+            if response.secondary_clicked()
+                && let Some(pos) = hover_world
+            {
+                self.select_near(pos, 8.0 / self.zoom);
+            }
+            self.keyboard_camera(ui.ctx(), rect);
+            if std::mem::take(&mut self.fit_pending) {
+                self.fit_view(rect);
+            }
 
-            // Handle click-based spawning.
+            // Shift+click toggles the lock of an attractor; plain clicks spawn.
+            let shift = ui.input(|i| i.modifiers.shift);
             if response.clicked()
+                && shift
+                && let Some(center) = hover_world
+            {
+                self.toggle_lock_near(center, 6.0 / self.zoom);
+            } else if response.clicked()
                 && let Some(center) = hover_world
             {
-                match self.cfg.spawn_tool {
+                match self.sim.cfg.spawn_tool {
                     SpawnTool::RootNode => {
-                        let id = self.tree.add_free_node(center, 1.0);
-                        self.last_new_ids.clear();
-                        self.last_new_ids.push(id);
+                        let id = self.sim.add_root(center, 1.0);
+                        self.highlight_new(&[id]);
                     }
 
-                    SpawnTool::RectAttractors => {
-                        let new_set = AttractorSet::random_in_rect(
-                            center,
-                            self.cfg.spawn_rect_half_extents,
-                            self.cfg.spawn_attractors,
-                            &mut self.rng,
-                        );
-                        self.attractors.points.extend(new_set.points);
+                    SpawnTool::RectAttractors
+                    | SpawnTool::OvalAttractors
+                    | SpawnTool::RectOutlineAttractors => {
+                        let added = self.sim.spawn_attractors(center, &mut self.rng);
+                        self.weigh_newest_attractors(added);
                     }
 
-                    SpawnTool::OvalAttractors => {
-                        let new_set = AttractorSet::random_in_oval(
-                            center,
-                            self.cfg.spawn_oval_radii,
-                            self.cfg.spawn_attractors,
-                            &mut self.rng,
-                        );
-                        self.attractors.points.extend(new_set.points);
+                    SpawnTool::LineAttractors => {
+                        self.place_line_point(center);
                     }
+
+                    SpawnTool::Follow => {}
                 }
             }
 
@@ -538,37 +2057,238 @@ impl Viewer {
                 }
             }
 
-            // Draw tree edges.
-            for node in self.tree.nodes.iter() {
-                for &child in &node.children {
-                    let a = self.world_to_screen(node.pos, rect);
-                    let b = self.world_to_screen(self.tree.nodes[child].pos, rect);
-                    painter
-                        .line_segment([a, b], egui::Stroke::new(1.0, egui::Color32::LIGHT_GREEN));
-                }
+            painter.rect_filled(rect, 0.0, self.theme.background_color());
+            if self.theme.show_grid {
+                self.draw_grid(&painter, rect);
             }
 
-            // Draw tree nodes (highlighting newly added nodes in red).
-            for (i, node) in self.tree.nodes.iter().enumerate() {
-                let p = self.world_to_screen(node.pos, rect);
-                let r = (node.radius * self.zoom).max(2.0);
+            self.refresh_derived();
 
-                let color = if self.last_new_ids.contains(&i) {
-                    egui::Color32::RED
+            // Draw either the live tree or the recorded frame being scrubbed.
+            let scrubbed = self.scrubbed_tree();
+            let tree = scrubbed.as_ref().unwrap_or(&self.sim.tree);
+            let scrubbed_edges: EdgeBuffer;
+            let tree_edges = match &scrubbed {
+                Some(tree) => {
+                    scrubbed_edges = tree.edge_buffer();
+                    scrubbed_edges.segments()
+                }
+                None => self.derived.edges.segments(),
+            };
+            let scrubbed_radii: Vec<f32>;
+            let radii = match &scrubbed {
+                Some(tree) => {
+                    scrubbed_radii = tree.model_radii(self.radius_model, self.radius_params);
+                    &scrubbed_radii[..]
+                }
+                None => &self.derived.radii[..],
+            };
+
+            // Nodes are drawn back-to-front by z, with deeper nodes shifted
+            // slightly further along the pan for a parallax depth cue.
+            let z = Self::z_values(tree, self.z_from);
+            let z_max = z.iter().copied().fold(1.0, f32::max);
+            let order = Self::draw_order(&z);
+            let node_screen = |i: NodeId| {
+                self.world_to_screen(tree.nodes[i].pos, rect) + self.pan * (PARALLAX * z[i] / z_max)
+            };
+
+            // Level of detail only applies to the flat, uncolored edge path.
+            let lod = self.z_from == ZSource::None
+                && !self.branch_gradient
+                && !self.color_trees
+                && tree_edges.len() > self.lod_budget;
+
+            // Optional per-node coloring (trunk-to-tip gradient, or one hue
+            // per tree); each edge takes its child's color.
+            let gradient = if self.branch_gradient {
+                Some(self.gradient_colors(tree))
+            } else if self.color_trees {
+                Some(self.component_colors(tree))
+            } else {
+                None
+            };
+
+            // Draw tree edges. Without a depth cue or gradient nothing is
+            // offset or recolored, so the flat edge list can be drawn directly.
+            let edge_stroke = egui::Stroke::new(1.0, self.theme.edge());
+            if let Some(colors) = &gradient {
+                for &i in &order {
+                    if let Some(parent) = tree.nodes[i].parent {
+                        painter.line_segment(
+                            [node_screen(parent), node_screen(i)],
+                            egui::Stroke::new(1.0, colors[i]),
+                        );
+                    }
+                }
+            } else if self.z_from == ZSource::None {
+                // Huge trees are drawn from a decimated edge set.
+                let decimated;
+                let edges = if lod {
+                    decimated = tree.decimate(self.lod_budget);
+                    &decimated[..]
                 } else {
-                    egui::Color32::LIGHT_BLUE
+                    tree_edges
                 };
-
-                painter.circle_filled(p, r, color);
+                for &(a, b) in edges {
+                    painter.line_segment(
+                        [self.world_to_screen(a, rect), self.world_to_screen(b, rect)],
+                        edge_stroke,
+                    );
+                }
+            } else {
+                for &i in &order {
+                    if let Some(parent) = tree.nodes[i].parent {
+                        painter.line_segment([node_screen(parent), node_screen(i)], edge_stroke);
+                    }
+                }
             }
 
-            // Draw alive attractors.
-            for a in &self.attractors.points {
-                if !a.alive {
+            // Draw tree nodes (newly added nodes fade from the highlight
+            // color). Under LOD only the highlighted ones are drawn.
+            for &i in &order {
+                if lod && !self.new_node_births.contains_key(&i) {
                     continue;
                 }
-                let p = self.world_to_screen(a.pos, rect);
-                painter.circle_filled(p, 2.0, egui::Color32::LIGHT_RED);
+                let p = node_screen(i);
+                let r = (radii[i] * self.zoom).max(2.0);
+                let base = gradient
+                    .as_ref()
+                    .map_or(self.theme.node(), |colors| colors[i]);
+                painter.circle_filled(p, r, self.node_color(i, base));
+            }
+
+            // Concentric age rings inside nodes drawn large enough to show them.
+            if self.show_rings {
+                let scrubbed_rings: Vec<u32>;
+                let ring_counts = match (&scrubbed, &self.derived.rings) {
+                    (None, Some(rings)) => &rings[..],
+                    _ => {
+                        scrubbed_rings = tree.trunk_ring_counts();
+                        &scrubbed_rings[..]
+                    }
+                };
+                let stroke = egui::Stroke::new(1.0, self.theme.rings());
+                for &i in &order {
+                    let r = radii[i] * self.zoom;
+                    if r < RING_MIN_RADIUS {
+                        continue;
+                    }
+                    let rings = ring_counts[i].min((r / RING_SPACING) as u32);
+                    let p = node_screen(i);
+                    for k in 1..=rings {
+                        painter.circle_stroke(p, r * k as f32 / (rings + 1) as f32, stroke);
+                    }
+                }
+            }
+
+            // Keep repainting while a highlight is still fading.
+            if self.highlight_fade > 0.0
+                && self
+                    .new_node_births
+                    .values()
+                    .any(|&born| self.frame_time - born < self.highlight_fade)
+            {
+                ctx.request_repaint();
+            }
+
+            // Outline the active growth tips of the live tree.
+            if self.highlight_frontier && self.scrub_frame.is_none() {
+                let stroke = egui::Stroke::new(1.5, self.theme.accent);
+                for id in phases::frontier(&self.sim.tree, self.sim.influence()) {
+                    let p = self.world_to_screen(self.sim.tree.nodes[id].pos, rect);
+                    painter.circle_stroke(p, (radii[id] * self.zoom).max(2.0) + 2.0, stroke);
+                }
+            }
+
+            // Mark the selected node and frame its subtree.
+            if let Some(id) = self.selection()
+                && self.scrub_frame.is_none()
+            {
+                let stroke = egui::Stroke::new(1.5, self.theme.ink(255));
+                let p = self.world_to_screen(self.sim.tree.nodes[id].pos, rect);
+                painter.circle_stroke(p, (radii[id] * self.zoom).max(2.0) + 3.0, stroke);
+                let (min, max) = self.sim.tree.subtree_bounds(id);
+                let bounds = egui::Rect::from_two_pos(
+                    self.world_to_screen(min, rect),
+                    self.world_to_screen(max, rect),
+                );
+                painter.rect_stroke(
+                    bounds.expand(4.0),
+                    0.0,
+                    egui::Stroke::new(1.0, self.theme.ink(80)),
+                    egui::StrokeKind::Outside,
+                );
+            }
+
+            // Debug overlay: accumulated pull per influenced node, scaled by coherence.
+            if self.show_influence && self.scrub_frame.is_none() {
+                self.draw_influence_arrows(&painter, rect);
+            }
+
+            // Ghost markers for the children the next step would grow.
+            if self.show_preview && self.scrub_frame.is_none() {
+                let ghost = self.theme.ink(90);
+                for &(parent, pos) in self.derived.preview.iter().flatten() {
+                    let a = self.world_to_screen(self.sim.tree.nodes[parent].pos, rect);
+                    let b = self.world_to_screen(pos, rect);
+                    painter.line_segment([a, b], egui::Stroke::new(1.0, ghost));
+                    painter.circle_stroke(b, 3.0, egui::Stroke::new(1.0, ghost));
+                }
+            }
+
+            // Draw alive attractors, either as points or as a density heatmap.
+            match self.attractor_view {
+                AttractorView::Points => {
+                    for a in &self.sim.attractors.points {
+                        if !a.alive {
+                            continue;
+                        }
+                        let p = self.world_to_screen(a.pos, rect);
+                        let r = Self::attractor_radius(a.weight);
+                        painter.circle_filled(p, r, self.theme.attractor());
+                    }
+
+                    // Locked guides on top, so they stay visible in dense clouds.
+                    for a in self
+                        .sim
+                        .attractors
+                        .points
+                        .iter()
+                        .filter(|a| a.alive && a.locked)
+                    {
+                        let p = self.world_to_screen(a.pos, rect);
+                        let r = Self::attractor_radius(a.weight) + 1.0;
+                        painter.circle_filled(p, r, self.theme.locked_attractor());
+                    }
+
+                    if self.highlight_unreachable {
+                        for &i in self.derived.unreachable.iter().flatten() {
+                            let p = self.world_to_screen(self.sim.attractors.points[i].pos, rect);
+                            painter.circle_filled(p, 2.5, self.theme.unreachable());
+                        }
+                    }
+                }
+
+                AttractorView::Heatmap => {
+                    let cell = self.heatmap_cell.max(1.0);
+                    let (cols, _rows, counts) = self.bin_attractors(rect, cell);
+                    let max = counts.iter().copied().max().unwrap_or(0);
+                    if max > 0 {
+                        for (i, &c) in counts.iter().enumerate() {
+                            if c == 0 {
+                                continue;
+                            }
+                            let t = c as f32 / max as f32;
+                            let alpha = (40.0 + 180.0 * t) as u8;
+                            let color = self.theme.heat(alpha);
+                            let min = rect.min
+                                + egui::vec2((i % cols) as f32 * cell, (i / cols) as f32 * cell);
+                            let cell_rect = egui::Rect::from_min_size(min, egui::vec2(cell, cell));
+                            painter.rect_filled(cell_rect, 0.0, color);
+                        }
+                    }
+                }
             }
 
             // Tool hint overlay.
@@ -596,22 +2316,37 @@ impl App for Viewer {
     /// eframe callback that builds all UI panels for each frame.
     ///
     /// This method:
+    /// - Records the frame time used for new-node highlights.
+    /// - Handles the undo shortcut (Ctrl+Z / Cmd+Z).
     /// - Renders the top control bar and status bar.
     /// - Renders the config side panel and toolbar.
     /// - Draws the central simulation view and handles interactions.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.frame_time = ctx.input(|i| i.time);
+        self.adapt_lod(ctx.input(|i| i.unstable_dt));
+
+        let undo_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+        if ctx.input_mut(|i| i.consume_shortcut(&undo_shortcut)) {
+            self.undo();
+        }
+
         self.ui_top_panel(ctx);
+        self.refresh_derived();
         self.ui_status_bar(ctx);
         self.ui_config_panel(ctx);
         self.ui_central_panel(ctx);
         self.ui_toolbar(ctx);
+        self.ui_undo_toast(ctx);
+        self.ui_sweep_window(ctx);
+        if self.advance_sweep() {
+            ctx.request_repaint();
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use egui;
     use glam::Vec2;
 
     fn test_rect() -> egui::Rect {
@@ -652,27 +2387,27 @@ mod tests {
         let mut viewer = Viewer::new();
 
         // Mutate state to make sure reset actually changes things.
-        viewer.tree.add_free_node(Vec2::new(10.0, 0.0), 1.0);
-        viewer.attractors.points.clear();
-        viewer.acc = InfluenceBuffer::with_len(0);
-        viewer.last_new_ids.push(42);
+        viewer.sim.add_root(Vec2::new(10.0, 0.0), 1.0);
+        viewer.step_once();
+        viewer.sim.attractors.points.clear();
+        viewer.new_node_births.insert(42, 0.0);
         viewer.running = true;
 
         viewer.reset();
 
         // Tree should have exactly one root node again.
-        assert_eq!(viewer.tree.nodes.len(), 1);
-        assert!(viewer.tree.nodes[0].parent.is_none());
+        assert_eq!(viewer.sim.tree.nodes.len(), 1);
+        assert!(viewer.sim.tree.nodes[0].parent.is_none());
 
         // Attractors are regenerated; the exact positions don't matter,
         // but the count should match the hard-coded value in reset.
-        assert_eq!(viewer.attractors.points.len(), 1000);
+        assert_eq!(viewer.sim.attractors.points.len(), 1000);
 
-        // Influence buffer should be sized to the number of nodes.
-        assert_eq!(viewer.acc.count.len(), viewer.tree.nodes.len());
+        // The simulation should start over from step zero.
+        assert_eq!(viewer.sim.stats().steps, 0);
 
         // No "last new" nodes after reset.
-        assert!(viewer.last_new_ids.is_empty());
+        assert!(viewer.new_node_births.is_empty());
 
         // Simulation should not be running after reset.
         assert!(!viewer.running);
@@ -683,57 +2418,602 @@ mod tests {
         let mut viewer = Viewer::new();
 
         // Populate the viewer so that `clear` actually removes content.
-        assert!(!viewer.tree.nodes.is_empty());
-        assert!(!viewer.attractors.points.is_empty());
-        assert!(viewer.acc.count.len() > 0);
+        assert!(!viewer.sim.tree.nodes.is_empty());
+        assert!(!viewer.sim.attractors.points.is_empty());
+        viewer.step_once();
+        assert!(!viewer.sim.influence().count.is_empty());
 
-        viewer.last_new_ids.push(0);
+        viewer.new_node_births.insert(0, 0.0);
 
         viewer.clear();
 
-        assert!(viewer.tree.nodes.is_empty());
-        assert!(viewer.attractors.points.is_empty());
-        assert_eq!(viewer.acc.count.len(), 0);
-        assert!(viewer.last_new_ids.is_empty());
+        assert!(viewer.sim.tree.nodes.is_empty());
+        assert!(viewer.sim.attractors.points.is_empty());
+        assert_eq!(viewer.sim.influence().count.len(), 0);
+        assert!(viewer.new_node_births.is_empty());
+    }
+
+    #[test]
+    fn auto_compact_drops_dead_attractors_once_they_dominate() {
+        let mut viewer = Viewer::new();
+        viewer.auto_compact = true;
+        let total = viewer.sim.attractors.points.len();
+        for a in viewer.sim.attractors.points.iter_mut().take(total / 2) {
+            a.alive = false;
+        }
+
+        // Exactly half dead is not enough.
+        viewer.sim.cfg.kill_radius = 0.0;
+        viewer.step_once();
+        assert_eq!(viewer.sim.attractors.points.len(), total);
+
+        viewer.sim.attractors.points[total - 1].alive = false;
+        viewer.step_once();
+        assert_eq!(viewer.sim.attractors.dead_count(), 0);
+        assert_eq!(viewer.sim.attractors.points.len(), total / 2 - 1);
+    }
+
+    #[test]
+    fn radius_model_sets_drawn_widths_without_changing_the_tree() {
+        let mut viewer = Viewer::new();
+        viewer.radius_model = RadiusModel::Strahler;
+        for _ in 0..5 {
+            viewer.step_once();
+        }
+        let grown: Vec<f32> = viewer.sim.tree.nodes.iter().map(|n| n.radius).collect();
+        viewer.refresh_derived();
+        let orders = viewer.sim.tree.strahler_numbers();
+        for (&width, order) in viewer.derived.radii.iter().zip(orders) {
+            let expected = 0.5 * 2f32.powf((order - 1) as f32 / 2.5);
+            assert!((width - expected).abs() < 1e-6);
+        }
+        let stored: Vec<f32> = viewer.sim.tree.nodes.iter().map(|n| n.radius).collect();
+        assert_eq!(stored, grown);
+
+        // Switching the model refreshes the widths without a new revision.
+        viewer.radius_model = RadiusModel::Inherit;
+        viewer.refresh_derived();
+        assert_eq!(viewer.derived.radii, grown);
+    }
+
+    #[test]
+    fn auto_run_stops_once_growth_has_converged() {
+        let mut viewer = Viewer::new();
+        viewer.running = true;
+        viewer.step_once();
+        assert!(viewer.running);
+
+        // Nothing left to grow toward.
+        viewer.sim.attractors.points.clear();
+        viewer.step_once();
+        assert!(!viewer.running);
     }
 
     #[test]
-    fn step_once_creates_child_and_updates_last_new_ids() {
+    fn step_once_creates_child_and_highlights_it() {
         let mut viewer = Viewer::new();
 
         // Override the random setup with a deterministic scenario:
         // - one root at (0, 0)
         // - a single attractor at (10, 0)
         // - influence radius large enough to see the attractor
-        viewer.tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
-        viewer.attractors = AttractorSet::from_positions(vec![Vec2::new(10.0, 0.0)]);
-        viewer.acc = InfluenceBuffer::with_len(viewer.tree.nodes.len());
-
-        viewer.cfg = Config::default();
-        viewer.cfg.influence_radius = 20.0;
-        viewer.cfg.kill_radius = 1.0; // small: do not kill the attractor
-        viewer.cfg.attract_from_kn = 0;
-        viewer.cfg.kill_from_kn = 0;
-        viewer.cfg.tropism = Vec2::new(0.0, 0.0);
-        viewer.cfg.step_len = 2.0;
+        viewer.sim.tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        viewer.sim.attractors = AttractorSet::from_positions(vec![Vec2::new(10.0, 0.0)]);
+
+        viewer.sim.cfg = Config::default();
+        viewer.sim.cfg.influence_radius = 20.0;
+        viewer.sim.cfg.kill_radius = 1.0; // small: do not kill the attractor
+        viewer.sim.cfg.attract_from_kn = 0;
+        viewer.sim.cfg.kill_from_kn = 0;
+        viewer.sim.cfg.tropism = Vec2::new(0.0, 0.0);
+        viewer.sim.cfg.step_len = 2.0;
 
         viewer.step_once();
 
         // Exactly one new node should be created.
-        assert_eq!(viewer.last_new_ids.len(), 1);
-        let new_id = viewer.last_new_ids[0];
+        assert_eq!(viewer.new_node_births.len(), 1);
+        let new_id = *viewer.new_node_births.keys().next().unwrap();
         assert_eq!(new_id, 1);
-        assert_eq!(viewer.tree.nodes.len(), 2);
+        assert_eq!(viewer.sim.tree.nodes.len(), 2);
 
-        let new_node = &viewer.tree.nodes[new_id];
+        let new_node = &viewer.sim.tree.nodes[new_id];
 
         // Direction from (0, 0) to (10, 0) is (1, 0); step_len = 2.0 -> new pos (2, 0).
         assert_eq!(new_node.pos, Vec2::new(2.0, 0.0));
 
         // Radius should be inherited from the parent.
-        assert_eq!(new_node.radius, viewer.tree.nodes[0].radius);
+        assert_eq!(new_node.radius, viewer.sim.tree.nodes[0].radius);
 
         // The attractor should still be alive (kill radius is too small).
-        assert!(viewer.attractors.points[0].alive);
+        assert!(viewer.sim.attractors.points[0].alive);
+    }
+
+    #[test]
+    fn highlight_fades_over_duration_and_is_one_step_when_zero() {
+        let mut viewer = Viewer::new();
+        let blue = egui::Color32::LIGHT_BLUE;
+
+        viewer.highlight_fade = 0.0;
+        viewer.highlight_new(&[1]);
+        viewer.frame_time = 10.0;
+        assert_eq!(viewer.node_color(1, blue), viewer.theme.highlight());
+        viewer.highlight_new(&[2]);
+        assert_eq!(viewer.node_color(1, blue), blue);
+        assert_eq!(viewer.node_color(2, blue), viewer.theme.highlight());
+
+        viewer.highlight_fade = 1.0;
+        viewer.highlight_new(&[3]);
+        viewer.frame_time = 10.5;
+        let mid = viewer.node_color(3, blue);
+        assert_ne!(mid, viewer.theme.highlight());
+        assert_ne!(mid, blue);
+
+        viewer.frame_time = 11.0;
+        assert_eq!(viewer.node_color(3, blue), blue);
+        viewer.highlight_new(&[4]);
+        assert!(!viewer.new_node_births.contains_key(&3));
+        assert_eq!(viewer.node_color(4, blue), viewer.theme.highlight());
+    }
+
+    #[test]
+    fn gradient_runs_trunk_to_tip_and_composes_with_highlight() {
+        let mut viewer = Viewer::new();
+        viewer.sim.tree = Tree::new(Vec2::ZERO, 1.0);
+        let tip = viewer.sim.tree.add_child(0, Vec2::new(0.0, 2.0), 1.0);
+
+        let colors = viewer.gradient_colors(&viewer.sim.tree);
+        assert_eq!(colors, vec![viewer.gradient_root, viewer.gradient_tip]);
+
+        // A fresh node starts red and fades into its gradient color.
+        viewer.highlight_fade = 1.0;
+        viewer.frame_time = 5.0;
+        viewer.highlight_new(&[tip]);
+        assert_eq!(
+            viewer.node_color(tip, colors[tip]),
+            viewer.theme.highlight()
+        );
+        viewer.frame_time = 6.0;
+        assert_eq!(viewer.node_color(tip, colors[tip]), viewer.gradient_tip);
+    }
+
+    #[test]
+    fn component_colors_give_each_tree_its_own_hue() {
+        let mut viewer = Viewer::new();
+        viewer.sim.tree = Tree::new(Vec2::ZERO, 1.0);
+        let other = viewer.sim.tree.add_free_node(Vec2::new(10.0, 0.0), 1.0);
+        viewer.sim.tree.add_child(0, Vec2::new(0.0, 2.0), 1.0);
+        viewer.sim.tree.add_child(other, Vec2::new(10.0, 2.0), 1.0);
+
+        let colors = viewer.component_colors(&viewer.sim.tree);
+        assert_eq!(colors[0], colors[2]);
+        assert_eq!(colors[1], colors[3]);
+        assert_ne!(colors[0], colors[1]);
+    }
+
+    #[test]
+    fn lod_budget_follows_frame_time_within_bounds() {
+        let mut viewer = Viewer::new();
+        assert_eq!(viewer.lod_budget, LOD_MAX_EDGES);
+
+        viewer.adapt_lod(0.1);
+        assert_eq!(viewer.lod_budget, LOD_MAX_EDGES * 4 / 5);
+        for _ in 0..100 {
+            viewer.adapt_lod(0.1);
+        }
+        assert_eq!(viewer.lod_budget, LOD_MIN_EDGES);
+
+        viewer.adapt_lod(1.0 / 40.0);
+        assert_eq!(viewer.lod_budget, LOD_MIN_EDGES);
+        for _ in 0..100 {
+            viewer.adapt_lod(0.001);
+        }
+        assert_eq!(viewer.lod_budget, LOD_MAX_EDGES);
+    }
+
+    #[test]
+    fn zoom_to_selection_frames_the_selected_subtree() {
+        let mut viewer = Viewer::new();
+        let mut tree = Tree::new(Vec2::ZERO, 1.0);
+        let a = tree.add_child(0, Vec2::new(100.0, 100.0), 1.0);
+        tree.add_child(a, Vec2::new(300.0, 100.0), 1.0);
+        tree.add_child(a, Vec2::new(100.0, 200.0), 1.0);
+        viewer.sim.tree = tree;
+        let rect = test_rect();
+
+        assert!(!viewer.zoom_to_selection(rect));
+        assert_eq!(viewer.select_near(Vec2::new(50.0, 50.0), 2.0), None);
+        assert_eq!(viewer.select_near(Vec2::new(101.0, 99.0), 2.0), Some(a));
+        assert!(viewer.zoom_to_selection(rect));
+
+        // 200 x 100 world units into 800 x 600 pixels, limited by the width.
+        assert!((viewer.zoom - 800.0 / 200.0 * ZOOM_TO_FILL).abs() < 1e-4);
+        let center = viewer.world_to_screen(Vec2::new(200.0, 150.0), rect);
+        assert!((center - rect.center()).length() < 1e-3);
+
+        // The selection is dropped once the node no longer exists.
+        viewer.clear();
+        assert_eq!(viewer.selection(), None);
+    }
+
+    #[test]
+    fn key_pan_moves_by_speed_and_center_resets_the_camera() {
+        let left = egui::vec2(1.0, 0.0);
+        let pan = Viewer::key_pan(egui::Vec2::ZERO, left, false, 0.5);
+        assert_eq!(pan, egui::vec2(KEY_PAN_SPEED * 0.5, 0.0));
+
+        let down = egui::vec2(0.0, -1.0);
+        let fast = Viewer::key_pan(pan, down, true, 0.25);
+        assert_eq!(
+            fast,
+            egui::vec2(KEY_PAN_SPEED * 0.5, -KEY_PAN_SPEED * KEY_PAN_FAST * 0.25)
+        );
+        assert_eq!(Viewer::key_pan(fast, egui::Vec2::ZERO, true, 1.0), fast);
+
+        let mut viewer = Viewer::new();
+        viewer.pan = fast;
+        viewer.zoom = 7.5;
+        viewer.center_view();
+        assert_eq!(viewer.pan, egui::Vec2::ZERO);
+        assert_eq!(viewer.zoom, DEFAULT_ZOOM);
+    }
+
+    #[test]
+    fn fit_view_frames_the_whole_tree() {
+        let mut viewer = Viewer::new();
+        let rect = test_rect();
+        viewer.sim.tree = Tree { nodes: Vec::new() };
+        assert!(!viewer.fit_view(rect));
+
+        let mut tree = Tree::new(Vec2::new(-50.0, 0.0), 1.0);
+        tree.add_child(0, Vec2::new(50.0, 300.0), 1.0);
+        viewer.sim.tree = tree;
+        assert!(viewer.fit_view(rect));
+
+        // 100 x 300 world units into 800 x 600 pixels, limited by the height.
+        assert!((viewer.zoom - 600.0 / 300.0 * ZOOM_TO_FILL).abs() < 1e-4);
+        let center = viewer.world_to_screen(Vec2::new(0.0, 150.0), rect);
+        assert!((center - rect.center()).length() < 1e-3);
+        for p in [Vec2::new(-50.0, 0.0), Vec2::new(50.0, 300.0)] {
+            assert!(rect.contains(viewer.world_to_screen(p, rect)));
+        }
+    }
+
+    #[test]
+    fn same_seed_produces_identical_attractors() {
+        let positions = |v: &Viewer| {
+            v.sim
+                .attractors
+                .points
+                .iter()
+                .map(|a| a.pos)
+                .collect::<Vec<_>>()
+        };
+        let a = Viewer::with_seed(7);
+        let b = Viewer::with_seed(7);
+        assert_eq!(positions(&a), positions(&b));
+        assert_ne!(positions(&a), positions(&Viewer::with_seed(8)));
+
+        // Reset regenerates the same cloud from the seed.
+        let mut c = Viewer::with_seed(7);
+        c.step_once();
+        c.reset();
+        assert_eq!(positions(&c), positions(&a));
+    }
+
+    #[test]
+    fn attractor_radius_follows_weight_within_limits() {
+        assert_eq!(Viewer::attractor_radius(1.0), ATTRACTOR_RADIUS);
+        assert!(Viewer::attractor_radius(2.0) > Viewer::attractor_radius(1.0));
+        assert_eq!(Viewer::attractor_radius(100.0), ATTRACTOR_RADIUS_MAX);
+        assert_eq!(Viewer::attractor_radius(0.0), 1.0);
+    }
+
+    #[test]
+    fn line_tool_spawns_between_consecutive_points() {
+        let mut viewer = Viewer::new();
+        viewer.sim.attractors.points.clear();
+        viewer.sim.cfg.spawn_attractors = 20;
+        viewer.spawn_weight = 2.0;
+
+        assert_eq!(viewer.place_line_point(Vec2::new(0.0, 0.0)), 0);
+        assert_eq!(viewer.place_line_point(Vec2::new(0.0, 50.0)), 20);
+        assert_eq!(viewer.place_line_point(Vec2::new(50.0, 50.0)), 20);
+
+        assert_eq!(viewer.line_start, Some(Vec2::new(50.0, 50.0)));
+        assert_eq!(viewer.sim.attractors.points.len(), 40);
+        assert!(viewer.sim.attractors.points.iter().all(|a| a.weight == 2.0));
+        let jitter = viewer.sim.cfg.spawn_line_jitter;
+        for a in &viewer.sim.attractors.points[..20] {
+            assert!(a.pos.x.abs() <= jitter);
+        }
+    }
+
+    #[test]
+    fn toggle_lock_near_locks_nearest_attractor_in_range() {
+        let mut viewer = Viewer::new();
+        viewer.sim.attractors =
+            AttractorSet::from_positions(vec![Vec2::new(0.0, 0.0), Vec2::new(3.0, 0.0)]);
+
+        assert_eq!(viewer.toggle_lock_near(Vec2::new(2.5, 0.0), 1.0), Some(1));
+        assert!(viewer.sim.attractors.points[1].locked);
+        assert!(!viewer.sim.attractors.points[0].locked);
+
+        assert_eq!(viewer.toggle_lock_near(Vec2::new(3.0, 0.5), 1.0), Some(1));
+        assert!(!viewer.sim.attractors.points[1].locked);
+
+        assert_eq!(viewer.toggle_lock_near(Vec2::new(10.0, 0.0), 1.0), None);
+    }
+
+    #[test]
+    fn bin_attractors_counts_only_alive_points_inside_rect() {
+        let mut viewer = Viewer::new();
+        viewer.zoom = 1.0;
+        viewer.pan = egui::vec2(0.0, 0.0);
+        let rect = test_rect();
+
+        // Rect center maps to world (0, 0); two points share a cell, one is dead,
+        // and one lies far outside the visible area.
+        viewer.sim.attractors = AttractorSet::from_positions(vec![
+            Vec2::new(1.0, -1.0),
+            Vec2::new(2.0, -2.0),
+            Vec2::new(3.0, -3.0),
+            Vec2::new(5000.0, 0.0),
+        ]);
+        viewer.sim.attractors.points[2].alive = false;
+
+        let (cols, rows, counts) = viewer.bin_attractors(rect, 16.0);
+
+        assert_eq!(cols, 50);
+        assert_eq!(rows, 38);
+        assert_eq!(counts.len(), cols * rows);
+        assert_eq!(counts.iter().sum::<u32>(), 2);
+        assert_eq!(counts.iter().copied().max(), Some(2));
+    }
+
+    #[test]
+    fn export_png_writes_png_file() {
+        let mut viewer = Viewer::new();
+        let path = std::env::temp_dir().join("sca_viewer_export_test.png");
+
+        viewer.export_png(&path).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+    }
+
+    #[test]
+    fn export_svg_writes_svg_file() {
+        let mut viewer = Viewer::new();
+        viewer.step_once();
+        let path = std::env::temp_dir().join("sca_viewer_export_test.svg");
+
+        viewer.export_svg(&path).unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(text.starts_with("<svg"));
+        assert_eq!(
+            text.matches("<line").count(),
+            viewer.sim.tree.edges().count()
+        );
+    }
+
+    #[test]
+    fn export_csv_writes_node_and_edge_tables() {
+        let mut viewer = Viewer::new();
+        viewer.step_once();
+        let dir = std::env::temp_dir();
+        let (nodes_path, edges_path) = (
+            dir.join("sca_viewer_nodes_test.csv"),
+            dir.join("sca_viewer_edges_test.csv"),
+        );
+
+        viewer.export_csv(&nodes_path, &edges_path).unwrap();
+
+        let nodes = std::fs::read_to_string(&nodes_path).unwrap();
+        let edges = std::fs::read_to_string(&edges_path).unwrap();
+        std::fs::remove_file(&nodes_path).ok();
+        std::fs::remove_file(&edges_path).ok();
+        assert_eq!(nodes.lines().count(), 1 + viewer.sim.tree.nodes.len());
+        assert_eq!(edges.lines().count(), 1 + viewer.sim.tree.edges().count());
+    }
+
+    #[test]
+    fn z_sources_order_nodes_back_to_front() {
+        let mut tree = Tree::new(Vec2::new(0.0, 0.0), 1.0);
+        let a = tree.add_child(0, Vec2::new(0.0, 1.0), 1.0);
+        tree.add_child(a, Vec2::new(0.0, 2.0), 1.0);
+        let free = tree.add_free_node(Vec2::new(5.0, 0.0), 1.0);
+        tree.add_child(free, Vec2::new(5.0, 1.0), 1.0);
+
+        let depth = Viewer::z_values(&tree, ZSource::Depth);
+        assert_eq!(depth, vec![0.0, 1.0, 2.0, 0.0, 1.0]);
+        assert_eq!(Viewer::draw_order(&depth), tree.depth_order());
+
+        // Without a z source, or by birth, nodes are drawn in id order.
+        for source in [ZSource::None, ZSource::BornStep] {
+            let z = Viewer::z_values(&tree, source);
+            assert_eq!(Viewer::draw_order(&z), vec![0, 1, 2, 3, 4]);
+        }
+    }
+
+    #[test]
+    fn drawing_a_frame_does_not_count_as_a_simulation_change() {
+        let mut viewer = Viewer::new();
+        viewer.show_preview = true;
+        viewer.highlight_unreachable = true;
+        let ctx = egui::Context::default();
+        let frame = |viewer: &mut Viewer| {
+            let _ = ctx.run(egui::RawInput::default(), |ctx| {
+                viewer.ui_top_panel(ctx);
+                viewer.ui_status_bar(ctx);
+                viewer.ui_config_panel(ctx);
+                viewer.ui_central_panel(ctx);
+                viewer.ui_toolbar(ctx);
+            });
+        };
+
+        frame(&mut viewer);
+        let revision = viewer.sim.revision;
+        frame(&mut viewer);
+        assert_eq!(viewer.sim.revision, revision);
+    }
+
+    #[test]
+    fn derived_overlays_are_recomputed_after_simulation_changes() {
+        let mut viewer = Viewer::new();
+        viewer.highlight_unreachable = true;
+        viewer.refresh_derived();
+        let alive = viewer.sim.stats().alive_attractors;
+        assert!(viewer.derived.unreachable.as_ref().unwrap().len() < alive);
+        assert!(viewer.derived.preview.is_none());
+
+        for a in &mut viewer.sim.attractors.points {
+            a.pos.x += 10_000.0;
+        }
+        viewer.refresh_derived();
+        assert_eq!(viewer.derived.unreachable.as_ref().unwrap().len(), alive);
+
+        viewer.step_once();
+        viewer.show_preview = true;
+        viewer.refresh_derived();
+        assert_eq!(viewer.derived.tree_stats, viewer.sim.tree.stats());
+        assert_eq!(
+            viewer.derived.preview.as_deref(),
+            Some(&viewer.sim.preview_growth()[..])
+        );
+    }
+
+    #[test]
+    fn sweep_grows_across_frames_and_keeps_its_parameter() {
+        let mut viewer = Viewer::new();
+        viewer.sweep.samples = 3;
+        viewer.sweep.steps = 5;
+        viewer.sweep_run = Some(SweepRun::new(viewer.sim.cfg, viewer.sweep));
+        viewer.sweep.param = SweepParam::StepLen;
+
+        assert!(viewer.advance_sweep());
+        assert!(viewer.advance_sweep());
+        assert!(!viewer.advance_sweep());
+        assert!(!viewer.advance_sweep());
+        let run = viewer.sweep_run.as_ref().unwrap();
+        assert_eq!(run.thumbnails.len(), 3);
+        assert_eq!(run.settings.param, SweepParam::TropismY);
+    }
+
+    #[test]
+    fn cached_edges_follow_direct_node_edits() {
+        let mut viewer = Viewer::new();
+        for _ in 0..5 {
+            viewer.step_once();
+        }
+        viewer.refresh_derived();
+        let edges: Vec<_> = viewer.sim.tree.edges().collect();
+        assert!(!edges.is_empty());
+        assert_eq!(viewer.derived.edges.segments(), &edges[..]);
+
+        // Writing `nodes` directly bypasses every `Tree` method.
+        let last = viewer.sim.tree.nodes.len() - 1;
+        viewer.sim.tree.nodes[last].pos += Vec2::new(3.0, 0.0);
+        viewer.refresh_derived();
+        assert_eq!(viewer.derived.edges, viewer.sim.tree.edge_buffer());
+        assert_ne!(viewer.derived.edges.segments(), &edges[..]);
+    }
+
+    #[test]
+    fn ring_counts_are_cached_until_the_tree_changes() {
+        let mut viewer = Viewer::new();
+        viewer.refresh_derived();
+        assert!(viewer.derived.rings.is_none());
+
+        viewer.show_rings = true;
+        viewer.refresh_derived();
+        assert_eq!(
+            viewer.derived.rings.as_deref(),
+            Some(&viewer.sim.tree.trunk_ring_counts()[..])
+        );
+
+        viewer.step_once();
+        viewer.refresh_derived();
+        assert_eq!(
+            viewer.derived.rings.as_deref(),
+            Some(&viewer.sim.tree.trunk_ring_counts()[..])
+        );
+    }
+
+    #[test]
+    fn file_actions_write_to_output_dir_and_report_failures() {
+        let mut viewer = Viewer::new();
+        let dir = std::env::temp_dir();
+        viewer.output_dir = dir.to_string_lossy().into_owned();
+
+        viewer.run_file_action("Exported", "sca_viewer_action_test.svg", |v, path| {
+            v.export_svg(path)
+        });
+        let path = dir.join("sca_viewer_action_test.svg");
+        assert!(path.exists());
+        std::fs::remove_file(&path).ok();
+        assert!(matches!(viewer.file_status, Some(Ok(_))));
+
+        viewer.output_dir = dir.join("sca_missing_dir").to_string_lossy().into_owned();
+        viewer.run_file_action("Exported", "tree.svg", |v, path| v.export_svg(path));
+        assert!(matches!(viewer.file_status, Some(Err(_))));
+    }
+
+    #[test]
+    fn load_restores_saved_state_and_can_be_undone() {
+        let mut viewer = Viewer::new();
+        viewer.step_once();
+        let nodes = viewer.sim.tree.nodes.len();
+        let path = std::env::temp_dir().join("sca_viewer_state_test.json");
+        viewer.save_state(&path).unwrap();
+
+        viewer.clear();
+        viewer.load_state(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(viewer.sim.tree.nodes.len(), nodes);
+        assert_eq!(viewer.recorder.frame_count(), 1);
+        assert!(viewer.undo());
+        assert!(viewer.sim.tree.nodes.is_empty());
+    }
+
+    #[test]
+    fn failed_load_is_reported_and_keeps_the_simulation() {
+        let mut viewer = Viewer::new();
+        viewer.step_once();
+        let nodes = viewer.sim.tree.nodes.len();
+        let dir = std::env::temp_dir().join("sca_viewer_bad_state");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(STATE_FILE), "{\"version\":1}").unwrap();
+        viewer.output_dir = dir.to_string_lossy().into_owned();
+
+        viewer.run_file_action("Loaded", STATE_FILE, |v, path| v.load_state(path));
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(matches!(&viewer.file_status, Some(Err(msg)) if msg.contains(STATE_FILE)));
+        assert_eq!(viewer.sim.tree.nodes.len(), nodes);
+        assert!(!viewer.undo());
+    }
+
+    #[test]
+    fn clear_then_undo_restores_counts() {
+        let mut viewer = Viewer::new();
+        viewer.step_once();
+        let nodes = viewer.sim.tree.nodes.len();
+        let attractors = viewer.sim.attractors.points.len();
+        let frames = viewer.recorder.frame_count();
+
+        viewer.clear();
+        assert!(viewer.sim.tree.nodes.is_empty());
+
+        assert!(viewer.undo());
+        assert_eq!(viewer.sim.tree.nodes.len(), nodes);
+        assert_eq!(viewer.sim.attractors.points.len(), attractors);
+        assert_eq!(viewer.recorder.frame_count(), frames);
+
+        // Nothing left to undo.
+        assert!(!viewer.undo());
     }
 }